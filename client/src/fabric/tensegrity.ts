@@ -201,11 +201,12 @@ export class Tensegrity {
 
     public removeInterval(interval: IInterval): void {
         const index = interval.index
+        const last = this.intervals.length - 1
         this.intervals = this.intervals.filter(existing => existing.index !== index)
         this.fabric.remove_interval(index)
         this.intervals.forEach(existing => {
-            if (existing.index > index) {
-                existing.index--
+            if (existing.index === last) {
+                existing.index = index
             }
         })
         interval.removed = true
@@ -64,6 +64,9 @@ export function featureMapping(feature: WorldFeature): IFeatureMapping {
         case WorldFeature.PretensingCountdown:
             // percents: [50, 75, 90, 100, 125, 150, 200],
             return linearMapping(feature, "-Pretenst countdown", FeatureStage.All, 50, 200)
+        case WorldFeature.ShapingSlewRate:
+            // percents: [10, 50, 100, 200, 500, 1000],
+            return linearMapping(feature, "-Shaping Slew Rate", FeatureStage.Preslack, 10, 1000)
         case WorldFeature.VisualStrain:
             // percents: [0, 10, 50, 100, 200, 300, 500, 1000],
             return linearMapping(feature, "Visual strain", FeatureStage.All, 0, 300)
@@ -0,0 +1,966 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use crate::real::consts::{FRAC_PI_2, TAU};
+use crate::real::Real;
+
+use nalgebra::*;
+
+use crate::constants::{AlarmLevel, IntervalRole, Stage};
+use crate::error::Error;
+use crate::fabric::Fabric;
+use crate::interval::Interval;
+use crate::role::RoleDefaults;
+use crate::skin;
+use crate::world::World;
+
+const SWEEP_AMPLITUDE: Real = 0.001;
+const SETTLE_CYCLES: Real = 5.0;
+const MEASURE_CYCLES: Real = 3.0;
+const MIN_SETTLE_TICKS: u32 = 200;
+const MAX_SWEEP_TICKS: u32 = 100_000;
+const REGISTRATION_ITERATIONS: usize = 50;
+const REGISTRATION_CONVERGED: Real = 1e-7;
+
+#[derive(Clone, Copy, Debug)]
+pub struct TensioningRow {
+    pub interval: usize,
+    pub target: Real,
+    // tension right after this cable was stressed and the fabric settled
+    pub installed: Real,
+    // tension once every later cable had been stressed too
+    pub remaining: Real,
+    // what to stress this cable to instead, so that it ends at the target
+    pub compensated: Real,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Element {
+    Joint(usize),
+    Interval(usize),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Divergence {
+    pub tick: u32,
+    pub element: Element,
+    // location distance for a joint, strain difference for an interval
+    pub difference: Real,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Redundancy {
+    pub interval: usize,
+    // furthest any joint ended up from the intact shape
+    pub deviation: Real,
+    // biggest strain change among the intervals left standing, and which one it was
+    pub redistribution: Real,
+    pub most_affected: Option<usize>,
+}
+
+// Compounded per simulated year.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AgingCurve {
+    // fraction of the stiffness lost
+    pub stiffness_loss: Real,
+    // fraction the rest length grows, negative for shrinkage
+    pub creep: Real,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CreepPoint {
+    // in whatever unit the creep rate is per
+    pub time: Real,
+    // furthest any joint has dropped since the start, and which one
+    pub sag: Real,
+    pub joint: usize,
+    pub mean_pull_strain: Real,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Inspection {
+    pub interval: usize,
+    pub year: Real,
+    pub level: AlarmLevel,
+    pub strain: Real,
+}
+
+#[derive(Clone, Debug)]
+pub struct Registration {
+    // takes scanned points onto the simulation
+    pub transform: Isometry3<Real>,
+    // per joint, distance to the nearest registered scan point
+    pub residuals: Vec<Real>,
+    pub rms: Real,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Structure {
+    // pushes meeting end to end in nearly a straight line, in order along the column
+    Column(Vec<usize>),
+    // pulls sharing a tag that close a loop, in order around it
+    Hoop(Vec<usize>),
+}
+
+#[derive(Clone, Debug)]
+pub struct SweepPoint {
+    pub frequency: Real,
+    // response amplitude over excitation amplitude, for each monitored joint
+    pub gains: Vec<Real>,
+}
+
+// Shake every anchor sideways at frequencies (cycles per tick) spaced geometrically from start to end,
+// on a fresh copy each time, and record the steady-state amplitude of the monitored joints.
+pub fn frequency_sweep(
+    fabric: &Fabric,
+    world: &World,
+    monitored: &[usize],
+    f_start: Real,
+    f_end: Real,
+    steps: usize,
+) -> Result<Vec<SweepPoint>, Error> {
+    for &joint_index in monitored {
+        fabric.check_joint(joint_index)?;
+    }
+    for frequency in [f_start, f_end] {
+        if !(frequency.is_finite() && frequency > 0.0) {
+            return Err(Error::IllegalFrequency { frequency });
+        }
+    }
+    if fabric.anchors.is_empty() {
+        return Err(Error::NoAnchors);
+    }
+    let ratio = if steps > 1 {
+        (f_end / f_start).powf(1.0 / (steps - 1) as Real)
+    } else {
+        1.0
+    };
+    let mut frequency = f_start;
+    let mut curve = Vec::with_capacity(steps);
+    for _step in 0..steps {
+        curve.push(SweepPoint {
+            frequency,
+            gains: response(fabric, world, monitored, frequency),
+        });
+        frequency *= ratio;
+    }
+    Ok(curve)
+}
+
+fn response(fabric: &Fabric, world: &World, monitored: &[usize], frequency: Real) -> Vec<Real> {
+    let mut shaken = fabric.clone();
+    let rest: Vec<Point3<Real>> = shaken
+        .anchors
+        .iter()
+        .map(|anchor| anchor.location)
+        .collect();
+    let period = 1.0 / frequency;
+    let settle_ticks = ((SETTLE_CYCLES * period) as u32).clamp(MIN_SETTLE_TICKS, MAX_SWEEP_TICKS);
+    let measure_ticks = ((MEASURE_CYCLES * period) as u32).clamp(1, MAX_SWEEP_TICKS);
+    let mut lows = vec![Vector3::repeat(Real::MAX); monitored.len()];
+    let mut highs = vec![Vector3::repeat(Real::MIN); monitored.len()];
+    for tick in 0..settle_ticks + measure_ticks {
+        let offset = Vector3::x() * SWEEP_AMPLITUDE * (TAU * frequency * tick as Real).sin();
+        for (anchor, location) in shaken.anchors.iter_mut().zip(rest.iter()) {
+            anchor.location = location + offset;
+        }
+        shaken.iterate_ticks(world, 1);
+        if tick < settle_ticks {
+            continue;
+        }
+        for (index, &joint_index) in monitored.iter().enumerate() {
+            let coords = &shaken.joints[joint_index].location.coords;
+            lows[index] = lows[index].inf(coords);
+            highs[index] = highs[index].sup(coords);
+        }
+    }
+    lows.iter()
+        .zip(highs.iter())
+        .map(|(low, high)| (high - low).magnitude() / 2.0 / SWEEP_AMPLITUDE)
+        .collect()
+}
+
+// Stress cables one at a time in the given order to target tensions, settling in between, and see
+// how much each loses while the later ones go in.
+pub fn tensioning_sequence(
+    fabric: &Fabric,
+    world: &World,
+    sequence: &[(usize, Real)],
+    settle_ticks: usize,
+) -> Result<Vec<TensioningRow>, Error> {
+    for &(interval_index, target) in sequence {
+        fabric.check_interval(interval_index)?;
+        if !(target.is_finite() && target >= 0.0) {
+            return Err(Error::IllegalTension { tension: target });
+        }
+    }
+    let mut tensioned = fabric.clone();
+    let tension = |fabric: &Fabric, index: usize| {
+        let interval = &fabric.intervals[index];
+        interval.strain
+            * interval.stiffness
+            * RoleDefaults::of(world, interval.role(), Stage::Pretenst).stiffness
+    };
+    let countdown = (settle_ticks / 2).max(1) as Real;
+    let mut installed = Vec::with_capacity(sequence.len());
+    for &(interval_index, target) in sequence {
+        let interval = &tensioned.intervals[interval_index];
+        let strain = target
+            / (interval.stiffness
+                * RoleDefaults::of(world, interval.role(), Stage::Pretenst).stiffness);
+        let rest_length = interval.calculate_current_length(&tensioned.joints) / (1.0 + strain);
+        tensioned.intervals[interval_index].change_rest_length(rest_length, countdown);
+        tensioned.wake();
+        tensioned.iterate_ticks(world, settle_ticks);
+        installed.push(tension(&tensioned, interval_index));
+    }
+    Ok(sequence
+        .iter()
+        .zip(installed)
+        .map(|(&(interval, target), installed)| {
+            let remaining = tension(&tensioned, interval);
+            TensioningRow {
+                interval,
+                target,
+                installed,
+                remaining,
+                compensated: target + (target - remaining),
+            }
+        })
+        .collect())
+}
+
+// Step the fabric alongside a copy holding its intervals in reverse order and report the first
+// element that drifts more than epsilon apart. Summing forces in another order should only cost
+// rounding, so anything bigger depends on the order of evaluation.
+pub fn determinism_audit(
+    fabric: &Fabric,
+    world: &World,
+    ticks: u32,
+    epsilon: Real,
+) -> Result<Option<Divergence>, Error> {
+    lockstep(fabric, world, ticks, epsilon, |tick, order| {
+        if tick == 0 {
+            order.reverse();
+        }
+    })
+}
+
+// Like the determinism audit, but the copy gets a fresh seeded shuffle of its intervals every tick,
+// so order dependence shows up however it is triggered and the same seed finds it again.
+pub fn shuffle_audit(
+    fabric: &Fabric,
+    world: &World,
+    ticks: u32,
+    seed: u64,
+    epsilon: Real,
+) -> Result<Option<Divergence>, Error> {
+    let mut state = seed | 1;
+    lockstep(fabric, world, ticks, epsilon, |_tick, order| {
+        for index in (1..order.len()).rev() {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            order.swap(index, (state % (index as u64 + 1)) as usize);
+        }
+    })
+}
+
+// Only the intervals are permuted, so both sides go without what finds intervals by index: the audit
+// is of the order the physics sums forces in, with the same fabric on both sides.
+fn bare(fabric: &Fabric) -> Fabric {
+    let mut bare = fabric.clone();
+    bare.labels.clear();
+    bare.sensors.clear();
+    bare.muscles.clear();
+    bare.pattern_generator = None;
+    bare.readings = None;
+    bare.deployment = None;
+    bare.sequencer = None;
+    bare.boundary_recording = None;
+    bare
+}
+
+// The copy's intervals travel with their original index, reordered by the caller before each tick.
+fn lockstep(
+    fabric: &Fabric,
+    world: &World,
+    ticks: u32,
+    epsilon: Real,
+    mut reorder: impl FnMut(u32, &mut Vec<(usize, Interval)>),
+) -> Result<Option<Divergence>, Error> {
+    if !(epsilon.is_finite() && epsilon >= 0.0) {
+        return Err(Error::IllegalTolerance { tolerance: epsilon });
+    }
+    let mut original = bare(fabric);
+    let mut permuted = original.clone();
+    let mut order: Vec<usize> = (0..fabric.intervals.len()).collect();
+    for tick in 0..ticks {
+        let mut entries: Vec<(usize, Interval)> = order
+            .iter()
+            .copied()
+            .zip(permuted.intervals.drain(..))
+            .collect();
+        reorder(tick, &mut entries);
+        (order, permuted.intervals) = entries.into_iter().unzip();
+        original.iterate_ticks(world, 1);
+        permuted.iterate_ticks(world, 1);
+        let joints = original
+            .joints
+            .iter()
+            .zip(permuted.joints.iter())
+            .enumerate();
+        for (index, (joint, twin)) in joints {
+            let difference = (joint.location - twin.location).magnitude();
+            if difference.is_nan() || difference > epsilon {
+                return Ok(Some(Divergence {
+                    tick,
+                    element: Element::Joint(index),
+                    difference,
+                }));
+            }
+        }
+        for (&index, twin) in order.iter().zip(permuted.intervals.iter()) {
+            let difference = (original.intervals[index].strain - twin.strain).abs();
+            if difference.is_nan() || difference > epsilon {
+                return Ok(Some(Divergence {
+                    tick,
+                    element: Element::Interval(index),
+                    difference,
+                }));
+            }
+        }
+    }
+    Ok(None)
+}
+
+// Settle the intact fabric, then for each interval settle a copy without it and measure how far the
+// shape moved and where its load went. Small numbers mean the member is redundant, big ones critical.
+// The copy loses the interval as any removal would, the last one moving into its place.
+pub fn redundancy(fabric: &Fabric, world: &World, settle_ticks: usize) -> Vec<Redundancy> {
+    let mut intact = fabric.clone();
+    intact.iterate_ticks(world, settle_ticks);
+    let last = intact.intervals.len().saturating_sub(1);
+    (0..intact.intervals.len())
+        .map(|interval| {
+            let mut damaged = intact.clone();
+            damaged.swap_remove_interval(interval);
+            damaged.wake();
+            damaged.iterate_ticks(world, settle_ticks);
+            let deviation = intact
+                .joints
+                .iter()
+                .zip(damaged.joints.iter())
+                .map(|(joint, moved)| (joint.location - moved.location).magnitude())
+                .fold(0.0, Real::max);
+            let mut redistribution = 0.0;
+            let mut most_affected = None;
+            for (damaged_index, after) in damaged.intervals.iter().enumerate() {
+                let index = if damaged_index == interval {
+                    last
+                } else {
+                    damaged_index
+                };
+                let change = (after.strain - intact.intervals[index].strain).abs();
+                if change > redistribution {
+                    redistribution = change;
+                    most_affected = Some(index);
+                }
+            }
+            Redundancy {
+                interval,
+                deviation,
+                redistribution,
+                most_affected,
+            }
+        })
+        .collect()
+}
+
+// Age the intervals year by year in steps, with a curve for pushes and one for pulls, settling after
+// each step. Reports when each interval first trips the world's strain alarms, in order of year.
+pub fn aging_schedule(
+    fabric: &Fabric,
+    world: &World,
+    push_curve: AgingCurve,
+    pull_curve: AgingCurve,
+    years: u32,
+    steps_per_year: u32,
+    settle_ticks: usize,
+) -> Result<Vec<Inspection>, Error> {
+    for curve in [push_curve, pull_curve] {
+        let AgingCurve {
+            stiffness_loss,
+            creep,
+        } = curve;
+        if !((0.0..1.0).contains(&stiffness_loss) && creep.is_finite() && creep > -1.0) {
+            return Err(Error::IllegalAgingCurve {
+                stiffness_loss,
+                creep,
+            });
+        }
+    }
+    let mut aged = fabric.clone();
+    let new: Vec<(Real, Real)> = aged
+        .intervals
+        .iter()
+        .map(|interval| (interval.stiffness, interval.length_1))
+        .collect();
+    let countdown = (settle_ticks / 2).max(1) as Real;
+    let mut reached = vec![AlarmLevel::Clear; new.len()];
+    let mut schedule = Vec::new();
+    for step in 1..=years * steps_per_year.max(1) {
+        let year = step as Real / steps_per_year.max(1) as Real;
+        for (interval, &(stiffness, rest_length)) in aged.intervals.iter_mut().zip(new.iter()) {
+            let curve = if interval.role() == IntervalRole::Push {
+                push_curve
+            } else {
+                pull_curve
+            };
+            interval.stiffness = stiffness * (1.0 - curve.stiffness_loss).powf(year);
+            interval.change_rest_length(rest_length * (1.0 + curve.creep).powf(year), countdown);
+        }
+        aged.wake();
+        aged.iterate_ticks(world, settle_ticks);
+        for (index, interval) in aged.intervals.iter().enumerate() {
+            if interval.alarm > reached[index] {
+                reached[index] = interval.alarm;
+                schedule.push(Inspection {
+                    interval: index,
+                    year,
+                    level: interval.alarm,
+                    strain: interval.strain,
+                });
+            }
+        }
+    }
+    Ok(schedule)
+}
+
+// Sustained tension makes cord creep. Time is compressed into steps: each one, every pull in tension
+// lengthens by the rate times its strain times the step, and the fabric settles for the ticks given.
+pub fn creep(
+    fabric: &Fabric,
+    world: &World,
+    rate: Real,
+    duration: Real,
+    steps: u32,
+    settle_ticks: usize,
+) -> Result<Vec<CreepPoint>, Error> {
+    if !(rate.is_finite() && rate >= 0.0) {
+        return Err(Error::IllegalAgingCurve {
+            stiffness_loss: 0.0,
+            creep: rate,
+        });
+    }
+    if !(duration.is_finite() && duration > 0.0) {
+        return Err(Error::IllegalCountdown {
+            countdown: duration,
+        });
+    }
+    let mut crept = fabric.clone();
+    let start: Vec<Real> = crept.joints.iter().map(|joint| joint.location.y).collect();
+    let step = duration / steps.max(1) as Real;
+    let countdown = (settle_ticks / 2).max(1) as Real;
+    let mut points = Vec::with_capacity(steps as usize);
+    for step_index in 1..=steps.max(1) {
+        for interval in crept.intervals.iter_mut() {
+            if interval.push || interval.strain <= 0.0 {
+                continue;
+            }
+            let rest_length = interval.length_1 * (1.0 + rate * interval.strain * step);
+            interval.change_rest_length(rest_length, countdown);
+        }
+        crept.wake();
+        crept.iterate_ticks(world, settle_ticks);
+        let (joint, sag) = crept
+            .joints
+            .iter()
+            .zip(start.iter())
+            .map(|(joint, &y)| y - joint.location.y)
+            .enumerate()
+            .fold(
+                (0, 0.0),
+                |best, (index, drop)| if drop > best.1 { (index, drop) } else { best },
+            );
+        let pulls: Vec<Real> = crept
+            .intervals
+            .iter()
+            .filter(|interval| !interval.push)
+            .map(|interval| interval.strain)
+            .collect();
+        let mean_pull_strain = if pulls.is_empty() {
+            0.0
+        } else {
+            pulls.iter().sum::<Real>() / pulls.len() as Real
+        };
+        points.push(CreepPoint {
+            time: step_index as Real * step,
+            sag,
+            joint,
+            mean_pull_strain,
+        });
+    }
+    Ok(points)
+}
+
+// Iterative closest point from the scan onto the joints: pair every joint with its nearest scan point,
+// fit the best rigid motion for those pairs (Kabsch), and repeat until the fit stops improving.
+pub fn register(fabric: &Fabric, points: &[Point3<Real>]) -> Result<Registration, Error> {
+    if points.is_empty() {
+        return Err(Error::EmptyPointCloud);
+    }
+    let joints: Vec<Point3<Real>> = fabric.joints.iter().map(|joint| joint.location).collect();
+    if joints.is_empty() {
+        return Ok(Registration {
+            transform: Isometry3::identity(),
+            residuals: Vec::new(),
+            rms: 0.0,
+        });
+    }
+    let centroid = |points: &[Point3<Real>]| {
+        Point3::from(
+            points
+                .iter()
+                .map(|point| point.coords)
+                .sum::<Vector3<Real>>()
+                / points.len() as Real,
+        )
+    };
+    let offset = centroid(&joints) - centroid(points);
+    let mut transform =
+        Isometry3::from_parts(Translation3::from(offset), UnitQuaternion::identity());
+    let mut previous_rms = Real::MAX;
+    let mut nearest = Vec::new();
+    let mut rms = 0.0;
+    for _iteration in 0..REGISTRATION_ITERATIONS {
+        let moved: Vec<Point3<Real>> = points.iter().map(|point| transform * point).collect();
+        nearest = joints
+            .iter()
+            .map(|joint| nearest_point(joint, &moved))
+            .collect();
+        rms = (nearest
+            .iter()
+            .map(|&(_, distance)| distance * distance)
+            .sum::<Real>()
+            / joints.len() as Real)
+            .sqrt();
+        if previous_rms - rms < REGISTRATION_CONVERGED {
+            break;
+        }
+        previous_rms = rms;
+        let matched: Vec<Point3<Real>> = nearest.iter().map(|&(index, _)| moved[index]).collect();
+        transform = kabsch(&matched, &joints) * transform;
+    }
+    Ok(Registration {
+        transform,
+        residuals: nearest.iter().map(|&(_, distance)| distance).collect(),
+        rms,
+    })
+}
+
+fn nearest_point(joint: &Point3<Real>, points: &[Point3<Real>]) -> (usize, Real) {
+    points
+        .iter()
+        .map(|point| (point - joint).magnitude())
+        .enumerate()
+        .fold((0, Real::MAX), |best, (index, distance)| {
+            if distance < best.1 {
+                (index, distance)
+            } else {
+                best
+            }
+        })
+}
+
+// The rotation and translation taking the from points onto the to points with least squared error.
+fn kabsch(from: &[Point3<Real>], to: &[Point3<Real>]) -> Isometry3<Real> {
+    let count = from.len() as Real;
+    let from_centroid = from.iter().map(|point| point.coords).sum::<Vector3<Real>>() / count;
+    let to_centroid = to.iter().map(|point| point.coords).sum::<Vector3<Real>>() / count;
+    let covariance = from
+        .iter()
+        .zip(to.iter())
+        .map(|(a, b)| (a.coords - from_centroid) * (b.coords - to_centroid).transpose())
+        .fold(Matrix3::zeros(), |sum, outer| sum + outer);
+    let svd = covariance.svd(true, true);
+    let (Some(u), Some(v_t)) = (svd.u, svd.v_t) else {
+        return Isometry3::identity();
+    };
+    let mut correction = Matrix3::identity();
+    if (v_t.transpose() * u.transpose()).determinant() < 0.0 {
+        correction[(2, 2)] = -1.0;
+    }
+    let rotation = Rotation3::from_matrix_unchecked(v_t.transpose() * correction * u.transpose());
+    let translation = to_centroid - rotation * from_centroid;
+    Isometry3::from_parts(
+        Translation3::from(translation),
+        UnitQuaternion::from_rotation_matrix(&rotation),
+    )
+}
+
+// Columns and hoops, so reports and controllers can name what they act on. Two pushes continue a column
+// when they share a joint and bend less than the angle tolerance, in radians, at it.
+pub fn structures(fabric: &Fabric, angle_tolerance: Real) -> Result<Vec<Structure>, Error> {
+    if !(0.0..=FRAC_PI_2).contains(&angle_tolerance) {
+        return Err(Error::IllegalTolerance {
+            tolerance: angle_tolerance,
+        });
+    }
+    let mut structures: Vec<Structure> = columns(fabric, angle_tolerance.cos())
+        .into_iter()
+        .map(Structure::Column)
+        .collect();
+    let mut tags: Vec<u16> = fabric
+        .intervals
+        .iter()
+        .filter(|interval| !interval.push && interval.tag != 0)
+        .map(|interval| interval.tag)
+        .collect();
+    tags.sort_unstable();
+    tags.dedup();
+    for tag in tags {
+        let Some(ring) = skin::cable_loop(&fabric.intervals, tag) else {
+            continue;
+        };
+        let hoop = (0..ring.len())
+            .filter_map(|position| {
+                let (a, b) = (ring[position], ring[(position + 1) % ring.len()]);
+                fabric.intervals.iter().position(|interval| {
+                    let ends = [interval.alpha_index, interval.omega_index];
+                    !interval.push && interval.tag == tag && ends.contains(&a) && ends.contains(&b)
+                })
+            })
+            .collect();
+        structures.push(Structure::Hoop(hoop));
+    }
+    Ok(structures)
+}
+
+fn columns(fabric: &Fabric, min_cosine: Real) -> Vec<Vec<usize>> {
+    let joints = &fabric.joints;
+    let pushes: Vec<usize> = (0..fabric.intervals.len())
+        .filter(|&index| fabric.intervals[index].push)
+        .collect();
+    let direction = |index: usize| {
+        let interval = &fabric.intervals[index];
+        (joints[interval.omega_index].location - joints[interval.alpha_index].location).normalize()
+    };
+    let ends = |index: usize| {
+        [
+            fabric.intervals[index].alpha_index,
+            fabric.intervals[index].omega_index,
+        ]
+    };
+    // the straightest continuation at a joint, if straight enough
+    let best = |index: usize, joint: usize| {
+        pushes
+            .iter()
+            .copied()
+            .filter(|&other| other != index && ends(other).contains(&joint))
+            .map(|other| (other, direction(index).dot(&direction(other)).abs()))
+            .filter(|&(_, cosine)| cosine >= min_cosine)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(other, _)| other)
+    };
+    let link = |index: usize, joint: usize| {
+        best(index, joint).filter(|&other| best(other, joint) == Some(index))
+    };
+    let mut visited = vec![false; fabric.intervals.len()];
+    let mut columns = Vec::new();
+    for &start in pushes.iter() {
+        let links: Vec<usize> = ends(start)
+            .iter()
+            .filter_map(|&joint| link(start, joint))
+            .collect();
+        if visited[start] || links.len() != 1 {
+            continue;
+        }
+        let mut column = vec![start];
+        visited[start] = true;
+        let mut current = start;
+        let mut next = links[0];
+        while !visited[next] {
+            visited[next] = true;
+            column.push(next);
+            let shared = ends(current)
+                .iter()
+                .copied()
+                .find(|joint| ends(next).contains(joint));
+            let far = ends(next)
+                .iter()
+                .copied()
+                .find(|&joint| Some(joint) != shared);
+            let Some(after) = far.and_then(|joint| link(next, joint)) else {
+                break;
+            };
+            current = next;
+            next = after;
+        }
+        columns.push(column);
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::Waveform;
+    use crate::fixture::chain;
+
+    use super::*;
+
+    // A tetrahedron of pulls set a little short, so it moves as it settles.
+    fn tetrahedron() -> Fabric {
+        let mut fabric = Fabric::new(0);
+        for (x, y, z) in [
+            (1.0, 1.0, 1.0),
+            (-1.0, -1.0, 1.0),
+            (-1.0, 1.0, -1.0),
+            (1.0, -1.0, -1.0),
+        ] {
+            fabric.create_joint(x, y + 3.0, z).unwrap();
+        }
+        for alpha in 0..4 {
+            for omega in alpha + 1..4 {
+                fabric
+                    .add_interval_safe(alpha, omega, IntervalRole::Pull, 1.0, 0.9, 0.0)
+                    .unwrap();
+            }
+        }
+        fabric
+    }
+
+    #[test]
+    fn lockstep_ignores_side_tables_that_find_intervals_by_index() {
+        let mut fabric = tetrahedron();
+        fabric
+            .add_muscle(0, Waveform::Square, 0.5, 0.05, 0.0)
+            .unwrap();
+        fabric.add_sensor(1, 0.5).unwrap();
+        let world = World::new();
+        assert!(determinism_audit(&fabric, &world, 50, 1e-3)
+            .unwrap()
+            .is_none());
+        assert!(shuffle_audit(&fabric, &world, 50, 7, 1e-3)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn redundancy_reports_intervals_by_their_index_in_the_intact_fabric() {
+        let fabric = tetrahedron();
+        let world = World::new();
+        let report = redundancy(&fabric, &world, 100);
+        assert_eq!(report.len(), 6);
+        for row in report.iter() {
+            assert_ne!(row.most_affected, Some(row.interval));
+            assert!(row.most_affected.is_none_or(|index| index < 6));
+        }
+    }
+
+    #[test]
+    fn sweeps_geometrically_and_anchored_joints_follow_the_shaking() {
+        let mut fabric = tetrahedron();
+        let world = World::new();
+        assert!(matches!(
+            frequency_sweep(&fabric, &world, &[0], 0.01, 0.04, 3),
+            Err(Error::NoAnchors)
+        ));
+        fabric.create_anchor(0, Real::MAX, Real::MAX).unwrap();
+        fabric.stage = Stage::Pretenst;
+        assert!(matches!(
+            frequency_sweep(&fabric, &world, &[0], 0.0, 0.04, 3),
+            Err(Error::IllegalFrequency { .. })
+        ));
+        let curve = frequency_sweep(&fabric, &world, &[0, 3], 0.01, 0.04, 3).unwrap();
+        let frequencies: Vec<Real> = curve.iter().map(|point| point.frequency).collect();
+        assert!(
+            (frequencies[1] - 0.02).abs() < 1e-6 && (frequencies[2] - 0.04).abs() < 1e-6,
+            "{:?}",
+            frequencies
+        );
+        for point in curve.iter() {
+            assert!((point.gains[0] - 1.0).abs() < 0.05, "{:?}", point.gains);
+            assert!(point.gains[1].is_finite());
+        }
+    }
+
+    #[test]
+    fn tensioning_compensates_for_what_later_cables_take_away() {
+        let fabric = tetrahedron();
+        let world = World::new();
+        assert!(matches!(
+            tensioning_sequence(&fabric, &world, &[(0, -1.0)], 10),
+            Err(Error::IllegalTension { .. })
+        ));
+        let rows = tensioning_sequence(&fabric, &world, &[(0, 0.5), (5, 0.5)], 100).unwrap();
+        assert_eq!(
+            rows.iter().map(|row| row.interval).collect::<Vec<_>>(),
+            [0, 5]
+        );
+        for row in rows.iter() {
+            assert!(row.installed.is_finite() && row.remaining.is_finite());
+            assert!((row.compensated - (2.0 * row.target - row.remaining)).abs() < 1e-6);
+        }
+        assert_eq!(rows[1].installed, rows[1].remaining);
+        assert_eq!(
+            fabric.intervals[0].length_1,
+            tetrahedron().intervals[0].length_1
+        );
+    }
+
+    #[test]
+    fn forces_sum_the_same_whatever_the_order_of_the_intervals() {
+        let fabric = tetrahedron();
+        let world = World::new();
+        assert!(matches!(
+            shuffle_audit(&fabric, &world, 10, 7, -1.0),
+            Err(Error::IllegalTolerance { .. })
+        ));
+        for seed in [1, 7, 42] {
+            assert!(shuffle_audit(&fabric, &world, 200, seed, 0.0)
+                .unwrap()
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn lockstep_reports_where_the_copy_first_drifts() {
+        let fabric = tetrahedron();
+        let divergence = lockstep(&fabric, &World::new(), 50, 1e-6, |tick, entries| {
+            if tick == 5 {
+                entries[0].1.length_0 *= 0.5;
+            }
+        });
+        let divergence = divergence.unwrap().expect("a shortened interval drifts");
+        assert_eq!(
+            (divergence.tick, divergence.element),
+            (5, Element::Joint(0))
+        );
+    }
+
+    #[test]
+    fn aging_reports_the_year_each_alarm_is_first_reached() {
+        let mut fabric = Fabric::new(0);
+        for x in [0.0, 1.0] {
+            let joint = fabric.create_joint(x, 1.0, 0.0).unwrap();
+            fabric.set_joint_fixed(joint, true).unwrap();
+        }
+        fabric
+            .add_interval_safe(0, 1, IntervalRole::Pull, 1.0, 1.0, 0.0)
+            .unwrap();
+        let mut world = World::new();
+        world
+            .set_strain_alarm(IntervalRole::Pull, 0.1, 0.3)
+            .unwrap();
+        let sound = AgingCurve {
+            stiffness_loss: 0.0,
+            creep: 0.0,
+        };
+        let shrinking = AgingCurve {
+            stiffness_loss: 0.1,
+            creep: -0.1,
+        };
+        assert!(aging_schedule(
+            &fabric,
+            &world,
+            sound,
+            AgingCurve {
+                stiffness_loss: 1.0,
+                creep: 0.0
+            },
+            1,
+            1,
+            10
+        )
+        .is_err());
+        let schedule = aging_schedule(&fabric, &world, sound, shrinking, 4, 1, 100).unwrap();
+        let found: Vec<(Real, AlarmLevel)> = schedule
+            .iter()
+            .map(|inspection| (inspection.year, inspection.level))
+            .collect();
+        assert_eq!(
+            found,
+            [(1.0, AlarmLevel::Warning), (3.0, AlarmLevel::Critical)]
+        );
+        assert!(aging_schedule(&fabric, &world, sound, sound, 4, 1, 100)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn registration_undoes_a_rigid_motion_of_the_scan() {
+        let fabric = tetrahedron();
+        assert!(matches!(
+            register(&fabric, &[]),
+            Err(Error::EmptyPointCloud)
+        ));
+        let motion = Isometry3::new(Vector3::new(0.3, 0.0, 0.2), Vector3::y() * 0.1);
+        let scan: Vec<Point3<Real>> = fabric
+            .joints
+            .iter()
+            .map(|joint| motion * joint.location)
+            .collect();
+        let registration = register(&fabric, &scan).unwrap();
+        assert!(registration.rms < 1e-4, "{}", registration.rms);
+        for (joint, point) in fabric.joints.iter().zip(scan.iter()) {
+            assert!((registration.transform * point - joint.location).magnitude() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn finds_straight_columns_and_closed_hoops() {
+        let mut fabric = Fabric::new(0);
+        for (x, y) in [(0.0, 1.0), (1.0, 1.0), (2.0, 1.0), (3.0, 1.0), (3.0, 2.0)] {
+            fabric.create_joint(x, y, 0.0).unwrap();
+        }
+        for alpha in 0..4 {
+            fabric
+                .add_interval_safe(alpha, alpha + 1, IntervalRole::Push, 1.0, 1.0, 0.0)
+                .unwrap();
+        }
+        for (alpha, omega) in [(0, 1), (1, 4), (4, 0)] {
+            let index = fabric
+                .add_interval_safe(alpha, omega, IntervalRole::Pull, 1.0, 1.0, 0.0)
+                .unwrap();
+            fabric.set_interval_tag(index, 3).unwrap();
+        }
+        assert!(structures(&fabric, 2.0).is_err());
+        let found = structures(&fabric, 0.1).unwrap();
+        assert_eq!(
+            found,
+            [
+                Structure::Column(vec![0, 1, 2]),
+                Structure::Hoop(vec![4, 5, 6])
+            ]
+        );
+    }
+
+    #[test]
+    fn creeping_pulls_let_a_span_sag_further_than_it_would_anyway() {
+        let mut fabric = chain(3, 0.9);
+        fabric.set_joint_fixed(0, true).unwrap();
+        fabric.set_joint_fixed(2, true).unwrap();
+        fabric.stage = Stage::Pretenst;
+        let world = World::new();
+        assert!(creep(&fabric, &world, -1.0, 1.0, 3, 100).is_err());
+        assert!(creep(&fabric, &world, 1.0, 0.0, 3, 100).is_err());
+        let still = creep(&fabric, &world, 0.0, 3.0, 3, 100).unwrap();
+        let crept = creep(&fabric, &world, 1.0, 3.0, 3, 100).unwrap();
+        assert_eq!(
+            crept.iter().map(|point| point.time).collect::<Vec<Real>>(),
+            [1.0, 2.0, 3.0]
+        );
+        assert!(crept.iter().all(|point| point.joint == 1), "{:?}", crept);
+        assert!(crept[2].sag > still[2].sag, "{:?} {:?}", crept, still);
+        assert!(
+            crept[2].mean_pull_strain < still[2].mean_pull_strain,
+            "{:?} {:?}",
+            crept,
+            still
+        );
+        assert_eq!(fabric.intervals[0].length_1, 0.9);
+    }
+}
@@ -0,0 +1,99 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use nalgebra::*;
+
+use crate::joint::Joint;
+use crate::real::Real;
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Anchor {
+    pub(crate) joint_index: usize,
+    pub(crate) location: Point3<Real>,
+    pub(crate) max_vertical: Real,
+    pub(crate) max_lateral: Real,
+    pub(crate) reaction: Vector3<Real>,
+}
+
+impl Anchor {
+    pub fn new(
+        joint_index: usize,
+        location: Point3<Real>,
+        max_vertical: Real,
+        max_lateral: Real,
+    ) -> Anchor {
+        Anchor {
+            joint_index,
+            location,
+            max_vertical,
+            max_lateral,
+            reaction: zero(),
+        }
+    }
+
+    pub fn joint_removed(&mut self, index: usize) {
+        if self.joint_index > index {
+            self.joint_index -= 1;
+        }
+    }
+
+    // The ground supplies whatever holds the joint still. Bearing is unlimited, uplift and shear
+    // are not: returns false when the anchor pulls out.
+    pub fn hold(&mut self, joint: &mut Joint, gravity: &Vector3<Real>) -> bool {
+        self.reaction = -gravity * joint.interval_mass - joint.force;
+        let uplift = -self.reaction.y;
+        let lateral =
+            (self.reaction.x * self.reaction.x + self.reaction.z * self.reaction.z).sqrt();
+        if uplift > self.max_vertical || lateral > self.max_lateral {
+            return false;
+        }
+        joint.velocity = zero();
+        joint.lead = zero();
+        joint.location = self.location;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::Stage;
+    use crate::error::Error;
+    use crate::event::FabricEvent;
+    use crate::fixture::{chain, joints};
+    use crate::world::World;
+
+    use super::*;
+
+    #[test]
+    fn anchors_hold_until_the_reaction_exceeds_their_capacity() {
+        let pulled = |max_lateral: Real, ticks: usize| {
+            let mut fabric = chain(2, 0.5);
+            fabric.create_anchor(0, Real::MAX, max_lateral).unwrap();
+            fabric.stage = Stage::Pretenst;
+            fabric.iterate_ticks(&World::new(), ticks);
+            fabric
+        };
+        assert!(pulled(Real::MAX, 1).anchors[0].reaction.x < 0.0);
+        let held = pulled(Real::MAX, 100);
+        assert_eq!(held.anchors.len(), 1);
+        assert_eq!(held.joints[0].location, Point3::new(0.0, 1.0, 0.0));
+        let released = pulled(0.0, 100);
+        assert!(released.anchors.is_empty());
+        assert!(matches!(
+            released.events[0],
+            FabricEvent::AnchorReleased {
+                joint: 0,
+                tick: 0,
+                ..
+            }
+        ));
+        assert!(released.joints[0].location.x > 0.0);
+        assert!(matches!(
+            joints(1).create_anchor(0, -1.0, 1.0),
+            Err(Error::IllegalCapacity { .. })
+        ));
+    }
+}
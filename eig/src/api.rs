@@ -0,0 +1,235 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+// The stable face of the engine. Descriptor layouts only change with API_VERSION, whatever happens
+// to Fabric, Joint and Interval underneath.
+
+use wasm_bindgen::prelude::*;
+
+use crate::real::Real;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::constants::*;
+use crate::error::Error;
+use crate::fabric::Fabric;
+use crate::world::World;
+
+pub const API_VERSION: u32 = 1;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct JointDescriptor {
+    pub x: Real,
+    pub y: Real,
+    pub z: Real,
+}
+
+#[wasm_bindgen]
+impl JointDescriptor {
+    #[wasm_bindgen(constructor)]
+    pub fn new(x: Real, y: Real, z: Real) -> JointDescriptor {
+        JointDescriptor { x, y, z }
+    }
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IntervalDescriptor {
+    pub alpha: u32,
+    pub omega: u32,
+    pub role: IntervalRole,
+    pub rest_length: Real,
+    pub stiffness: Real,
+    pub tag: u16,
+}
+
+#[wasm_bindgen]
+impl IntervalDescriptor {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        alpha: u32,
+        omega: u32,
+        role: IntervalRole,
+        rest_length: Real,
+        stiffness: Real,
+        tag: u16,
+    ) -> IntervalDescriptor {
+        IntervalDescriptor {
+            alpha,
+            omega,
+            role,
+            rest_length,
+            stiffness,
+            tag,
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct FabricHandle {
+    fabric: Fabric,
+}
+
+#[wasm_bindgen]
+impl FabricHandle {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> FabricHandle {
+        FabricHandle {
+            fabric: Fabric::new(0),
+        }
+    }
+
+    pub fn api_version() -> u32 {
+        API_VERSION
+    }
+
+    pub fn joint_count(&self) -> u32 {
+        self.fabric.joints.len() as u32
+    }
+
+    pub fn interval_count(&self) -> u32 {
+        self.fabric.intervals.len() as u32
+    }
+
+    pub fn add_joint(&mut self, joint: JointDescriptor) -> Result<u32, Error> {
+        Ok(self.fabric.create_joint(joint.x, joint.y, joint.z)? as u32)
+    }
+
+    pub fn add_interval(&mut self, interval: IntervalDescriptor) -> Result<u32, Error> {
+        let index = self.fabric.create_interval(
+            interval.alpha as usize,
+            interval.omega as usize,
+            interval.role == IntervalRole::Push,
+            interval.rest_length,
+            interval.rest_length,
+            interval.stiffness,
+            0.0,
+        )?;
+        self.fabric.set_interval_tag(index, interval.tag)?;
+        Ok(index as u32)
+    }
+
+    pub fn joint(&self, index: u32) -> Result<JointDescriptor, Error> {
+        self.fabric.check_joint(index as usize)?;
+        let location = self.fabric.joints[index as usize].location;
+        Ok(JointDescriptor {
+            x: location.x,
+            y: location.y,
+            z: location.z,
+        })
+    }
+
+    pub fn interval(&self, index: u32) -> Result<IntervalDescriptor, Error> {
+        self.fabric.check_interval(index as usize)?;
+        let interval = &self.fabric.intervals[index as usize];
+        Ok(IntervalDescriptor {
+            alpha: interval.alpha_index as u32,
+            omega: interval.omega_index as u32,
+            role: interval.role(),
+            rest_length: interval.length_1,
+            stiffness: interval.stiffness,
+            tag: interval.tag,
+        })
+    }
+
+    pub fn stage(&self) -> Stage {
+        self.fabric.stage
+    }
+
+    pub fn request_stage(&mut self, stage: Stage, world: &World) -> Option<Stage> {
+        self.fabric.request_stage(stage, world)
+    }
+
+    pub fn iterate(&mut self, world: &World) -> bool {
+        self.fabric.iterate(world)
+    }
+
+    pub fn save_snapshot(&self, world: &World) -> Vec<u8> {
+        self.fabric.save_snapshot(world)
+    }
+
+    pub fn load_snapshot(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.fabric.load_snapshot(bytes)
+    }
+}
+
+impl Default for FabricHandle {
+    fn default() -> Self {
+        FabricHandle::new()
+    }
+}
+
+impl FabricHandle {
+    pub fn fabric(&self) -> &Fabric {
+        &self.fabric
+    }
+
+    pub fn fabric_mut(&mut self) -> &mut Fabric {
+        &mut self.fabric
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn descriptors_come_back_as_they_went_in() {
+        let mut handle = FabricHandle::new();
+        for x in [0.0, 2.0] {
+            handle.add_joint(JointDescriptor::new(x, 1.0, 0.0)).unwrap();
+        }
+        let push = IntervalDescriptor::new(0, 1, IntervalRole::Push, 1.5, 2.0, 7);
+        assert_eq!(handle.add_interval(push).unwrap(), 0);
+        assert_eq!(handle.interval(0).unwrap(), push);
+        assert_eq!(
+            handle.joint(1).unwrap(),
+            JointDescriptor::new(2.0, 1.0, 0.0)
+        );
+        assert!(matches!(
+            handle.joint(2),
+            Err(Error::JointOutOfRange { index: 2, count: 2 })
+        ));
+        assert!(handle
+            .add_interval(IntervalDescriptor::new(
+                0,
+                5,
+                IntervalRole::Pull,
+                1.0,
+                1.0,
+                0
+            ))
+            .is_err());
+        assert_eq!(handle.interval_count(), 1);
+    }
+
+    #[test]
+    fn a_snapshot_restores_a_handle() {
+        let world = World::new();
+        let mut handle = FabricHandle::new();
+        for x in [0.0, 1.0] {
+            handle.add_joint(JointDescriptor::new(x, 1.0, 0.0)).unwrap();
+        }
+        handle
+            .add_interval(IntervalDescriptor::new(
+                0,
+                1,
+                IntervalRole::Pull,
+                1.0,
+                1.0,
+                3,
+            ))
+            .unwrap();
+        let bytes = handle.save_snapshot(&world);
+        let mut restored = FabricHandle::default();
+        restored.load_snapshot(&bytes).unwrap();
+        assert_eq!(restored.joint_count(), 2);
+        assert_eq!(restored.interval(0).unwrap().tag, 3);
+    }
+}
@@ -0,0 +1,179 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use nalgebra::*;
+
+use crate::constants::*;
+use crate::interval::Interval;
+use crate::joint::Joint;
+use crate::real::Real;
+use crate::world::World;
+
+// Per tick, the force on each boundary joint, in the order of the joints.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BoundaryForces {
+    pub joints: Vec<usize>,
+    pub series: Vec<Vec<Vector3<Real>>>,
+}
+
+// What the recorded intervals, the part to be left out of the next run, do to the boundary joints.
+#[derive(Clone, Debug)]
+pub(crate) struct BoundaryRecording {
+    pub(crate) forces: BoundaryForces,
+    intervals: Vec<usize>,
+}
+
+impl BoundaryRecording {
+    pub fn new(joints: &[usize], intervals: &[usize]) -> BoundaryRecording {
+        BoundaryRecording {
+            forces: BoundaryForces {
+                joints: joints.to_vec(),
+                series: Vec::new(),
+            },
+            intervals: intervals.to_vec(),
+        }
+    }
+
+    // Interval physics puts half the axial force along the unit on alpha, and takes it from omega.
+    pub fn record(&mut self, intervals: &[Interval], world: &World, stage: Stage) {
+        let mut tick = vec![zero(); self.forces.joints.len()];
+        for &index in self.intervals.iter() {
+            let interval = &intervals[index];
+            let half = interval.unit * interval.axial_force(world, stage) / 2.0;
+            for (slot, &joint) in self.forces.joints.iter().enumerate() {
+                if interval.alpha_index == joint {
+                    tick[slot] += half;
+                } else if interval.omega_index == joint {
+                    tick[slot] -= half;
+                }
+            }
+        }
+        self.forces.series.push(tick);
+    }
+
+    pub fn interval_removed(&mut self, index: usize, last: usize) {
+        self.intervals.retain(|&interval| interval != index);
+        for interval in self
+            .intervals
+            .iter_mut()
+            .filter(|interval| **interval == last)
+        {
+            *interval = index;
+        }
+    }
+
+    // False when a boundary joint itself went.
+    pub fn joint_removed(&mut self, index: usize) -> bool {
+        joint_removed(&mut self.forces.joints, index)
+    }
+}
+
+// A recorded series replayed onto joints of another fabric, one tick at a time.
+#[derive(Clone, Debug)]
+pub(crate) struct BoundaryLoads {
+    joints: Vec<usize>,
+    series: Vec<Vec<Vector3<Real>>>,
+    tick: usize,
+}
+
+impl BoundaryLoads {
+    pub fn new(joints: &[usize], series: &[Vec<Vector3<Real>>]) -> BoundaryLoads {
+        BoundaryLoads {
+            joints: joints.to_vec(),
+            series: series.to_vec(),
+            tick: 0,
+        }
+    }
+
+    // False once the series has run out.
+    pub fn apply(&mut self, joints: &mut [Joint]) -> bool {
+        let Some(forces) = self.series.get(self.tick) else {
+            return false;
+        };
+        for (&joint, force) in self.joints.iter().zip(forces.iter()) {
+            joints[joint].force += force;
+        }
+        self.tick += 1;
+        true
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.series.len().saturating_sub(self.tick)
+    }
+
+    pub fn joint_removed(&mut self, index: usize) -> bool {
+        joint_removed(&mut self.joints, index)
+    }
+}
+
+fn joint_removed(joints: &mut [usize], index: usize) -> bool {
+    if joints.contains(&index) {
+        return false;
+    }
+    for joint in joints.iter_mut().filter(|joint| **joint > index) {
+        *joint -= 1;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::fixture::{chain, joints};
+
+    use super::*;
+
+    #[test]
+    fn boundary_forces_recorded_in_one_run_replay_as_loads_in_another() {
+        let world = World::new();
+        let mut whole = joints(3);
+        whole
+            .add_interval_safe(0, 1, IntervalRole::Pull, 1.0, 0.5, 0.0)
+            .unwrap();
+        whole
+            .add_interval_safe(1, 2, IntervalRole::Pull, 1.0, 1.0, 0.0)
+            .unwrap();
+        for joint in 0..3 {
+            whole.set_joint_fixed(joint, true).unwrap();
+        }
+        whole.stage = Stage::Pretenst;
+        whole.record_boundary_forces(&[1], &[0]).unwrap();
+        whole.iterate_ticks(&world, 3);
+        let forces = whole.export_boundary_forces().unwrap();
+        assert_eq!(forces.series.len(), 3);
+        assert!(
+            forces
+                .series
+                .iter()
+                .all(|tick| tick[0].x < 0.0 && tick[0] == forces.series[0][0]),
+            "{:?}",
+            forces.series
+        );
+        assert!(whole.export_boundary_forces().is_none());
+
+        let mut part = chain(2, 1.0);
+        part.stage = Stage::Pretenst;
+        assert!(matches!(
+            part.apply_boundary_forces(&forces, &[0, 1]),
+            Err(Error::WrongLength {
+                expected: 1,
+                actual: 2
+            })
+        ));
+        part.apply_boundary_forces(&forces, &[0]).unwrap();
+        assert_eq!(part.get_boundary_ticks_remaining(), 3);
+        part.iterate_ticks(&world, 5);
+        assert_eq!(part.get_boundary_ticks_remaining(), 0);
+        assert!(
+            part.joints[0].location.x < 0.0,
+            "{}",
+            part.joints[0].location
+        );
+
+        whole.record_boundary_forces(&[1], &[0]).unwrap();
+        whole.remove_joint(1).unwrap();
+        assert!(whole.export_boundary_forces().is_none());
+    }
+}
@@ -0,0 +1,226 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use std::collections::HashMap;
+
+use nalgebra::*;
+
+use crate::interval::Interval;
+use crate::joint::Joint;
+use crate::real::Real;
+
+// A strut whose box would mark more cells than this, long against the grid or far out, is paired
+// with every other strut instead.
+const MAX_CELLS: Real = 64.0;
+
+// Struts as capsules of the radius: every overlapping pair not sharing a joint is pushed apart in
+// proportion to the overlap, each end taking its share by where the closest points lie.
+pub fn separate_struts(
+    intervals: &[Interval],
+    joints: &mut [Joint],
+    radius: Real,
+    stiffness: Real,
+) {
+    let struts: Vec<usize> = (0..intervals.len())
+        .filter(|&index| intervals[index].push)
+        .collect();
+    if struts.len() < 2 {
+        return;
+    }
+    let reach = 2.0 * radius;
+    for (first, second) in candidate_pairs(intervals, joints, &struts, reach) {
+        let (a, b) = (&intervals[first], &intervals[second]);
+        if a.touches(b.alpha_index) || a.touches(b.omega_index) {
+            continue;
+        }
+        let (p0, p1) = (
+            joints[a.alpha_index].location,
+            joints[a.omega_index].location,
+        );
+        let (q0, q1) = (
+            joints[b.alpha_index].location,
+            joints[b.omega_index].location,
+        );
+        let (s, t) = closest_parameters(&p0, &p1, &q0, &q1);
+        let apart = (p0 + (p1 - p0) * s) - (q0 + (q1 - q0) * t);
+        let distance = apart.magnitude();
+        if distance >= reach || distance < Real::EPSILON {
+            continue;
+        }
+        let push = apart * (stiffness * (reach - distance) / distance);
+        joints[a.alpha_index].force += push * (1.0 - s);
+        joints[a.omega_index].force += push * s;
+        joints[b.alpha_index].force -= push * (1.0 - t);
+        joints[b.omega_index].force -= push * t;
+    }
+}
+
+// Broadphase: each strut's box, grown by the reach, marks the cells it covers, with cells as big as
+// the average strut so most struts cover only a few. Struts with an end at no real place collide with
+// nothing. Sorted, so forces always add up in the same order.
+fn candidate_pairs(
+    intervals: &[Interval],
+    joints: &[Joint],
+    struts: &[usize],
+    reach: Real,
+) -> Vec<(usize, usize)> {
+    let ends = |index: usize| {
+        (
+            joints[intervals[index].alpha_index].location,
+            joints[intervals[index].omega_index].location,
+        )
+    };
+    let struts: Vec<usize> = struts
+        .iter()
+        .copied()
+        .filter(|&index| {
+            let (alpha, omega) = ends(index);
+            alpha
+                .iter()
+                .chain(omega.iter())
+                .all(|coordinate| coordinate.is_finite())
+        })
+        .collect();
+    if struts.len() < 2 {
+        return Vec::new();
+    }
+    let mean_length = struts
+        .iter()
+        .map(|&index| {
+            let (alpha, omega) = ends(index);
+            (omega - alpha).magnitude()
+        })
+        .sum::<Real>()
+        / struts.len() as Real;
+    let cell = mean_length.max(reach).max(Real::EPSILON);
+    let mut grid: HashMap<[i32; 3], Vec<usize>> = HashMap::new();
+    let mut oversized = Vec::new();
+    for &index in struts.iter() {
+        let (alpha, omega) = ends(index);
+        let low = (alpha.coords.inf(&omega.coords).add_scalar(-reach) / cell).map(Real::floor);
+        let high = (alpha.coords.sup(&omega.coords).add_scalar(reach) / cell).map(Real::floor);
+        let cells = (high - low).add_scalar(1.0).product();
+        if cells.is_nan() || cells > MAX_CELLS {
+            oversized.push(index);
+            continue;
+        }
+        for x in low.x as i32..=high.x as i32 {
+            for y in low.y as i32..=high.y as i32 {
+                for z in low.z as i32..=high.z as i32 {
+                    grid.entry([x, y, z]).or_default().push(index);
+                }
+            }
+        }
+    }
+    let mut pairs = Vec::new();
+    for &first in oversized.iter() {
+        for &second in struts.iter().filter(|&&second| second != first) {
+            pairs.push((first.min(second), first.max(second)));
+        }
+    }
+    for members in grid.values() {
+        for (position, &first) in members.iter().enumerate() {
+            for &second in members[position + 1..].iter() {
+                pairs.push((first.min(second), first.max(second)));
+            }
+        }
+    }
+    pairs.sort_unstable();
+    pairs.dedup();
+    pairs
+}
+
+// Where along each segment, from 0 to 1, the two come closest.
+fn closest_parameters(
+    p0: &Point3<Real>,
+    p1: &Point3<Real>,
+    q0: &Point3<Real>,
+    q1: &Point3<Real>,
+) -> (Real, Real) {
+    let d1 = p1 - p0;
+    let d2 = q1 - q0;
+    let r = p0 - q0;
+    let a = d1.dot(&d1);
+    let e = d2.dot(&d2);
+    let f = d2.dot(&r);
+    if a < Real::EPSILON && e < Real::EPSILON {
+        return (0.0, 0.0);
+    }
+    if a < Real::EPSILON {
+        return (0.0, (f / e).clamp(0.0, 1.0));
+    }
+    let c = d1.dot(&r);
+    if e < Real::EPSILON {
+        return ((-c / a).clamp(0.0, 1.0), 0.0);
+    }
+    let b = d1.dot(&d2);
+    let denominator = a * e - b * b;
+    let mut s = if denominator > Real::EPSILON {
+        ((b * f - c * e) / denominator).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let mut t = (b * s + f) / e;
+    if t < 0.0 {
+        t = 0.0;
+        s = (-c / a).clamp(0.0, 1.0);
+    } else if t > 1.0 {
+        t = 1.0;
+        s = ((b - c) / a).clamp(0.0, 1.0);
+    }
+    (s, t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn struts(ends: &[([Real; 3], [Real; 3])]) -> (Vec<Interval>, Vec<Joint>) {
+        let mut joints = Vec::new();
+        let mut intervals = Vec::new();
+        for (alpha, omega) in ends {
+            joints.push(Joint::new(alpha[0], alpha[1], alpha[2]));
+            joints.push(Joint::new(omega[0], omega[1], omega[2]));
+            intervals.push(Interval::new(
+                joints.len() - 2,
+                joints.len() - 1,
+                true,
+                1.0,
+                1.0,
+                1.0,
+                0.0,
+            ));
+        }
+        (intervals, joints)
+    }
+
+    #[test]
+    fn long_struts_still_meet_short_ones() {
+        let mut ends = vec![([-500.0, 0.0, -500.0], [500.0, 0.0, 500.0])];
+        ends.extend((0..10).map(|step| {
+            let x = step as Real * 3.0;
+            ([x, -0.5, x], [x, 0.5, x])
+        }));
+        let (intervals, joints) = struts(&ends);
+        let all: Vec<usize> = (0..intervals.len()).collect();
+        let pairs = candidate_pairs(&intervals, &joints, &all, 0.2);
+        assert!((1..intervals.len()).all(|short| pairs.contains(&(0, short))));
+    }
+
+    #[test]
+    fn far_and_unreal_coordinates_cost_nothing() {
+        let (intervals, joints) = struts(&[
+            ([-1e30, 0.0, 0.0], [1e30, 0.0, 0.0]),
+            ([0.0, Real::INFINITY, 0.0], [0.0, 1.0, 0.0]),
+            ([0.0, Real::NAN, 0.0], [0.0, 1.0, 0.0]),
+            ([0.0, -1.0, 1e-3], [0.0, 1.0, 1e-3]),
+        ]);
+        let all: Vec<usize> = (0..intervals.len()).collect();
+        assert_eq!(
+            candidate_pairs(&intervals, &joints, &all, 0.2),
+            vec![(0, 3)]
+        );
+    }
+}
@@ -0,0 +1,255 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+// Operations packed into one Float32Array, opcode first, so a whole frame of editing crosses
+// the wasm boundary in a single call. Indices ride along as floats, exact up to 2^24.
+
+use wasm_bindgen::prelude::*;
+
+use crate::constants::*;
+use crate::error::Error;
+use crate::fabric::Fabric;
+use crate::real::{reals, Real};
+use crate::world::World;
+
+pub(crate) const WORLD_FEATURES: [WorldFeature; 14] = [
+    WorldFeature::VisualStrain,
+    WorldFeature::IterationsPerFrame,
+    WorldFeature::Gravity,
+    WorldFeature::PretenstFactor,
+    WorldFeature::StiffnessFactor,
+    WorldFeature::PushOverPull,
+    WorldFeature::Drag,
+    WorldFeature::ShapingPretenstFactor,
+    WorldFeature::ShapingDrag,
+    WorldFeature::ShapingStiffnessFactor,
+    WorldFeature::Antigravity,
+    WorldFeature::IntervalCountdown,
+    WorldFeature::PretensingCountdown,
+    WorldFeature::ShapingSlewRate,
+];
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    SetFeature,         // feature, value
+    MultiplyRestLength, // interval, factor, countdown
+    ChangeRestLength,   // interval, rest length, countdown
+    Twitch,             // interval, attack countdown, decay countdown, size nuance
+    TwitchGroup,        // tag, attack countdown, decay countdown, size nuance
+    AddInterval,        // alpha, omega, role, stiffness, rest factor, countdown
+    RemoveInterval,     // interval
+}
+
+impl Command {
+    const ALL: [Command; 7] = [
+        Command::SetFeature,
+        Command::MultiplyRestLength,
+        Command::ChangeRestLength,
+        Command::Twitch,
+        Command::TwitchGroup,
+        Command::AddInterval,
+        Command::RemoveInterval,
+    ];
+
+    fn arguments(self) -> usize {
+        match self {
+            Command::SetFeature => 2,
+            Command::MultiplyRestLength | Command::ChangeRestLength => 3,
+            Command::Twitch | Command::TwitchGroup => 4,
+            Command::AddInterval => 6,
+            Command::RemoveInterval => 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CommandBuffer(Vec<f32>);
+
+impl CommandBuffer {
+    pub fn new() -> CommandBuffer {
+        CommandBuffer::default()
+    }
+
+    pub fn push(&mut self, command: Command, arguments: &[f32]) -> Result<(), Error> {
+        if arguments.len() != command.arguments() {
+            return Err(Error::WrongLength {
+                expected: command.arguments(),
+                actual: arguments.len(),
+            });
+        }
+        self.0.push(command as u8 as f32);
+        self.0.extend_from_slice(arguments);
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        &self.0
+    }
+}
+
+// Largest whole number a float carries exactly.
+pub(crate) const MAX_WHOLE: usize = 1 << 24;
+
+// Opcodes, indices and tags must be whole numbers in range, not whatever a cast would make of them.
+pub(crate) fn whole(word: Real, max: usize) -> Option<usize> {
+    if word.is_finite() && word >= 0.0 && word.fract() == 0.0 && word <= max as Real {
+        Some(word as usize)
+    } else {
+        None
+    }
+}
+
+// Applies commands in order, stopping at the first failure. Returns how many were applied.
+pub fn apply(fabric: &mut Fabric, world: &mut World, words: &[f32]) -> Result<usize, Error> {
+    let words = reals(words);
+    let mut offset = 0;
+    let mut applied = 0;
+    while offset < words.len() {
+        let malformed = Error::MalformedCommand { offset };
+        let command = whole(words[offset], MAX_WHOLE)
+            .and_then(|opcode| Command::ALL.get(opcode))
+            .copied()
+            .ok_or(malformed.clone())?;
+        let arguments = words
+            .get(offset + 1..offset + 1 + command.arguments())
+            .ok_or(malformed.clone())?;
+        let index = |at: usize| whole(arguments[at], MAX_WHOLE).ok_or(malformed.clone());
+        match command {
+            Command::SetFeature => {
+                let feature = *WORLD_FEATURES.get(index(0)?).ok_or(malformed)?;
+                world.set_float_value(feature, arguments[1]);
+            }
+            Command::MultiplyRestLength => {
+                fabric.multiply_rest_length(index(0)?, arguments[1], arguments[2])?;
+            }
+            Command::ChangeRestLength => {
+                fabric.change_rest_length(index(0)?, arguments[1], arguments[2])?;
+            }
+            Command::Twitch => {
+                fabric.twitch_interval(index(0)?, arguments[1], arguments[2], arguments[3])?;
+            }
+            Command::TwitchGroup => {
+                let tag = whole(arguments[0], u16::MAX as usize).ok_or(malformed)? as u16;
+                for interval_index in 0..fabric.intervals.len() {
+                    if fabric.intervals[interval_index].tag == tag {
+                        fabric.twitch_interval(
+                            interval_index,
+                            arguments[1],
+                            arguments[2],
+                            arguments[3],
+                        )?;
+                    }
+                }
+            }
+            Command::AddInterval => {
+                let role = match index(2)? {
+                    role if role == IntervalRole::Push as usize => IntervalRole::Push,
+                    role if role == IntervalRole::Pull as usize => IntervalRole::Pull,
+                    _ => return Err(malformed),
+                };
+                fabric.add_interval_safe(
+                    index(0)?,
+                    index(1)?,
+                    role,
+                    arguments[3],
+                    arguments[4],
+                    arguments[5],
+                )?;
+            }
+            Command::RemoveInterval => {
+                fabric.remove_interval(index(0)?)?;
+            }
+        }
+        offset += 1 + command.arguments();
+        applied += 1;
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair() -> Fabric {
+        let mut fabric = Fabric::new(0);
+        fabric.create_joint(0.0, 0.0, 0.0).unwrap();
+        fabric.create_joint(1.0, 0.0, 0.0).unwrap();
+        fabric
+            .add_interval_safe(0, 1, IntervalRole::Pull, 1.0, 1.0, 0.0)
+            .unwrap();
+        fabric
+    }
+
+    #[test]
+    fn push_refuses_the_wrong_number_of_arguments() {
+        let mut buffer = CommandBuffer::new();
+        assert!(matches!(
+            buffer.push(Command::RemoveInterval, &[0.0, 1.0]),
+            Err(Error::WrongLength {
+                expected: 1,
+                actual: 2
+            })
+        ));
+        assert!(buffer.as_slice().is_empty());
+        buffer
+            .push(Command::ChangeRestLength, &[0.0, 2.0, 10.0])
+            .unwrap();
+        assert_eq!(buffer.as_slice().len(), 4);
+    }
+
+    #[test]
+    fn applies_whole_indices() {
+        let mut fabric = pair();
+        let mut world = World::new();
+        let mut buffer = CommandBuffer::new();
+        buffer
+            .push(Command::ChangeRestLength, &[0.0, 2.0, 10.0])
+            .unwrap();
+        buffer
+            .push(
+                Command::AddInterval,
+                &[1.0, 0.0, IntervalRole::Push as u8 as f32, 2.0, 1.0, 0.0],
+            )
+            .unwrap();
+        assert_eq!(
+            apply(&mut fabric, &mut world, buffer.as_slice()).unwrap(),
+            2
+        );
+        assert!(fabric.intervals[1].push);
+        assert_eq!(fabric.intervals[1].stiffness, 2.0);
+    }
+
+    #[test]
+    fn rejects_indices_that_are_not_whole_numbers_in_range() {
+        for word in [0.5, -1.0, f32::NAN, f32::INFINITY, 1e30] {
+            let mut fabric = pair();
+            let words = [Command::RemoveInterval as u8 as f32, word];
+            let result = apply(&mut fabric, &mut World::new(), &words);
+            assert!(
+                matches!(result, Err(Error::MalformedCommand { offset: 0 })),
+                "{}",
+                word
+            );
+            assert_eq!(fabric.intervals.len(), 1);
+        }
+        let words = [
+            Command::AddInterval as u8 as f32,
+            0.0,
+            1.0,
+            7.0,
+            1.0,
+            1.0,
+            0.0,
+        ];
+        assert!(apply(&mut pair(), &mut World::new(), &words).is_err());
+        assert!(apply(&mut pair(), &mut World::new(), &[6.5, 0.0]).is_err());
+    }
+}
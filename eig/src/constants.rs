@@ -5,9 +5,12 @@
 
 use wasm_bindgen::prelude::*;
 
+use crate::real::Real;
+
 #[wasm_bindgen]
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Stage {
     Growing,
     Shaping,
@@ -25,6 +28,117 @@ pub enum SurfaceCharacter {
     Bouncy,
 }
 
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IntervalRole {
+    Push,
+    Pull,
+}
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlarmLevel {
+    Clear,
+    Warning,
+    Critical,
+}
+
+// How strains map onto the color scale: one range for everything, a range per role, or a range per
+// role that leaves out the strains beyond a percentile so a few outliers don't flatten the rest.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StrainScale {
+    Shared,
+    PerRole,
+    Percentile,
+}
+
+// The shape of one cycle of a muscle, from one down to minus one and back.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Waveform {
+    Sine,
+    Square,
+    Triangle,
+}
+
+// How the forces of a tick move the joints, from cheapest to most accurate.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Integration {
+    SemiImplicitEuler,
+    VelocityVerlet,
+    RungeKutta4,
+}
+
+// Every buffer a View can fill. New channels only ever go at the end.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViewChannel {
+    JointLocations,
+    JointVelocities,
+    JointColors,
+    JointSizes,
+    LineLocations,
+    LineColors,
+    ShadowLocations,
+    FaceMidpoints,
+    FaceNormals,
+    FaceVertexLocations,
+    UnitVectors,
+    IdealLengths,
+    Strains,
+    StrainLimits,
+    StrainNuances,
+    Stiffnesses,
+    LinearDensities,
+    Alarms,
+    AxialForces,
+    ForceVectors,
+    CalloutLocations,
+    CalloutIntervals,
+}
+
+// What one stride of a channel describes.
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViewElement {
+    Fabric,
+    Joint,
+    Interval,
+    Face,
+    Callout,
+}
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ViewScalar {
+    F32,
+    U8,
+    U32,
+}
+
+#[wasm_bindgen]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JointColoring {
+    Off,
+    Degree,
+    Contact,
+    Anchored,
+}
+
 #[wasm_bindgen]
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
@@ -42,24 +156,26 @@ pub enum WorldFeature {
     Antigravity,
     IntervalCountdown,
     PretensingCountdown,
+    ShapingSlewRate,
 }
 
 #[wasm_bindgen]
-pub fn default_world_feature(fabric_feature: WorldFeature) -> f32 {
+pub fn default_world_feature(fabric_feature: WorldFeature) -> Real {
     match fabric_feature {
-        WorldFeature::Gravity => 2e-7_f32,
-        WorldFeature::Antigravity => 0.001_f32,
-        WorldFeature::ShapingDrag => 0.0005_f32,
-        WorldFeature::Drag => 0.0001_f32,
-        WorldFeature::ShapingPretenstFactor => 0.3_f32,
-        WorldFeature::PretenstFactor => 0.03_f32,
-        WorldFeature::ShapingStiffnessFactor => 0.0005_f32,
-        WorldFeature::StiffnessFactor => 0.01_f32,
-        WorldFeature::IterationsPerFrame => 50_f32,
-        WorldFeature::IntervalCountdown => 2000_f32,
-        WorldFeature::PretensingCountdown => 10000_f32,
-        WorldFeature::VisualStrain => 1_f32,
-        WorldFeature::PushOverPull => 3_f32,
+        WorldFeature::Gravity => 2e-7,
+        WorldFeature::Antigravity => 0.001,
+        WorldFeature::ShapingDrag => 0.0005,
+        WorldFeature::Drag => 0.0001,
+        WorldFeature::ShapingPretenstFactor => 0.3,
+        WorldFeature::PretenstFactor => 0.03,
+        WorldFeature::ShapingStiffnessFactor => 0.0005,
+        WorldFeature::StiffnessFactor => 0.01,
+        WorldFeature::IterationsPerFrame => 50.0,
+        WorldFeature::IntervalCountdown => 2000.0,
+        WorldFeature::PretensingCountdown => 10000.0,
+        WorldFeature::VisualStrain => 1.0,
+        WorldFeature::PushOverPull => 3.0,
+        WorldFeature::ShapingSlewRate => 0.001,
     }
 }
 
@@ -0,0 +1,149 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use nalgebra::*;
+
+use crate::joint::Joint;
+use crate::real::Real;
+
+// A measuring tape with tension: holds two joints at a distance and remembers how hard that was.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Constraint {
+    pub(crate) alpha_index: usize,
+    pub(crate) omega_index: usize,
+    pub(crate) distance: Real,
+    pub(crate) force: Real,
+}
+
+impl Constraint {
+    pub fn new(alpha_index: usize, omega_index: usize, distance: Real) -> Constraint {
+        Constraint {
+            alpha_index,
+            omega_index,
+            distance,
+            force: 0.0,
+        }
+    }
+
+    pub fn involves(&self, joint_index: usize) -> bool {
+        self.alpha_index == joint_index || self.omega_index == joint_index
+    }
+
+    pub fn joint_removed(&mut self, index: usize) {
+        if self.alpha_index > index {
+            self.alpha_index -= 1;
+        }
+        if self.omega_index > index {
+            self.omega_index -= 1;
+        }
+    }
+
+    // Positive force pulls the joints together, negative pushes them apart. A fixed joint doesn't give,
+    // so the free one takes the whole correction, and between two fixed joints there's nothing to do.
+    pub fn hold(&mut self, joints: &mut [Joint]) {
+        let alpha = joints[self.alpha_index];
+        let omega = joints[self.omega_index];
+        let span = omega.location - alpha.location;
+        let length = span.magnitude();
+        if length == 0.0 {
+            return;
+        }
+        let unit = span / length;
+        let separating = (omega.velocity - alpha.velocity).dot(&unit);
+        let (alpha_share, omega_share, reduced_mass) = match (alpha.fixed, omega.fixed) {
+            (true, true) => return,
+            (true, false) => (0.0, 1.0, omega.interval_mass),
+            (false, true) => (1.0, 0.0, alpha.interval_mass),
+            (false, false) => {
+                let total_mass = alpha.interval_mass + omega.interval_mass;
+                (
+                    omega.interval_mass / total_mass,
+                    alpha.interval_mass / total_mass,
+                    alpha.interval_mass * omega.interval_mass / total_mass,
+                )
+            }
+        };
+        // the impulse this tick that cancels both the stretch and the separating speed
+        self.force = (length - self.distance + separating) * reduced_mass;
+        let correction: Vector3<Real> = unit * (length - self.distance);
+        let velocity_correction: Vector3<Real> = unit * separating;
+        let alpha = &mut joints[self.alpha_index];
+        alpha.location += correction * alpha_share;
+        alpha.velocity += velocity_correction * alpha_share;
+        let omega = &mut joints[self.omega_index];
+        omega.location -= correction * omega_share;
+        omega.velocity -= velocity_correction * omega_share;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::IntervalRole;
+    use crate::error::Error;
+    use crate::fixture::joints;
+    use crate::world::World;
+
+    use super::*;
+
+    fn pair(alpha_fixed: bool, omega_fixed: bool) -> Vec<Joint> {
+        let mut joints = vec![Joint::new(0.0, 0.0, 0.0), Joint::new(2.0, 0.0, 0.0)];
+        for (joint, fixed) in joints.iter_mut().zip([alpha_fixed, omega_fixed]) {
+            joint.interval_mass = 1.0;
+            joint.fixed = fixed;
+        }
+        joints
+    }
+
+    #[test]
+    fn a_fixed_joint_stays_and_the_free_one_takes_the_correction() {
+        let mut joints = pair(true, false);
+        Constraint::new(0, 1, 1.0).hold(&mut joints);
+        assert_eq!(joints[0].location, Point3::origin());
+        assert!((joints[1].location.x - 1.0).abs() < 1e-6);
+        let mut joints = pair(false, false);
+        Constraint::new(0, 1, 1.0).hold(&mut joints);
+        assert!((joints[0].location.x - 0.5).abs() < 1e-6);
+        assert!((joints[1].location.x - 1.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn two_fixed_joints_are_left_alone() {
+        let mut joints = pair(true, true);
+        let mut constraint = Constraint::new(0, 1, 1.0);
+        constraint.hold(&mut joints);
+        assert_eq!(joints[1].location.x, 2.0);
+        assert_eq!(constraint.force, 0.0);
+    }
+
+    #[test]
+    fn constraints_hold_their_distance_against_a_pull_and_report_the_force() {
+        let mut fabric = joints(3);
+        fabric
+            .add_interval_safe(0, 1, IntervalRole::Pull, 1.0, 0.5, 0.0)
+            .unwrap();
+        assert!(matches!(
+            fabric.create_constraint(1, 1, 1.0),
+            Err(Error::IdenticalJoints { index: 1 })
+        ));
+        assert!(matches!(
+            fabric.create_constraint(0, 1, -1.0),
+            Err(Error::IllegalLength { .. })
+        ));
+        let index = fabric.create_constraint(0, 1, 1.0).unwrap();
+        fabric.iterate_ticks(&World::new(), 100);
+        let span = fabric.joints[1].location - fabric.joints[0].location;
+        assert!(
+            (span.magnitude() - 1.0).abs() < 1e-5,
+            "{}",
+            span.magnitude()
+        );
+        assert!(fabric.get_constraint_force(index).unwrap() < 0.0);
+        fabric.remove_joint(2).unwrap();
+        assert_eq!(fabric.constraints.len(), 1);
+        fabric.remove_joint(0).unwrap();
+        assert!(fabric.constraints.is_empty());
+    }
+}
@@ -0,0 +1,219 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use crate::error::Error;
+use crate::fabric::Fabric;
+use crate::interval::Interval;
+use crate::real::Real;
+use crate::sensors::FabricSensors;
+
+// Closed-loop control from the Rust side, called before every tick of an iterate with what the
+// sensors read at the end of the last one. Commands go through the actuators, so that a controller
+// can do no more to the fabric than the motors on a built one. Send and Sync, since the fabric goes
+// to other threads through a FabricReader.
+pub trait Controller: Send + Sync {
+    fn control(&mut self, fabric_view: &FabricSensors, actuators: &mut Actuators);
+}
+
+impl Fabric {
+    // Replaces any controller there was.
+    pub fn set_controller(&mut self, controller: Box<dyn Controller>) {
+        self.controller = Some(controller);
+    }
+
+    pub fn take_controller(&mut self) -> Option<Box<dyn Controller>> {
+        self.controller.take()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Command {
+    Hold {
+        rest_length: Real,
+    },
+    Actuate {
+        rest_length: Real,
+        countdown: Real,
+    },
+    Twitch {
+        delta: Real,
+        attack: Real,
+        decay: Real,
+    },
+}
+
+// The commands of one call, carried out in order once the controller returns.
+#[derive(Clone, Debug, Default)]
+pub struct Actuators {
+    interval_count: usize,
+    commands: Vec<(usize, Command)>,
+}
+
+impl Actuators {
+    pub fn new(interval_count: usize) -> Actuators {
+        Actuators {
+            interval_count,
+            commands: Vec::new(),
+        }
+    }
+
+    // Straight to the rest length, no ramp.
+    pub fn hold(&mut self, interval_index: usize, rest_length: Real) -> Result<(), Error> {
+        self.check(interval_index, rest_length)?;
+        self.commands
+            .push((interval_index, Command::Hold { rest_length }));
+        Ok(())
+    }
+
+    // To the rest length over the countdown, through the interval's actuator when it has one.
+    pub fn actuate(
+        &mut self,
+        interval_index: usize,
+        rest_length: Real,
+        countdown: Real,
+    ) -> Result<(), Error> {
+        self.check(interval_index, rest_length)?;
+        if !(countdown.is_finite() && countdown > 0.0) {
+            return Err(Error::IllegalCountdown { countdown });
+        }
+        self.commands.push((
+            interval_index,
+            Command::Actuate {
+                rest_length,
+                countdown,
+            },
+        ));
+        Ok(())
+    }
+
+    pub fn twitch(
+        &mut self,
+        interval_index: usize,
+        delta: Real,
+        attack: Real,
+        decay: Real,
+    ) -> Result<(), Error> {
+        self.check(interval_index, delta)?;
+        if let Some(countdown) = [attack, decay]
+            .iter()
+            .copied()
+            .find(|countdown| !(countdown.is_finite() && *countdown > 0.0))
+        {
+            return Err(Error::IllegalCountdown { countdown });
+        }
+        self.commands.push((
+            interval_index,
+            Command::Twitch {
+                delta,
+                attack,
+                decay,
+            },
+        ));
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn apply(self, intervals: &mut [Interval]) {
+        for (index, command) in self.commands {
+            let interval = &mut intervals[index];
+            match command {
+                Command::Hold { rest_length } => {
+                    interval.length_0 = rest_length;
+                    interval.length_1 = rest_length;
+                    interval.length_nuance = 0.0;
+                    interval.attack = 0.0;
+                    interval.decay = 0.0;
+                }
+                Command::Actuate {
+                    rest_length,
+                    countdown,
+                } => interval.actuate(rest_length, countdown),
+                Command::Twitch {
+                    delta,
+                    attack,
+                    decay,
+                } => interval.actuate_twitch(attack, decay, delta),
+            }
+        }
+    }
+
+    fn check(&self, interval_index: usize, length: Real) -> Result<(), Error> {
+        if interval_index >= self.interval_count {
+            return Err(Error::IntervalOutOfRange {
+                index: interval_index,
+                count: self.interval_count,
+            });
+        }
+        if !(length.is_finite() && length > 0.0) {
+            return Err(Error::IllegalLength { length });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    use crate::fixture::chain;
+    use crate::world::World;
+
+    use super::*;
+
+    // Holds the first interval at half its length, and counts what age it was called at.
+    struct Holder {
+        calls: Arc<AtomicU32>,
+    }
+
+    impl Controller for Holder {
+        fn control(&mut self, fabric_view: &FabricSensors, actuators: &mut Actuators) {
+            assert_eq!(fabric_view.age(), self.calls.fetch_add(1, Ordering::SeqCst));
+            actuators.hold(0, 0.5).unwrap();
+        }
+    }
+
+    #[test]
+    fn commands_are_checked_before_they_are_taken() {
+        let mut actuators = Actuators::new(2);
+        assert!(actuators.is_empty());
+        assert!(matches!(
+            actuators.hold(2, 1.0),
+            Err(Error::IntervalOutOfRange { index: 2, count: 2 })
+        ));
+        assert!(matches!(
+            actuators.hold(0, 0.0),
+            Err(Error::IllegalLength { .. })
+        ));
+        assert!(matches!(
+            actuators.actuate(0, 1.0, 0.0),
+            Err(Error::IllegalCountdown { .. })
+        ));
+        assert!(matches!(
+            actuators.twitch(0, 0.1, 1.0, Real::NAN),
+            Err(Error::IllegalCountdown { .. })
+        ));
+        assert!(actuators.is_empty());
+        actuators.actuate(1, 1.0, 10.0).unwrap();
+        assert!(!actuators.is_empty());
+    }
+
+    #[test]
+    fn the_controller_is_called_before_every_tick_and_its_commands_land() {
+        let mut fabric = chain(2, 1.0);
+        let calls = Arc::new(AtomicU32::new(0));
+        fabric.set_controller(Box::new(Holder {
+            calls: calls.clone(),
+        }));
+        fabric.iterate_ticks(&World::new(), 3);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(fabric.intervals[0].length_1, 0.5);
+        assert!(fabric.take_controller().is_some());
+        assert!(fabric.take_controller().is_none());
+    }
+}
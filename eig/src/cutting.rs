@@ -0,0 +1,97 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use crate::error::Error;
+use crate::real::Real;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cut {
+    pub interval: usize,
+    pub length: Real,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StockBar {
+    pub stock_length: Real,
+    pub cuts: Vec<Cut>,
+    // what is left of the bar after the last cut and its kerf
+    pub offcut: Real,
+}
+
+// First fit decreasing: longest piece first, into the first bar with room, otherwise into a fresh bar
+// of the shortest stock it fits. Every cut costs its kerf on top of its length.
+pub fn plan(pieces: &[Cut], stock_lengths: &[Real], kerf: Real) -> Result<Vec<StockBar>, Error> {
+    if !(kerf.is_finite() && kerf >= 0.0) {
+        return Err(Error::IllegalLength { length: kerf });
+    }
+    let mut stock: Vec<Real> = stock_lengths.to_vec();
+    for &length in stock.iter() {
+        if !(length.is_finite() && length > 0.0) {
+            return Err(Error::IllegalLength { length });
+        }
+    }
+    stock.sort_by(Real::total_cmp);
+    let mut pieces = pieces.to_vec();
+    pieces.sort_by(|a, b| b.length.total_cmp(&a.length));
+    let mut bars: Vec<StockBar> = Vec::new();
+    for piece in pieces {
+        if let Some(bar) = bars.iter_mut().find(|bar| bar.offcut >= piece.length) {
+            bar.offcut = (bar.offcut - piece.length - kerf).max(0.0);
+            bar.cuts.push(piece);
+            continue;
+        }
+        let Some(&stock_length) = stock.iter().find(|&&length| length >= piece.length) else {
+            return Err(Error::PieceTooLong {
+                interval: piece.interval,
+                length: piece.length,
+            });
+        };
+        bars.push(StockBar {
+            stock_length,
+            cuts: vec![piece],
+            offcut: (stock_length - piece.length - kerf).max(0.0),
+        });
+    }
+    Ok(bars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cut(interval: usize, length: Real) -> Cut {
+        Cut { interval, length }
+    }
+
+    #[test]
+    fn nests_longest_first_into_the_shortest_stock_that_fits() {
+        let bars = plan(&[cut(0, 2.5), cut(1, 6.0), cut(2, 3.0)], &[10.0, 7.0], 0.5).unwrap();
+        assert_eq!(
+            bars,
+            [
+                StockBar {
+                    stock_length: 7.0,
+                    cuts: vec![cut(1, 6.0)],
+                    offcut: 0.5
+                },
+                StockBar {
+                    stock_length: 7.0,
+                    cuts: vec![cut(2, 3.0), cut(0, 2.5)],
+                    offcut: 0.5
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn refuses_pieces_longer_than_any_stock() {
+        assert!(matches!(
+            plan(&[cut(3, 11.0)], &[10.0], 0.0),
+            Err(Error::PieceTooLong { interval: 3, .. })
+        ));
+        assert!(plan(&[cut(0, 1.0)], &[10.0], -0.1).is_err());
+        assert!(plan(&[cut(0, 1.0)], &[0.0], 0.0).is_err());
+    }
+}
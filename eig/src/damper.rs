@@ -0,0 +1,156 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use nalgebra::*;
+
+use crate::joint::Joint;
+use crate::real::Real;
+
+const MIN_AMPLITUDE: Real = 1e-6;
+const PERIOD_TOLERANCE: Real = 0.25;
+const SUSTAINED_AMPLITUDE: Real = 0.9;
+const BOOST_DECAY: Real = 0.999;
+
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ActiveDamper {
+    pub(crate) ticks: u32,
+    pub(crate) last_velocity: Vector3<Real>,
+    pub(crate) last_crossing: u32,
+    pub(crate) half_period: Real,
+    pub(crate) peak_speed: Real,
+    pub(crate) last_peak_speed: Real,
+    pub(crate) boost: Real,
+    pub(crate) kinetic_energy: Real,
+}
+
+impl ActiveDamper {
+    pub fn new() -> ActiveDamper {
+        ActiveDamper {
+            ticks: 0,
+            last_velocity: zero(),
+            last_crossing: 0,
+            half_period: 0.0,
+            peak_speed: 0.0,
+            last_peak_speed: 0.0,
+            boost: 0.0,
+            kinetic_energy: 0.0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = ActiveDamper::new();
+    }
+
+    // zero-crossings of the centroid velocity, sustained at a steady period, mean resonance
+    pub fn observe(&mut self, joints: &[Joint], aggressiveness: Real, drag: Real) {
+        self.ticks += 1;
+        self.boost *= BOOST_DECAY;
+        if joints.is_empty() {
+            return;
+        }
+        let mut velocity: Vector3<Real> = zero();
+        for joint in joints {
+            velocity += &joint.velocity;
+        }
+        velocity /= joints.len() as Real;
+        let speed = velocity.magnitude();
+        if speed > self.peak_speed {
+            self.peak_speed = speed;
+        }
+        if velocity.dot(&self.last_velocity) < 0.0 {
+            let half_period = (self.ticks - self.last_crossing) as Real;
+            let steady = self.half_period > 0.0
+                && (half_period - self.half_period).abs() < self.half_period * PERIOD_TOLERANCE;
+            let sustained = self.peak_speed > MIN_AMPLITUDE
+                && self.peak_speed >= self.last_peak_speed * SUSTAINED_AMPLITUDE;
+            if steady && sustained {
+                self.boost = aggressiveness * drag;
+                trace_info!(
+                    frequency = self.frequency(),
+                    boost = self.boost,
+                    "oscillation damped"
+                );
+            }
+            self.half_period = if self.half_period == 0.0 {
+                half_period
+            } else {
+                (self.half_period + half_period) / 2.0
+            };
+            self.last_crossing = self.ticks;
+            self.last_peak_speed = self.peak_speed;
+            self.peak_speed = 0.0;
+        }
+        self.last_velocity = velocity;
+    }
+
+    // True when the energy has dropped since the last tick, which means it peaked in between. The
+    // energy starts over from zero, since the velocities are about to be.
+    pub fn kinetic_peak(&mut self, kinetic_energy: Real) -> bool {
+        if kinetic_energy < self.kinetic_energy {
+            self.kinetic_energy = 0.0;
+            return true;
+        }
+        self.kinetic_energy = kinetic_energy;
+        false
+    }
+
+    pub fn frequency(&self) -> Real {
+        if self.half_period == 0.0 {
+            0.0
+        } else {
+            1.0 / (2.0 * self.half_period)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every joint swinging along x, turning every half period, each swing the fraction of the last.
+    fn swing(damper: &mut ActiveDamper, half_period: u32, fraction: Real, half_periods: u32) {
+        let mut joints = vec![Joint::new(0.0, 1.0, 0.0); 2];
+        let mut speed = 1.0;
+        for half in 0..half_periods {
+            let sign = if half % 2 == 0 { 1.0 } else { -1.0 };
+            for _ in 0..half_period {
+                for joint in joints.iter_mut() {
+                    joint.velocity = Vector3::new(sign * speed, 0.0, 0.0);
+                }
+                damper.observe(&joints, 2.0, 0.1);
+            }
+            speed *= fraction;
+        }
+    }
+
+    #[test]
+    fn boosts_a_sustained_resonance() {
+        let mut damper = ActiveDamper::new();
+        swing(&mut damper, 10, 1.0, 6);
+        assert!(damper.boost > 0.19, "{}", damper.boost);
+        assert!(
+            (damper.frequency() - 0.05).abs() < 0.001,
+            "{}",
+            damper.frequency()
+        );
+    }
+
+    #[test]
+    fn leaves_a_dying_oscillation_alone() {
+        let mut damper = ActiveDamper::new();
+        swing(&mut damper, 10, 0.5, 6);
+        assert_eq!(damper.boost, 0.0);
+    }
+
+    #[test]
+    fn finds_the_kinetic_peak_once_the_energy_drops() {
+        let mut damper = ActiveDamper::new();
+        assert!(!damper.kinetic_peak(1.0));
+        assert!(!damper.kinetic_peak(2.0));
+        assert!(damper.kinetic_peak(1.5));
+        assert!(!damper.kinetic_peak(0.5));
+    }
+}
@@ -0,0 +1,136 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use crate::constraint::Constraint;
+use crate::interval::Interval;
+use crate::real::Real;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeployAction {
+    // let go of the constraints holding these joints together
+    Release {
+        alpha: usize,
+        omega: usize,
+    },
+    // pay out (or reel in) a cable to a new rest length over the countdown
+    LetOut {
+        interval: usize,
+        rest_length: Real,
+        countdown: Real,
+    },
+}
+
+// Unfolding from a packed state on a schedule, counted in ticks from the first scheduled step, and
+// the worst strain every interval went through on the way.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Deployment {
+    pub(crate) schedule: Vec<(u32, DeployAction)>,
+    pub(crate) elapsed: u32,
+    // largest magnitude, with its sign
+    pub(crate) peak_strains: Vec<Real>,
+}
+
+impl Deployment {
+    pub fn schedule(&mut self, tick: u32, action: DeployAction) {
+        let position = self
+            .schedule
+            .partition_point(|&(scheduled, _)| scheduled <= tick);
+        self.schedule.insert(position, (tick, action));
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.schedule.len()
+    }
+
+    // Carry out whatever is due, then record strains as they are after this tick.
+    pub fn advance(&mut self, intervals: &mut [Interval], constraints: &mut Vec<Constraint>) {
+        while let Some(&(tick, action)) = self.schedule.first() {
+            if tick > self.elapsed {
+                break;
+            }
+            self.schedule.remove(0);
+            match action {
+                DeployAction::Release { alpha, omega } => {
+                    constraints.retain(|constraint| {
+                        !(constraint.involves(alpha) && constraint.involves(omega))
+                    });
+                }
+                DeployAction::LetOut {
+                    interval,
+                    rest_length,
+                    countdown,
+                } => {
+                    if let Some(interval) = intervals.get_mut(interval) {
+                        interval.change_rest_length(rest_length, countdown);
+                    }
+                }
+            }
+        }
+        self.peak_strains.resize(intervals.len(), 0.0);
+        for (peak, interval) in self.peak_strains.iter_mut().zip(intervals.iter()) {
+            if interval.strain.abs() > peak.abs() {
+                *peak = interval.strain;
+            }
+        }
+        self.elapsed += 1;
+    }
+
+    // The last interval took the place of the removed one.
+    pub fn interval_removed(&mut self, index: usize, last: usize) {
+        self.schedule.retain(|&(_, action)| !matches!(action, DeployAction::LetOut { interval, .. } if interval == index));
+        for (_, action) in self.schedule.iter_mut() {
+            if let DeployAction::LetOut { interval, .. } = action {
+                if *interval == last {
+                    *interval = index;
+                }
+            }
+        }
+        if index < self.peak_strains.len() {
+            self.peak_strains.swap_remove(index);
+        }
+    }
+
+    pub fn joint_removed(&mut self, index: usize) {
+        self.schedule.retain(|&(_, action)| {
+            !matches!(action, DeployAction::Release { alpha, omega } if alpha == index || omega == index)
+        });
+        for (_, action) in self.schedule.iter_mut() {
+            if let DeployAction::Release { alpha, omega } = action {
+                for joint in [alpha, omega] {
+                    if *joint > index {
+                        *joint -= 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::chain;
+    use crate::world::World;
+
+    #[test]
+    fn stays_awake_and_busy_until_the_last_step() {
+        let mut fabric = chain(2, 1.0);
+        let mut world = World::new();
+        world.gravity = 0.0;
+        world.sleep_ticks = 2;
+        world.sleep_velocity = 0.001;
+        world.sleep_force = 0.001;
+        fabric.iterate_ticks(&world, 10);
+        assert!(fabric.asleep);
+        fabric.schedule_let_out(40, 0, 1.2, 10.0).unwrap();
+        assert!(!fabric.asleep);
+        assert!(fabric.iterate_ticks(&world, 20));
+        assert!(!fabric.asleep);
+        fabric.iterate_ticks(&world, 30);
+        assert_eq!(fabric.get_deployment_remaining(), 0);
+        assert_eq!(fabric.intervals[0].length_1, 1.2);
+    }
+}
@@ -0,0 +1,319 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use std::fmt::{Display, Formatter};
+
+use wasm_bindgen::prelude::*;
+
+use crate::real::Real;
+use crate::snapshot::Section;
+
+#[derive(Debug, Clone)]
+pub enum Error {
+    JointOutOfRange {
+        index: usize,
+        count: usize,
+    },
+    IntervalOutOfRange {
+        index: usize,
+        count: usize,
+    },
+    FaceOutOfRange {
+        index: usize,
+        count: usize,
+    },
+    AnchorOutOfRange {
+        index: usize,
+        count: usize,
+    },
+    ConstraintOutOfRange {
+        index: usize,
+        count: usize,
+    },
+    RegionOutOfRange {
+        index: usize,
+        count: usize,
+    },
+    SensorOutOfRange {
+        index: usize,
+        count: usize,
+    },
+    OscillatorOutOfRange {
+        index: usize,
+        count: usize,
+    },
+    StaleHandle {
+        slot: u32,
+        generation: u32,
+    },
+    IdenticalJoints {
+        index: usize,
+    },
+    IllegalLength {
+        length: Real,
+    },
+    IllegalStiffness {
+        stiffness: Real,
+    },
+    IllegalCountdown {
+        countdown: Real,
+    },
+    WrongLength {
+        expected: usize,
+        actual: usize,
+    },
+    NotALoop {
+        tag: u16,
+    },
+    IllegalTolerance {
+        tolerance: Real,
+    },
+    IllegalCapacity {
+        capacity: Real,
+    },
+    IllegalFrequency {
+        frequency: Real,
+    },
+    IllegalPosition {
+        position: Real,
+    },
+    IllegalTension {
+        tension: Real,
+    },
+    IllegalMass {
+        mass: Real,
+    },
+    IllegalForce {
+        force: Real,
+    },
+    IllegalSpeed {
+        speed: Real,
+    },
+    IllegalRange {
+        min: Real,
+        max: Real,
+    },
+    DegreeExceeded {
+        joint: usize,
+        max: usize,
+    },
+    Undertriangulated {
+        joint: usize,
+        pulls: usize,
+        min: usize,
+    },
+    IllegalMedium {
+        top: Real,
+        drag: Real,
+        density: Real,
+    },
+    IllegalWind {
+        speed: Real,
+        gustiness: Real,
+        turbulence: Real,
+        drag: Real,
+    },
+    IllegalAgingCurve {
+        stiffness_loss: Real,
+        creep: Real,
+    },
+    IllegalTimeStep {
+        time_step: Real,
+        acceleration_limit: Real,
+    },
+    IllegalCurve {
+        point: usize,
+    },
+    IllegalDamping {
+        damping: Real,
+    },
+    IllegalStrain {
+        strain: Real,
+    },
+    IllegalFlow {
+        flow: Real,
+    },
+    IllegalAmplitude {
+        amplitude: Real,
+    },
+    IllegalCoupling {
+        weight: Real,
+    },
+    IllegalFriction {
+        along: Real,
+        across: Real,
+    },
+    PieceTooLong {
+        interval: usize,
+        length: Real,
+    },
+    NoAnchors,
+    EmptyPointCloud,
+    CorruptSnapshot {
+        offset: usize,
+    },
+    UnsupportedVersion {
+        version: u8,
+    },
+    UnsupportedCodec {
+        codec: u8,
+    },
+    MissingSnapshotSection {
+        section: Section,
+    },
+    MalformedCommand {
+        offset: usize,
+    },
+    IllegalGrowth {
+        reason: String,
+    },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::JointOutOfRange { index, count } =>
+                write!(f, "joint {index} out of range ({count} joints)"),
+            Error::IntervalOutOfRange { index, count } =>
+                write!(f, "interval {index} out of range ({count} intervals)"),
+            Error::FaceOutOfRange { index, count } =>
+                write!(f, "face {index} out of range ({count} faces)"),
+            Error::AnchorOutOfRange { index, count } =>
+                write!(f, "anchor {index} out of range ({count} anchors)"),
+            Error::ConstraintOutOfRange { index, count } =>
+                write!(f, "constraint {index} out of range ({count} constraints)"),
+            Error::RegionOutOfRange { index, count } =>
+                write!(f, "region {index} out of range ({count} regions)"),
+            Error::SensorOutOfRange { index, count } =>
+                write!(f, "sensor {index} out of range ({count} sensors)"),
+            Error::OscillatorOutOfRange { index, count } =>
+                write!(f, "oscillator {index} out of range ({count} oscillators)"),
+            Error::StaleHandle { slot, generation } =>
+                write!(f, "handle {slot}.{generation} refers to a removed element"),
+            Error::IdenticalJoints { index } =>
+                write!(f, "interval would connect joint {index} to itself"),
+            Error::IllegalLength { length } =>
+                write!(f, "illegal length {length}"),
+            Error::IllegalStiffness { stiffness } =>
+                write!(f, "illegal stiffness {stiffness}"),
+            Error::IllegalCountdown { countdown } =>
+                write!(f, "illegal countdown {countdown}"),
+            Error::WrongLength { expected, actual } =>
+                write!(f, "expected {expected} values, got {actual}"),
+            Error::NotALoop { tag } =>
+                write!(f, "pulls tagged {tag} do not form a closed loop"),
+            Error::IllegalTolerance { tolerance } =>
+                write!(f, "illegal tolerance {tolerance}"),
+            Error::IllegalCapacity { capacity } =>
+                write!(f, "illegal anchor capacity {capacity}"),
+            Error::IllegalFrequency { frequency } =>
+                write!(f, "illegal frequency {frequency}"),
+            Error::IllegalPosition { position } =>
+                write!(f, "illegal position {position} along an interval"),
+            Error::IllegalTension { tension } =>
+                write!(f, "illegal tension {tension}"),
+            Error::IllegalMass { mass } =>
+                write!(f, "illegal mass {mass}"),
+            Error::IllegalForce { force } =>
+                write!(f, "illegal force {force}"),
+            Error::IllegalSpeed { speed } =>
+                write!(f, "illegal speed {speed}"),
+            Error::IllegalRange { min, max } =>
+                write!(f, "illegal range {min} to {max}"),
+            Error::DegreeExceeded { joint, max } =>
+                write!(f, "joint {joint} already has the maximum of {max} intervals"),
+            Error::Undertriangulated { joint, pulls, min } =>
+                write!(f, "push end at joint {joint} would have {pulls} pulls, needs {min}"),
+            Error::IllegalMedium { top, drag, density } =>
+                write!(f, "illegal medium: top {top}, drag {drag}, density {density}"),
+            Error::IllegalWind { speed, gustiness, turbulence, drag } =>
+                write!(f, "illegal wind: speed {speed}, gustiness {gustiness}, turbulence {turbulence}, drag {drag}"),
+            Error::IllegalAgingCurve { stiffness_loss, creep } =>
+                write!(f, "illegal aging curve: stiffness loss {stiffness_loss}, creep {creep}"),
+            Error::IllegalTimeStep { time_step, acceleration_limit } =>
+                write!(f, "illegal time step {time_step} with acceleration limit {acceleration_limit}"),
+            Error::IllegalCurve { point } =>
+                write!(f, "illegal stress-strain curve at point {point}"),
+            Error::IllegalDamping { damping } =>
+                write!(f, "illegal damping {damping}"),
+            Error::IllegalStrain { strain } =>
+                write!(f, "illegal strain {strain}"),
+            Error::IllegalFlow { flow } =>
+                write!(f, "illegal flow {flow}, not from zero to one"),
+            Error::IllegalAmplitude { amplitude } =>
+                write!(f, "illegal amplitude {amplitude}"),
+            Error::IllegalCoupling { weight } =>
+                write!(f, "illegal coupling weight {weight}"),
+            Error::IllegalFriction { along, across } =>
+                write!(f, "illegal friction: along {along}, across {across}"),
+            Error::PieceTooLong { interval, length } =>
+                write!(f, "interval {interval} needs {length}, longer than any stock"),
+            Error::NoAnchors =>
+                write!(f, "fabric has no anchors"),
+            Error::EmptyPointCloud =>
+                write!(f, "point cloud has no points"),
+            Error::CorruptSnapshot { offset } =>
+                write!(f, "corrupt snapshot chunk at byte {offset}"),
+            Error::UnsupportedVersion { version } =>
+                write!(f, "unsupported snapshot version {version}"),
+            Error::UnsupportedCodec { codec } =>
+                write!(f, "unsupported snapshot codec {codec}"),
+            Error::MissingSnapshotSection { section } =>
+                write!(f, "snapshot has no {section:?} section"),
+            Error::MalformedCommand { offset } =>
+                write!(f, "malformed command at word {offset}"),
+            Error::IllegalGrowth { reason } =>
+                write!(f, "illegal growth: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for JsValue {
+    fn from(error: Error) -> Self {
+        JsValue::from_str(&error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::IntervalRole;
+    use crate::fabric::Fabric;
+    use crate::fixture::chain;
+
+    use super::*;
+
+    #[test]
+    fn bad_indices_come_back_as_errors_that_say_what_was_wrong() {
+        let mut fabric = Fabric::new(0);
+        fabric.create_joint(0.0, 1.0, 0.0).unwrap();
+        let error = fabric
+            .add_interval_safe(0, 1, IntervalRole::Pull, 1.0, 1.0, 0.0)
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::JointOutOfRange { index: 1, count: 1 }
+        ));
+        assert_eq!(error.to_string(), "joint 1 out of range (1 joints)");
+        let error = fabric.change_rest_length(0, 1.0, 1.0).unwrap_err();
+        assert_eq!(error.to_string(), "interval 0 out of range (0 intervals)");
+    }
+
+    #[test]
+    fn countdowns_must_be_real_and_positive() {
+        let mut fabric = chain(2, 1.0);
+        for countdown in [0.0, -1.0, Real::NAN, Real::INFINITY] {
+            assert!(
+                matches!(
+                    fabric.change_rest_length(0, 1.0, countdown),
+                    Err(Error::IllegalCountdown { .. })
+                ),
+                "{}",
+                countdown
+            );
+        }
+    }
+}
@@ -0,0 +1,58 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use nalgebra::*;
+
+use crate::constants::AlarmLevel;
+use crate::real::Real;
+
+pub const MAX_EVENTS: usize = 10000;
+
+#[derive(Clone, Copy, Debug)]
+pub struct ContactEvent {
+    pub joint: usize,
+    pub normal: Vector3<Real>,
+    pub impulse: Real,
+    pub tick: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum FabricEvent {
+    Contact(ContactEvent),
+    FabricAsleep {
+        tick: u32,
+    },
+    // the interval that was last now lives at `index`, unless the removed one was last
+    IntervalRemoved {
+        index: usize,
+        moved_from: Option<usize>,
+    },
+    // every joint above `index` moved down one, after the intervals at it were removed
+    JointRemoved {
+        index: usize,
+    },
+    AnchorReleased {
+        joint: usize,
+        reaction: Vector3<Real>,
+        tick: u32,
+    },
+    StrainAlarm {
+        interval: usize,
+        level: AlarmLevel,
+        strain: Real,
+        tick: u32,
+    },
+    // followed by IntervalRemoved if broken intervals are removed
+    IntervalBroken {
+        interval: usize,
+        strain: Real,
+        tick: u32,
+    },
+    // the bud on the face grows no further
+    GrowthStopped {
+        face: [usize; 3],
+        tick: u32,
+    },
+}
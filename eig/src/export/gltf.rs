@@ -0,0 +1,153 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+// A self-contained glTF 2.0 document, the buffer embedded as a data URI. The geometry is what the
+// View shows: strain-colored lines for the intervals and triangles for the faces.
+
+use std::fmt::Write;
+
+use crate::fabric::Fabric;
+use crate::view::View;
+use crate::world::World;
+
+const FLOAT: u32 = 5126;
+const LINES: u32 = 1;
+const TRIANGLES: u32 = 4;
+const FACE_GRAY: f32 = 0.8_f32;
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub fn document(fabric: &Fabric, world: &World) -> String {
+    let mut view = View::on_fabric(fabric);
+    view.render(fabric, world);
+    let face_colors = vec![FACE_GRAY; view.face_vertex_locations.len()];
+    let mut parts = Document::default();
+    let mut primitives = Vec::new();
+    if !view.line_locations.is_empty() {
+        let position = parts.accessor(&view.line_locations, true);
+        let color = parts.accessor(&view.line_colors, false);
+        primitives.push(format!(
+            r#"{{"attributes":{{"POSITION":{position},"COLOR_0":{color}}},"mode":{LINES}}}"#
+        ));
+    }
+    if !view.face_vertex_locations.is_empty() {
+        let position = parts.accessor(&view.face_vertex_locations, true);
+        let normal = parts.accessor(&view.face_normals, false);
+        let color = parts.accessor(&face_colors, false);
+        primitives.push(format!(
+            r#"{{"attributes":{{"POSITION":{position},"NORMAL":{normal},"COLOR_0":{color}}},"mode":{TRIANGLES}}}"#
+        ));
+    }
+    format!(
+        concat!(
+            r#"{{"asset":{{"version":"2.0","generator":"eig"}},"scene":0,"scenes":[{{"nodes":[0]}}],"#,
+            r#""nodes":[{{"mesh":0}}],"meshes":[{{"primitives":[{}]}}],"#,
+            r#""buffers":[{{"byteLength":{},"uri":"data:application/octet-stream;base64,{}"}}],"#,
+            r#""bufferViews":[{}],"accessors":[{}]}}"#,
+        ),
+        primitives.join(","),
+        parts.buffer.len(),
+        base64(&parts.buffer),
+        parts.buffer_views.join(","),
+        parts.accessors.join(","),
+    )
+}
+
+#[derive(Default)]
+struct Document {
+    buffer: Vec<u8>,
+    buffer_views: Vec<String>,
+    accessors: Vec<String>,
+}
+
+impl Document {
+    // One view and one accessor per attribute, of xyz triples. Positions need their bounds.
+    fn accessor(&mut self, values: &[f32], bounded: bool) -> usize {
+        let offset = self.buffer.len();
+        for value in values {
+            self.buffer.extend_from_slice(&value.to_le_bytes());
+        }
+        let view = self.buffer_views.len();
+        self.buffer_views.push(format!(
+            r#"{{"buffer":0,"byteOffset":{offset},"byteLength":{}}}"#,
+            self.buffer.len() - offset,
+        ));
+        let mut accessor = format!(
+            r#"{{"bufferView":{view},"componentType":{FLOAT},"count":{},"type":"VEC3""#,
+            values.len() / 3,
+        );
+        if bounded {
+            let mut min = [f32::MAX; 3];
+            let mut max = [f32::MIN; 3];
+            for triple in values.chunks(3) {
+                for axis in 0..3 {
+                    min[axis] = min[axis].min(triple[axis]);
+                    max[axis] = max[axis].max(triple[axis]);
+                }
+            }
+            let _ = write!(
+                accessor,
+                r#","min":[{},{},{}],"max":[{},{},{}]"#,
+                min[0], min[1], min[2], max[0], max[1], max[2]
+            );
+        }
+        accessor.push('}');
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let triple = chunk
+            .iter()
+            .enumerate()
+            .fold(0_u32, |triple, (index, &byte)| {
+                triple | (byte as u32) << (16 - 8 * index)
+            });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(BASE64[((triple >> (18 - 6 * index)) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::IntervalRole;
+
+    use super::*;
+
+    #[test]
+    fn pads_base64_to_whole_quads() {
+        assert_eq!(base64(b"Man"), "TWFu");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"M"), "TQ==");
+        assert_eq!(base64(b""), "");
+    }
+
+    #[test]
+    fn carries_a_line_per_interval() {
+        let mut fabric = Fabric::new(0);
+        fabric.create_joint(0.0, 1.0, 0.0).unwrap();
+        fabric.create_joint(1.0, 1.0, 0.0).unwrap();
+        fabric
+            .add_interval_safe(0, 1, IntervalRole::Pull, 1.0, 1.0, 0.0)
+            .unwrap();
+        let document = document(&fabric, &World::new());
+        // two ends of three floats for the positions, and again for the colors
+        assert!(document.contains(r#""byteLength":48,"#), "{}", document);
+        assert!(
+            document.contains(r#""count":2,"type":"VEC3","min":[0,1,0],"max":[1,1,0]"#),
+            "{}",
+            document
+        );
+        assert!(!document.contains("NORMAL"));
+    }
+}
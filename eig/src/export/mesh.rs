@@ -0,0 +1,145 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+// Closed triangle meshes for printing a scale model: the faces, plus a capped cylinder per push
+// when the strut radius is above zero.
+
+use std::fmt::Write;
+
+use nalgebra::*;
+
+use crate::fabric::Fabric;
+use crate::real::Real;
+
+const CYLINDER_SIDES: usize = 12;
+
+pub type Triangle = [Point3<Real>; 3];
+
+pub fn triangles(fabric: &Fabric, strut_radius: Real) -> Vec<Triangle> {
+    let mut triangles: Vec<Triangle> = fabric
+        .faces
+        .iter()
+        .map(|face| face.joints().map(|index| fabric.joints[index].location))
+        .collect();
+    if strut_radius > 0.0 {
+        for push in fabric.intervals.iter().filter(|interval| interval.push) {
+            let alpha = fabric.joints[push.alpha_index].location;
+            let omega = fabric.joints[push.omega_index].location;
+            cylinder(&mut triangles, alpha, omega, strut_radius);
+        }
+    }
+    triangles
+}
+
+pub fn obj(triangles: &[Triangle]) -> String {
+    let mut obj = String::from("# eig\n");
+    for vertex in triangles.iter().flatten() {
+        let _ = writeln!(obj, "v {} {} {}", vertex.x, vertex.y, vertex.z);
+    }
+    for index in 0..triangles.len() {
+        let first = index * 3 + 1;
+        let _ = writeln!(obj, "f {} {} {}", first, first + 1, first + 2);
+    }
+    obj
+}
+
+pub fn stl(triangles: &[Triangle]) -> String {
+    let mut stl = String::from("solid eig\n");
+    for [a, b, c] in triangles {
+        let normal = (b - a)
+            .cross(&(c - a))
+            .try_normalize(Real::EPSILON)
+            .unwrap_or_else(zero);
+        let _ = writeln!(stl, "facet normal {} {} {}", normal.x, normal.y, normal.z);
+        stl.push_str("outer loop\n");
+        for vertex in [a, b, c] {
+            let _ = writeln!(stl, "vertex {} {} {}", vertex.x, vertex.y, vertex.z);
+        }
+        stl.push_str("endloop\nendfacet\n");
+    }
+    stl.push_str("endsolid eig\n");
+    stl
+}
+
+// Wound so that every normal points outward.
+fn cylinder(triangles: &mut Vec<Triangle>, alpha: Point3<Real>, omega: Point3<Real>, radius: Real) {
+    let Some(axis) = (omega - alpha).try_normalize(Real::EPSILON) else {
+        return;
+    };
+    let across = if axis.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::y()
+    };
+    let u = axis.cross(&across).normalize();
+    let v = axis.cross(&u);
+    let rim = |center: Point3<Real>, side: usize| {
+        let angle = crate::real::consts::TAU * side as Real / CYLINDER_SIDES as Real;
+        center + (u * angle.cos() + v * angle.sin()) * radius
+    };
+    for side in 0..CYLINDER_SIDES {
+        let next = side + 1;
+        let (alpha_0, alpha_1) = (rim(alpha, side), rim(alpha, next));
+        let (omega_0, omega_1) = (rim(omega, side), rim(omega, next));
+        triangles.push([alpha_0, alpha_1, omega_1]);
+        triangles.push([alpha_0, omega_1, omega_0]);
+        triangles.push([omega, omega_0, omega_1]);
+        triangles.push([alpha, alpha_1, alpha_0]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::IntervalRole;
+
+    use super::*;
+
+    fn strut() -> Fabric {
+        let mut fabric = Fabric::new(0);
+        fabric.create_joint(0.0, 1.0, 0.0).unwrap();
+        fabric.create_joint(0.0, 3.0, 0.0).unwrap();
+        fabric
+            .add_interval_safe(0, 1, IntervalRole::Push, 1.0, 1.0, 0.0)
+            .unwrap();
+        fabric
+    }
+
+    // Positive only when every triangle is wound outward.
+    fn signed_volume(triangles: &[Triangle]) -> Real {
+        triangles
+            .iter()
+            .map(|[a, b, c]| a.coords.dot(&b.coords.cross(&c.coords)) / 6.0)
+            .sum()
+    }
+
+    #[test]
+    fn struts_are_closed_prisms_wound_outward() {
+        let triangles = triangles(&strut(), 0.1);
+        assert_eq!(triangles.len(), CYLINDER_SIDES * 4);
+        let sides = CYLINDER_SIDES as Real;
+        let prism = sides / 2.0 * 0.01 * (crate::real::consts::TAU / sides).sin() * 2.0;
+        assert!(
+            (signed_volume(&triangles) - prism).abs() < 1e-5,
+            "{} {}",
+            signed_volume(&triangles),
+            prism
+        );
+        assert!(super::triangles(&strut(), 0.0).is_empty());
+    }
+
+    #[test]
+    fn obj_indexes_three_fresh_vertices_per_face() {
+        let obj = obj(&triangles(&strut(), 0.1));
+        assert_eq!(
+            obj.lines().filter(|line| line.starts_with("v ")).count(),
+            CYLINDER_SIDES * 12
+        );
+        let last = obj.lines().last().unwrap();
+        let top = CYLINDER_SIDES * 12;
+        assert_eq!(last, format!("f {} {} {}", top - 2, top - 1, top));
+        let stl = stl(&triangles(&strut(), 0.1));
+        assert_eq!(stl.matches("endfacet").count(), CYLINDER_SIDES * 4);
+    }
+}
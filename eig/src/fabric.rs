@@ -3,26 +3,124 @@
  * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
  */
 
+use std::collections::{HashMap, VecDeque};
+
 use nalgebra::*;
 use wasm_bindgen::prelude::*;
 
+use crate::anchor::Anchor;
+use crate::api::IntervalDescriptor;
+use crate::boundary::{BoundaryForces, BoundaryLoads, BoundaryRecording};
+use crate::collision;
+use crate::command::{self, whole, MAX_WHOLE};
 use crate::constants::*;
+use crate::constraint::Constraint;
+use crate::controller::{Actuators, Controller};
+use crate::cutting::{self, Cut, StockBar};
+use crate::damper::ActiveDamper;
+use crate::deploy::{DeployAction, Deployment};
+use crate::error::Error;
+use crate::event::{ContactEvent, FabricEvent, MAX_EVENTS};
+use crate::export;
 use crate::face::Face;
-use crate::interval::Interval;
-use crate::joint::Joint;
+use crate::golden::Digest;
+use crate::handle::{FaceId, Handles, IntervalId, JointId};
+use crate::hardware::{Catalog, Unbuildable};
+use crate::integrator::{self, Step};
+#[cfg(feature = "parallel")]
+use crate::interval::Contribution;
+use crate::interval::{Actuator, Attachment, Curve, Interval};
+use crate::joint::{Friction, Joint};
+#[cfg(not(feature = "parallel"))]
+use crate::lanes::Lanes;
+use crate::momentum::Momentum;
+use crate::muscle::{Muscle, Wave};
+use crate::oscillator::{Coupling, Oscillator, PatternGenerator};
+use crate::real::{reals, single, Real};
+use crate::region::{Region, RegionStrain, RegionWatch};
+use crate::report;
+use crate::role::RoleDefaults;
+use crate::sensor::Sensor;
+use crate::sensors::Readings;
+use crate::sequencer::{Cue, Sequencer, Target};
+use crate::skin;
+use crate::snapshot;
+use crate::tenscript::growth::{Bud, Growth, Twist};
+use crate::tenscript::parse_growth;
 use crate::world::World;
 
-pub const DEFAULT_STRAIN_LIMITS: [f32; 4] = [0_f32, -1e9_f32, 1e9_f32, 0_f32];
+const PAYLOAD_STIFFNESS: Real = 10.0;
+const SETTLING_PROBE_TICKS: u32 = 600;
+const SETTLING_WINDOW_TICKS: u32 = 50;
+const MAX_SETTLED_ITERATIONS: usize = 100;
+const SEQUENCE_ROW: usize = 6;
+const TWIST_ANGLE: Real = crate::real::consts::FRAC_PI_6;
+const TWIST_HEIGHT: Real = 1.6;
+
+pub const DEFAULT_STRAIN_LIMITS: [Real; 4] = [0.0, -1e9, 1e9, 0.0];
+
+#[derive(Clone, Debug)]
+pub struct LengthFamily {
+    pub role: IntervalRole,
+    pub mean: Real,
+    pub intervals: Vec<usize>,
+}
 
 #[wasm_bindgen]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Fabric {
     pub age: u32,
     pub(crate) stage: Stage,
     pub(crate) joints: Vec<Joint>,
     pub(crate) intervals: Vec<Interval>,
     pub(crate) faces: Vec<Face>,
-    pub(crate) pretensing_countdown: f32,
-    pub(crate) strain_limits: [f32; 4],
+    pub(crate) pretensing_countdown: Real,
+    pub(crate) strain_limits: [Real; 4],
+    pub(crate) damper: ActiveDamper,
+    pub(crate) floating: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) events: Vec<FabricEvent>,
+    pub(crate) asleep: bool,
+    pub(crate) anchors: Vec<Anchor>,
+    pub(crate) ground_motion_clock: (u32, Real),
+    pub(crate) frozen: bool,
+    pub(crate) constraints: Vec<Constraint>,
+    pub(crate) momentum: Option<(u32, Momentum, Momentum)>,
+    pub(crate) regions: Vec<RegionWatch>,
+    pub(crate) sensors: Vec<Sensor>,
+    pub(crate) muscles: Vec<Muscle>,
+    pub(crate) pattern_generator: Option<PatternGenerator>,
+    pub(crate) deployment: Option<Deployment>,
+    pub(crate) sequencer: Option<Sequencer>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) growth: Option<Growth>,
+    pub(crate) max_degree: usize,
+    pub(crate) min_push_pulls: usize,
+    pub(crate) labels: HashMap<usize, String>,
+    pub(crate) actuation_energy: Real,
+    // pushed on every tick of the next iterate, then dropped
+    pub(crate) external_forces: Vec<(usize, Vector3<Real>)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) boundary_recording: Option<BoundaryRecording>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) boundary_loads: Option<BoundaryLoads>,
+    // max joint speed and max strain rate, per tick, over the last iterates
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) settling: VecDeque<(Real, Real)>,
+    #[cfg(not(feature = "parallel"))]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) lanes: Lanes,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) readings: Option<Readings>,
+    // stays with this fabric, clones go without
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) controller: Option<Box<dyn Controller>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) joint_handles: Handles,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) interval_handles: Handles,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) face_handles: Handles,
 }
 
 #[wasm_bindgen]
@@ -31,11 +129,42 @@ impl Fabric {
         Fabric {
             age: 0,
             stage: Stage::Growing,
-            pretensing_countdown: 0_f32,
+            pretensing_countdown: 0.0,
             joints: Vec::with_capacity(joint_count),
             intervals: Vec::with_capacity(joint_count * 10),
             faces: Vec::with_capacity(joint_count),
             strain_limits: DEFAULT_STRAIN_LIMITS,
+            damper: ActiveDamper::new(),
+            floating: false,
+            events: Vec::new(),
+            asleep: false,
+            anchors: Vec::new(),
+            ground_motion_clock: (0, 0.0),
+            frozen: false,
+            constraints: Vec::new(),
+            momentum: None,
+            regions: Vec::new(),
+            sensors: Vec::new(),
+            muscles: Vec::new(),
+            pattern_generator: None,
+            deployment: None,
+            sequencer: None,
+            growth: None,
+            max_degree: 0,
+            min_push_pulls: 0,
+            labels: HashMap::new(),
+            actuation_energy: 0.0,
+            external_forces: Vec::new(),
+            boundary_recording: None,
+            boundary_loads: None,
+            settling: VecDeque::with_capacity(MAX_SETTLED_ITERATIONS),
+            #[cfg(not(feature = "parallel"))]
+            lanes: Lanes::default(),
+            readings: None,
+            controller: None,
+            joint_handles: Handles::default(),
+            interval_handles: Handles::default(),
+            face_handles: Handles::default(),
         }
     }
 
@@ -45,8 +174,34 @@ impl Fabric {
         self.joints.clear();
         self.intervals.clear();
         self.faces.clear();
+        self.damper.reset();
+        self.events.clear();
+        self.anchors.clear();
+        self.frozen = false;
+        self.constraints.clear();
+        self.momentum = None;
+        self.regions.clear();
+        self.sensors.clear();
+        self.muscles.clear();
+        self.pattern_generator = None;
+        self.deployment = None;
+        self.sequencer = None;
+        self.growth = None;
+        self.labels.clear();
+        self.actuation_energy = 0.0;
+        self.external_forces.clear();
+        self.boundary_recording = None;
+        self.boundary_loads = None;
+        self.settling.clear();
+        self.readings = None;
+        self.controller = None;
+        self.joint_handles.sync(0);
+        self.interval_handles.sync(0);
+        self.face_handles.sync(0);
+        self.wake();
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn clone(&self) -> Fabric {
         Fabric {
             age: self.age,
@@ -56,6 +211,37 @@ impl Fabric {
             intervals: self.intervals.clone(),
             faces: self.faces.clone(),
             strain_limits: DEFAULT_STRAIN_LIMITS,
+            damper: self.damper,
+            floating: self.floating,
+            events: Vec::new(),
+            asleep: self.asleep,
+            anchors: self.anchors.clone(),
+            ground_motion_clock: self.ground_motion_clock,
+            frozen: self.frozen,
+            constraints: self.constraints.clone(),
+            momentum: self.momentum,
+            regions: self.regions.clone(),
+            sensors: self.sensors.clone(),
+            muscles: self.muscles.clone(),
+            pattern_generator: self.pattern_generator.clone(),
+            deployment: self.deployment.clone(),
+            sequencer: self.sequencer.clone(),
+            growth: self.growth.clone(),
+            max_degree: self.max_degree,
+            min_push_pulls: self.min_push_pulls,
+            labels: self.labels.clone(),
+            actuation_energy: self.actuation_energy,
+            external_forces: self.external_forces.clone(),
+            boundary_recording: self.boundary_recording.clone(),
+            boundary_loads: self.boundary_loads.clone(),
+            settling: self.settling.clone(),
+            #[cfg(not(feature = "parallel"))]
+            lanes: Lanes::default(),
+            readings: self.readings.clone(),
+            controller: None,
+            joint_handles: self.joint_handles.clone(),
+            interval_handles: self.interval_handles.clone(),
+            face_handles: self.face_handles.clone(),
         }
     }
 
@@ -71,20 +257,261 @@ impl Fabric {
         self.faces.len() as u16
     }
 
-    pub fn create_joint(&mut self, x: f32, y: f32, z: f32) -> usize {
+    // Handles survive removals elsewhere in the fabric; indices shift under them.
+    pub fn get_joint_id(&mut self, index: usize) -> Result<JointId, Error> {
+        self.check_joint(index)?;
+        let (slot, generation) = self.joint_handles.issue(index, self.joints.len());
+        Ok(JointId { slot, generation })
+    }
+
+    pub fn get_joint_index(&mut self, id: JointId) -> Result<usize, Error> {
+        self.joint_handles.sync(self.joints.len());
+        self.joint_handles
+            .resolve(id.slot, id.generation)
+            .ok_or(Error::StaleHandle {
+                slot: id.slot,
+                generation: id.generation,
+            })
+    }
+
+    pub fn get_interval_id(&mut self, index: usize) -> Result<IntervalId, Error> {
+        self.check_interval(index)?;
+        let (slot, generation) = self.interval_handles.issue(index, self.intervals.len());
+        Ok(IntervalId { slot, generation })
+    }
+
+    pub fn get_interval_index(&mut self, id: IntervalId) -> Result<usize, Error> {
+        self.interval_handles.sync(self.intervals.len());
+        self.interval_handles
+            .resolve(id.slot, id.generation)
+            .ok_or(Error::StaleHandle {
+                slot: id.slot,
+                generation: id.generation,
+            })
+    }
+
+    pub fn get_face_id(&mut self, index: usize) -> Result<FaceId, Error> {
+        self.check_face(index)?;
+        let (slot, generation) = self.face_handles.issue(index, self.faces.len());
+        Ok(FaceId { slot, generation })
+    }
+
+    pub fn get_face_index(&mut self, id: FaceId) -> Result<usize, Error> {
+        self.face_handles.sync(self.faces.len());
+        self.face_handles
+            .resolve(id.slot, id.generation)
+            .ok_or(Error::StaleHandle {
+                slot: id.slot,
+                generation: id.generation,
+            })
+    }
+
+    pub fn create_joint(&mut self, x: Real, y: Real, z: Real) -> Result<usize, Error> {
+        for coordinate in [x, y, z] {
+            if !coordinate.is_finite() {
+                return Err(Error::IllegalLength { length: coordinate });
+            }
+        }
+        trace_debug!(x, y, z, "create joint");
+        self.wake();
         let index = self.joints.len();
         self.joints.push(Joint::new(x, y, z));
-        index
+        Ok(index)
+    }
+
+    // Directed ground friction for the joint, fractions of the velocity along and across the axis at
+    // the angle from x toward z, on top of what the surface character does on contact.
+    pub fn set_joint_friction(
+        &mut self,
+        index: usize,
+        angle: Real,
+        along: Real,
+        across: Real,
+    ) -> Result<(), Error> {
+        self.check_joint(index)?;
+        if !((0.0..=1.0).contains(&along) && (0.0..=1.0).contains(&across) && angle.is_finite()) {
+            return Err(Error::IllegalFriction { along, across });
+        }
+        self.joints[index].friction = Some(Friction::new(angle, along, across));
+        Ok(())
+    }
+
+    pub fn clear_joint_friction(&mut self, index: usize) -> Result<(), Error> {
+        self.check_joint(index)?;
+        self.joints[index].friction = None;
+        Ok(())
     }
 
-    pub fn remove_joint(&mut self, index: usize) {
+    // Unlike an anchor, a fixed joint holds in every stage and never lets go.
+    pub fn set_joint_fixed(&mut self, index: usize, fixed: bool) -> Result<(), Error> {
+        self.check_joint(index)?;
+        self.wake();
+        let joint = &mut self.joints[index];
+        joint.fixed = fixed;
+        joint.velocity = zero();
+        Ok(())
+    }
+
+    pub fn is_joint_fixed(&self, index: usize) -> Result<bool, Error> {
+        self.check_joint(index)?;
+        Ok(self.joints[index].fixed)
+    }
+
+    // Takes the intervals at the joint along with it, and shifts every joint index above it down one.
+    pub fn remove_joint(&mut self, index: usize) -> Result<(), Error> {
+        self.check_joint(index)?;
+        self.wake();
+        for interval_index in (0..self.intervals.len()).rev() {
+            if self.intervals[interval_index].touches(index) {
+                self.swap_remove_interval(interval_index);
+            }
+        }
+        self.joint_handles
+            .retain(self.joints.len(), |joint| joint != index);
         self.joints.remove(index);
+        let faces = &self.faces;
+        self.face_handles
+            .retain(faces.len(), |face| !faces[face].contains(index));
+        self.faces.retain(|face| !face.contains(index));
+        self.anchors.retain(|anchor| anchor.joint_index != index);
+        self.anchors
+            .iter_mut()
+            .for_each(|anchor| anchor.joint_removed(index));
+        self.constraints
+            .retain(|constraint| !constraint.involves(index));
+        self.constraints
+            .iter_mut()
+            .for_each(|constraint| constraint.joint_removed(index));
         self.intervals
             .iter_mut()
             .for_each(|interval| interval.joint_removed(index));
         self.faces
             .iter_mut()
             .for_each(|face| face.joint_removed(index));
+        if let Some(deployment) = &mut self.deployment {
+            deployment.joint_removed(index);
+        }
+        if let Some(sequencer) = &mut self.sequencer {
+            sequencer.joint_removed(index);
+        }
+        if let Some(growth) = &mut self.growth {
+            growth.joint_removed(index);
+        }
+        if let Some(readings) = &mut self.readings {
+            readings.joint_removed(index);
+        }
+        if self
+            .boundary_recording
+            .as_mut()
+            .is_some_and(|recording| !recording.joint_removed(index))
+        {
+            self.boundary_recording = None;
+        }
+        if self
+            .boundary_loads
+            .as_mut()
+            .is_some_and(|loads| !loads.joint_removed(index))
+        {
+            self.boundary_loads = None;
+        }
+        self.external_forces.retain(|&(joint, _)| joint != index);
+        for (joint, _) in self
+            .external_forces
+            .iter_mut()
+            .filter(|(joint, _)| *joint > index)
+        {
+            *joint -= 1;
+        }
+        if self.events.len() < MAX_EVENTS {
+            self.events.push(FabricEvent::JointRemoved { index });
+        }
+        Ok(())
+    }
+
+    // Holds the joint where it is now, until the uplift or lateral reaction exceeds its capacity.
+    pub fn create_anchor(
+        &mut self,
+        joint_index: usize,
+        max_vertical: Real,
+        max_lateral: Real,
+    ) -> Result<usize, Error> {
+        self.check_joint(joint_index)?;
+        for capacity in [max_vertical, max_lateral] {
+            if capacity.is_nan() || capacity < 0.0 {
+                return Err(Error::IllegalCapacity { capacity });
+            }
+        }
+        self.wake();
+        let index = self.anchors.len();
+        let location = self.joints[joint_index].location;
+        self.anchors.push(Anchor::new(
+            joint_index,
+            location,
+            max_vertical,
+            max_lateral,
+        ));
+        Ok(index)
+    }
+
+    pub fn remove_anchor(&mut self, index: usize) -> Result<(), Error> {
+        self.check_anchor(index)?;
+        self.wake();
+        self.anchors.remove(index);
+        Ok(())
+    }
+
+    pub fn get_anchor_count(&self) -> u16 {
+        self.anchors.len() as u16
+    }
+
+    pub fn is_anchored(&self, joint_index: usize) -> bool {
+        self.anchors
+            .iter()
+            .any(|anchor| anchor.joint_index == joint_index)
+    }
+
+    // Diagnostic only: holds the joints at the distance and reports the force it took each tick.
+    pub fn create_constraint(
+        &mut self,
+        alpha_index: usize,
+        omega_index: usize,
+        distance: Real,
+    ) -> Result<usize, Error> {
+        self.check_joint(alpha_index)?;
+        self.check_joint(omega_index)?;
+        if alpha_index == omega_index {
+            return Err(Error::IdenticalJoints { index: alpha_index });
+        }
+        if !(distance.is_finite() && distance >= 0.0) {
+            return Err(Error::IllegalLength { length: distance });
+        }
+        self.wake();
+        let index = self.constraints.len();
+        self.constraints
+            .push(Constraint::new(alpha_index, omega_index, distance));
+        Ok(index)
+    }
+
+    pub fn remove_constraint(&mut self, index: usize) -> Result<(), Error> {
+        self.check_constraint(index)?;
+        self.wake();
+        self.constraints.remove(index);
+        Ok(())
+    }
+
+    pub fn set_constraint_distance(&mut self, index: usize, distance: Real) -> Result<(), Error> {
+        self.check_constraint(index)?;
+        if !(distance.is_finite() && distance >= 0.0) {
+            return Err(Error::IllegalLength { length: distance });
+        }
+        self.wake();
+        self.constraints[index].distance = distance;
+        Ok(())
+    }
+
+    pub fn get_constraint_force(&self, index: usize) -> Result<Real, Error> {
+        self.check_constraint(index)?;
+        Ok(self.constraints[index].force)
     }
 
     pub fn create_interval(
@@ -92,11 +519,36 @@ impl Fabric {
         alpha_index: usize,
         omega_index: usize,
         push: bool,
-        length_0: f32,
-        length_1: f32,
-        stiffness: f32,
-        attack: f32,
-    ) -> usize {
+        length_0: Real,
+        length_1: Real,
+        stiffness: Real,
+        attack: Real,
+    ) -> Result<usize, Error> {
+        self.check_joint(alpha_index)?;
+        self.check_joint(omega_index)?;
+        if alpha_index == omega_index {
+            return Err(Error::IdenticalJoints { index: alpha_index });
+        }
+        for length in [length_0, length_1] {
+            if !(length.is_finite() && length >= 0.0) {
+                return Err(Error::IllegalLength { length });
+            }
+        }
+        if !(stiffness.is_finite() && stiffness >= 0.0) {
+            return Err(Error::IllegalStiffness { stiffness });
+        }
+        for joint in [alpha_index, omega_index] {
+            self.check_degree(joint)?;
+        }
+        trace_debug!(
+            alpha_index,
+            omega_index,
+            push,
+            length_0,
+            length_1,
+            "create interval"
+        );
+        self.wake();
         let index = self.intervals.len();
         self.intervals.push(Interval::new(
             alpha_index,
@@ -107,46 +559,482 @@ impl Fabric {
             stiffness,
             attack,
         ));
-        index
+        Ok(index)
+    }
+
+    /// Preferred over `create_interval`: starts at the current joint distance so nothing snaps.
+    pub fn add_interval_safe(
+        &mut self,
+        alpha_index: usize,
+        omega_index: usize,
+        role: IntervalRole,
+        stiffness: Real,
+        target_rest_factor: Real,
+        countdown: Real,
+    ) -> Result<usize, Error> {
+        self.check_joint(alpha_index)?;
+        self.check_joint(omega_index)?;
+        let alpha_location = self.joints[alpha_index].location;
+        let omega_location = self.joints[omega_index].location;
+        let current_length = (omega_location - alpha_location).magnitude();
+        let target_length = current_length * target_rest_factor;
+        let attack = if countdown > 0.0 {
+            1.0 / countdown
+        } else {
+            0.0
+        };
+        let length_0 = if attack > 0.0 {
+            current_length
+        } else {
+            target_length
+        };
+        self.create_interval(
+            alpha_index,
+            omega_index,
+            role == IntervalRole::Push,
+            length_0,
+            target_length,
+            stiffness,
+            attack,
+        )
     }
 
-    pub fn remove_interval(&mut self, index: usize) {
-        self.intervals.remove(index);
+    pub fn remove_interval(&mut self, index: usize) -> Result<(), Error> {
+        self.check_interval(index)?;
+        let interval = &self.intervals[index];
+        if !interval.push {
+            for joint in [interval.alpha_index, interval.omega_index] {
+                let pulls = self.pull_count(joint);
+                if self.push_count(joint) > 0 && pulls == self.min_push_pulls {
+                    return Err(Error::Undertriangulated {
+                        joint,
+                        pulls: pulls - 1,
+                        min: self.min_push_pulls,
+                    });
+                }
+            }
+        }
+        self.wake();
+        self.swap_remove_interval(index);
+        Ok(())
+    }
+
+    // Hub hardware limits, zero for no limit. Creating an interval respects the degree and removing a
+    // pull keeps every push end at least this well tied; generators call check_topology when done.
+    pub fn set_topology_rules(&mut self, max_degree: usize, min_push_pulls: usize) {
+        self.max_degree = max_degree;
+        self.min_push_pulls = min_push_pulls;
+    }
+
+    pub fn check_topology(&self) -> Result<(), Error> {
+        for joint in 0..self.joints.len() {
+            let degree = self.degree(joint);
+            if self.max_degree > 0 && degree > self.max_degree {
+                return Err(Error::DegreeExceeded {
+                    joint,
+                    max: self.max_degree,
+                });
+            }
+            let pulls = self.pull_count(joint);
+            if self.push_count(joint) > 0 && pulls < self.min_push_pulls {
+                return Err(Error::Undertriangulated {
+                    joint,
+                    pulls,
+                    min: self.min_push_pulls,
+                });
+            }
+        }
+        Ok(())
     }
 
-    pub fn create_face(&mut self, joint0: usize, joint1: usize, joint2: usize) -> usize {
+    pub fn create_face(
+        &mut self,
+        joint0: usize,
+        joint1: usize,
+        joint2: usize,
+    ) -> Result<usize, Error> {
+        for joint in [joint0, joint1, joint2] {
+            self.check_joint(joint)?;
+        }
+        self.wake();
         let index = self.faces.len();
         self.faces.push(Face::new(joint0, joint1, joint2));
-        index
+        Ok(index)
     }
 
-    pub fn remove_face(&mut self, index: usize) {
+    pub fn remove_face(&mut self, index: usize) -> Result<(), Error> {
+        self.check_face(index)?;
+        self.wake();
+        self.face_handles
+            .retain(self.faces.len(), |face| face != index);
         self.faces.remove(index);
+        Ok(())
+    }
+
+    // Replace the faces around a joint with a fan over their outer rim, so the surface stays closed
+    // when the joint is cut away. Returns how many faces were created, zero if the rim is not one chain.
+    pub fn retriangulate_around(&mut self, joint_index: usize) -> Result<usize, Error> {
+        self.check_joint(joint_index)?;
+        let mut edges: Vec<(usize, usize)> = self
+            .faces
+            .iter()
+            .filter_map(|face| face.opposite_edge(joint_index))
+            .collect();
+        if edges.len() < 2 {
+            return Ok(0);
+        }
+        let start = edges
+            .iter()
+            .position(|&(from, _)| edges.iter().all(|&(_, to)| to != from))
+            .unwrap_or(0);
+        let (first, mut next) = edges.swap_remove(start);
+        let mut rim = vec![first];
+        while let Some(position) = edges.iter().position(|&(from, _)| from == next) {
+            rim.push(next);
+            next = edges.swap_remove(position).1;
+        }
+        if !edges.is_empty() {
+            return Ok(0);
+        }
+        if next != first {
+            rim.push(next);
+        }
+        if rim.len() < 3 {
+            return Ok(0);
+        }
+        self.wake();
+        let faces = &self.faces;
+        self.face_handles
+            .retain(faces.len(), |face| !faces[face].contains(joint_index));
+        self.faces.retain(|face| !face.contains(joint_index));
+        for pair in rim[1..].windows(2) {
+            self.faces.push(Face::new(rim[0], pair[0], pair[1]));
+        }
+        Ok(rim.len() - 2)
+    }
+
+    // Offsets one end's force point: axial along the strut toward its far hub joint, radial toward the other end.
+    pub fn set_attachment_offset(
+        &mut self,
+        index: usize,
+        at_omega: bool,
+        hub_joint: usize,
+        axial: Real,
+        radial: Real,
+    ) -> Result<(), Error> {
+        self.check_interval(index)?;
+        self.check_joint(hub_joint)?;
+        let end = if at_omega { 1 } else { 0 };
+        let interval = &self.intervals[index];
+        let joint = if at_omega {
+            interval.omega_index
+        } else {
+            interval.alpha_index
+        };
+        if joint == hub_joint {
+            return Err(Error::IdenticalJoints { index: hub_joint });
+        }
+        for offset in [axial, radial] {
+            if !offset.is_finite() {
+                return Err(Error::IllegalLength { length: offset });
+            }
+        }
+        self.wake();
+        self.intervals[index].attachments[end] = Some(Attachment {
+            hub_index: hub_joint,
+            axial,
+            radial,
+        });
+        Ok(())
+    }
+
+    pub fn clear_attachment_offsets(&mut self, index: usize) -> Result<(), Error> {
+        self.check_interval(index)?;
+        self.wake();
+        self.intervals[index].attachments = [None, None];
+        Ok(())
+    }
+
+    // Turns the interval into a gap element that only pushes back once shorter than the gap length.
+    pub fn set_interval_gap(&mut self, index: usize, gap_length: Real) -> Result<(), Error> {
+        self.check_interval(index)?;
+        if !(gap_length.is_finite() && gap_length > 0.0) {
+            return Err(Error::IllegalLength { length: gap_length });
+        }
+        self.wake();
+        self.intervals[index].gap = Some(gap_length);
+        Ok(())
+    }
+
+    pub fn clear_interval_gap(&mut self, index: usize) -> Result<(), Error> {
+        self.check_interval(index)?;
+        self.wake();
+        self.intervals[index].gap = None;
+        Ok(())
+    }
+
+    // Commanded rest length changes go through a winch with this dead band and backlash, in length units.
+    pub fn set_interval_actuator(
+        &mut self,
+        index: usize,
+        dead_band: Real,
+        backlash: Real,
+    ) -> Result<(), Error> {
+        self.check_interval(index)?;
+        for length in [dead_band, backlash] {
+            if !(length.is_finite() && length >= 0.0) {
+                return Err(Error::IllegalLength { length });
+            }
+        }
+        self.intervals[index].actuator = Some(Actuator::new(dead_band, backlash));
+        Ok(())
+    }
+
+    pub fn clear_interval_actuator(&mut self, index: usize) -> Result<(), Error> {
+        self.check_interval(index)?;
+        self.intervals[index].actuator = None;
+        Ok(())
+    }
+
+    // The fraction the rest length has grown by yielding, negative where it shrank.
+    pub fn get_interval_plastic_strain(&self, index: usize) -> Result<Real, Error> {
+        self.check_interval(index)?;
+        Ok(self.intervals[index].plastic_strain)
+    }
+
+    // Multiplies the role's damping feature for this interval, zero for none.
+    pub fn set_interval_damping(&mut self, index: usize, damping: Real) -> Result<(), Error> {
+        self.check_interval(index)?;
+        if !(damping.is_finite() && damping >= 0.0) {
+            return Err(Error::IllegalDamping { damping });
+        }
+        self.intervals[index].damping = damping;
+        self.wake();
+        Ok(())
+    }
+
+    // The stress at each strain, strains rising, with the role stiffness still scaling it.
+    pub fn set_interval_curve(
+        &mut self,
+        index: usize,
+        strains: &[f32],
+        stresses: &[f32],
+    ) -> Result<(), Error> {
+        self.check_interval(index)?;
+        self.wake();
+        self.intervals[index].curve = Some(Curve::piecewise(&reals(strains), &reals(stresses))?);
+        Ok(())
+    }
+
+    // Up to four coefficients, of strain to the first power and up.
+    pub fn set_interval_polynomial(
+        &mut self,
+        index: usize,
+        coefficients: &[f32],
+    ) -> Result<(), Error> {
+        self.check_interval(index)?;
+        self.wake();
+        self.intervals[index].curve = Some(Curve::polynomial(&reals(coefficients))?);
+        Ok(())
+    }
+
+    // Back to strain times stiffness.
+    pub fn clear_interval_curve(&mut self, index: usize) -> Result<(), Error> {
+        self.check_interval(index)?;
+        self.wake();
+        self.intervals[index].curve = None;
+        Ok(())
+    }
+
+    // Takes the interval's force out of the fabric without removing it, to find out what it was doing.
+    pub fn set_interval_disabled(&mut self, index: usize, disabled: bool) -> Result<(), Error> {
+        self.check_interval(index)?;
+        self.wake();
+        self.intervals[index].disabled = disabled;
+        Ok(())
+    }
+
+    pub fn is_interval_disabled(&self, index: usize) -> Result<bool, Error> {
+        self.check_interval(index)?;
+        Ok(self.intervals[index].disabled)
+    }
+
+    pub fn set_interval_tag(&mut self, index: usize, tag: u16) -> Result<(), Error> {
+        self.check_interval(index)?;
+        self.intervals[index].tag = tag;
+        Ok(())
+    }
+
+    pub fn get_interval_tag(&self, index: usize) -> Result<u16, Error> {
+        self.check_interval(index)?;
+        Ok(self.intervals[index].tag)
+    }
+
+    // Text for annotated renders, an empty label removes it.
+    pub fn set_interval_label(&mut self, index: usize, label: &str) -> Result<(), Error> {
+        self.check_interval(index)?;
+        if label.is_empty() {
+            self.labels.remove(&index);
+        } else {
+            self.labels.insert(index, label.to_string());
+        }
+        Ok(())
+    }
+
+    pub fn get_interval_label(&self, index: usize) -> Result<Option<String>, Error> {
+        self.check_interval(index)?;
+        Ok(self.labels.get(&index).cloned())
+    }
+
+    // Membrane over the tagged cable loops: lofted between consecutive loops, capped at a centroid joint
+    // when there is only one. With a membrane stiffness, the new edges also get light pulls.
+    pub fn skin(
+        &mut self,
+        cable_loop_tags: &[u16],
+        membrane_stiffness: Real,
+    ) -> Result<usize, Error> {
+        if !(membrane_stiffness.is_finite() && membrane_stiffness >= 0.0) {
+            return Err(Error::IllegalStiffness {
+                stiffness: membrane_stiffness,
+            });
+        }
+        let mut rings = Vec::with_capacity(cable_loop_tags.len());
+        for &tag in cable_loop_tags {
+            let ring = skin::cable_loop(&self.intervals, tag).ok_or(Error::NotALoop { tag })?;
+            rings.push(ring);
+        }
+        let triangles = match rings.as_slice() {
+            [] => return Ok(0),
+            [ring] => {
+                let location = skin::centroid(&self.joints, ring);
+                let hub = self.create_joint(location.x, location.y, location.z)?;
+                (0..ring.len())
+                    .map(|index| [hub, ring[index], ring[(index + 1) % ring.len()]])
+                    .collect()
+            }
+            _ => {
+                let mut triangles = Vec::new();
+                let mut ring = rings[0].clone();
+                for other in &rings[1..] {
+                    let aligned = skin::align(&self.joints, &ring, other);
+                    triangles.extend(skin::loft(&self.joints, &ring, &aligned));
+                    ring = aligned;
+                }
+                triangles
+            }
+        };
+        self.wake();
+        for &[joint0, joint1, joint2] in triangles.iter() {
+            self.faces.push(Face::new(joint0, joint1, joint2));
+        }
+        if membrane_stiffness > 0.0 {
+            let mut edges: Vec<(usize, usize)> = triangles
+                .iter()
+                .flat_map(|&[joint0, joint1, joint2]| {
+                    [(joint0, joint1), (joint1, joint2), (joint2, joint0)]
+                })
+                .map(|(alpha, omega)| (alpha.min(omega), alpha.max(omega)))
+                .filter(|&(alpha, omega)| {
+                    !self
+                        .intervals
+                        .iter()
+                        .any(|interval| interval.connects(alpha, omega))
+                })
+                .collect();
+            edges.sort_unstable();
+            edges.dedup();
+            for (alpha, omega) in edges {
+                let length =
+                    (self.joints[omega].location - self.joints[alpha].location).magnitude();
+                self.create_interval(alpha, omega, false, length, length, membrane_stiffness, 0.0)?;
+            }
+        }
+        Ok(triangles.len())
+    }
+
+    // Make every interval the mean length of its family, so a physical build needs few distinct members.
+    pub fn snap_length_families(
+        &mut self,
+        tolerance: Real,
+        countdown: Real,
+    ) -> Result<usize, Error> {
+        if !(countdown.is_finite() && countdown > 0.0) {
+            return Err(Error::IllegalCountdown { countdown });
+        }
+        let families = self.length_families(tolerance)?;
+        self.wake();
+        for family in families.iter() {
+            for &index in family.intervals.iter() {
+                self.intervals[index].change_rest_length(family.mean, countdown);
+            }
+        }
+        Ok(families.len())
     }
 
     pub fn twitch_interval(
         &mut self,
         interval_index: usize,
-        attack_countdown: f32,
-        decay_countdown: f32,
-        delta_size_nuance: f32,
-    ) {
-        self.intervals[interval_index].twitch(attack_countdown, decay_countdown, delta_size_nuance)
+        attack_countdown: Real,
+        decay_countdown: Real,
+        delta_size_nuance: Real,
+    ) -> Result<(), Error> {
+        self.check_interval(interval_index)?;
+        for countdown in [attack_countdown, decay_countdown] {
+            if !(countdown.is_finite() && countdown > 0.0) {
+                return Err(Error::IllegalCountdown { countdown });
+            }
+        }
+        trace_debug!(
+            interval_index,
+            attack_countdown,
+            decay_countdown,
+            delta_size_nuance,
+            "twitch"
+        );
+        self.wake();
+        self.intervals[interval_index].actuate_twitch(
+            attack_countdown,
+            decay_countdown,
+            delta_size_nuance,
+        );
+        Ok(())
+    }
+
+    // Net work the interval's length changes have done against its load, since creation or the last reset.
+    pub fn get_interval_work(&self, index: usize) -> Result<Real, Error> {
+        self.check_interval(index)?;
+        Ok(self.intervals[index].work)
+    }
+
+    pub fn get_actuation_work(&self) -> Real {
+        self.intervals.iter().map(|interval| interval.work).sum()
+    }
+
+    // Energy spent by all actuators during the last tick.
+    pub fn get_actuation_energy(&self) -> Real {
+        self.actuation_energy
+    }
+
+    pub fn reset_actuation_work(&mut self) {
+        self.intervals
+            .iter_mut()
+            .for_each(|interval| interval.work = 0.0);
+        self.actuation_energy = 0.0;
     }
 
     pub fn centralize(&mut self) {
-        let mut midpoint: Vector3<f32> = zero();
+        let mut midpoint: Vector3<Real> = zero();
         for joint in self.joints.iter() {
             midpoint += &joint.location.coords;
         }
-        midpoint /= self.joints.len() as f32;
-        midpoint.y = 0_f32;
+        midpoint /= self.joints.len() as Real;
+        midpoint.y = 0.0;
         for joint in self.joints.iter_mut() {
             joint.location -= &midpoint;
         }
     }
 
-    pub fn set_altitude(&mut self, altitude: f32) {
+    pub fn set_altitude(&mut self, altitude: Real) {
         match self
             .joints
             .iter()
@@ -156,7 +1044,7 @@ impl Fabric {
         {
             Some(low_y) => {
                 let up = altitude - low_y;
-                if up > 0_f32 {
+                if up > 0.0 && !self.joints.iter().any(|joint| joint.fixed) {
                     for joint in &mut self.joints {
                         joint.location.y += up;
                     }
@@ -166,29 +1054,104 @@ impl Fabric {
         }
     }
 
-    pub fn multiply_rest_length(&mut self, index: usize, factor: f32, countdown: f32) {
-        self.intervals[index].multiply_rest_length(factor, countdown);
+    pub fn set_floating(&mut self, floating: bool) {
+        self.wake();
+        if floating && !self.floating {
+            for joint in self.joints.iter_mut() {
+                joint.velocity.fill(0.0);
+            }
+        }
+        self.floating = floating;
+    }
+
+    pub fn is_floating(&self) -> bool {
+        self.floating
+    }
+
+    // Holds every length transition and the pretensing countdown where they are, while physics carries on.
+    pub fn set_transitions_frozen(&mut self, frozen: bool) {
+        self.wake();
+        self.frozen = frozen;
+    }
+
+    pub fn is_transitions_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    pub fn multiply_rest_length(
+        &mut self,
+        index: usize,
+        factor: Real,
+        countdown: Real,
+    ) -> Result<(), Error> {
+        self.check_interval(index)?;
+        if !(factor.is_finite() && factor >= 0.0) {
+            return Err(Error::IllegalLength { length: factor });
+        }
+        if !(countdown.is_finite() && countdown > 0.0) {
+            return Err(Error::IllegalCountdown { countdown });
+        }
+        trace_debug!(index, factor, countdown, "multiply rest length");
+        self.wake();
+        let interval = &mut self.intervals[index];
+        interval.actuate(interval.length_1 * factor, countdown);
+        Ok(())
     }
 
-    pub fn change_rest_length(&mut self, index: usize, rest_length: f32, countdown: f32) {
-        self.intervals[index].change_rest_length(rest_length, countdown);
+    pub fn change_rest_length(
+        &mut self,
+        index: usize,
+        rest_length: Real,
+        countdown: Real,
+    ) -> Result<(), Error> {
+        self.check_interval(index)?;
+        if !(rest_length.is_finite() && rest_length >= 0.0) {
+            return Err(Error::IllegalLength {
+                length: rest_length,
+            });
+        }
+        if !(countdown.is_finite() && countdown > 0.0) {
+            return Err(Error::IllegalCountdown { countdown });
+        }
+        trace_debug!(index, rest_length, countdown, "change rest length");
+        self.wake();
+        self.intervals[index].actuate(rest_length, countdown);
+        Ok(())
     }
 
-    pub fn apply_matrix4(&mut self, m: &[f32]) {
-        let matrix: Matrix4<f32> = Matrix4::from_vec(m.to_vec());
+    pub fn apply_matrix4(&mut self, m: &[f32]) -> Result<(), Error> {
+        if m.len() != 16 {
+            return Err(Error::WrongLength {
+                expected: 16,
+                actual: m.len(),
+            });
+        }
+        self.wake();
+        let matrix: Matrix4<Real> = Matrix4::from_vec(reals(m));
         for joint in &mut self.joints {
             *joint.location = *matrix.transform_point(&joint.location);
             *joint.velocity = *matrix.transform_vector(&joint.velocity);
         }
+        Ok(())
     }
 
-    pub fn copy_stiffnesses(&mut self, new_stiffnesses: &mut [f32]) {
+    pub fn copy_stiffnesses(&mut self, new_stiffnesses: &mut [f32]) -> Result<(), Error> {
+        if new_stiffnesses.len() != self.intervals.len() {
+            return Err(Error::WrongLength {
+                expected: self.intervals.len(),
+                actual: new_stiffnesses.len(),
+            });
+        }
+        self.wake();
         for (index, interval) in &mut self.intervals.iter_mut().enumerate() {
-            interval.stiffness = new_stiffnesses[index];
+            interval.stiffness = new_stiffnesses[index] as Real;
         }
+        Ok(())
     }
 
     fn set_stage(&mut self, stage: Stage) -> Stage {
+        trace_info!(from = ?self.stage, to = ?stage, age = self.age, "stage transition");
+        self.wake();
         self.stage = stage;
         stage
     }
@@ -199,9 +1162,10 @@ impl Fabric {
             interval.length_1 = interval.length_0;
         }
         for joint in self.joints.iter_mut() {
-            joint.force.fill(0_f32);
-            joint.velocity.fill(0_f32);
+            joint.force.fill(0.0);
+            joint.velocity.fill(0.0);
         }
+        self.damper.reset();
         self.set_stage(Stage::Slack)
     }
 
@@ -222,7 +1186,7 @@ impl Fabric {
 
     fn calculate_strain_limits(&mut self) {
         self.strain_limits.copy_from_slice(&DEFAULT_STRAIN_LIMITS);
-        let margin = 1e-3_f32;
+        let margin = 1e-3;
         for interval in &self.intervals {
             let upper_strain = interval.strain + margin;
             let lower_strain = interval.strain - margin;
@@ -244,91 +1208,2626 @@ impl Fabric {
         }
     }
 
-    fn tick(&mut self, world: &World) {
+    // Returns the time step taken.
+    fn tick(&mut self, world: &World) -> Real {
         for joint in &mut self.joints {
             joint.reset();
         }
+        for &(index, force) in self.external_forces.iter() {
+            self.joints[index].force += force;
+        }
+        if let Some(loads) = &mut self.boundary_loads {
+            if !loads.apply(&mut self.joints) {
+                self.boundary_loads = None;
+            }
+        }
+        for muscle in self.muscles.iter() {
+            let interval = &mut self.intervals[muscle.interval_index];
+            let rest_length = muscle.rest_length(self.age);
+            interval.length_0 = rest_length;
+            interval.length_1 = rest_length;
+            interval.length_nuance = 0.0;
+            interval.attack = 0.0;
+            interval.decay = 0.0;
+        }
+        if let Some(pattern_generator) = &mut self.pattern_generator {
+            pattern_generator.advance(&mut self.intervals);
+        }
         let pretensing_nuance = world.pretensing_nuance(self);
-        for interval in &mut self.intervals {
-            interval.physics(world, &mut self.joints, self.stage, pretensing_nuance);
+        let slew_rate = if self.stage == Stage::Shaping {
+            world.shaping_slew_rate
+        } else {
+            0.0
+        };
+        self.interval_physics(world, pretensing_nuance);
+        for interval in &self.intervals {
+            for watch in self.regions.iter_mut() {
+                if watch.contains(interval, &self.joints) {
+                    watch.accumulate(interval.strain);
+                }
+            }
+        }
+        if !self.frozen && world.plastic_yield.iter().any(|&[_, flow]| flow > 0.0) {
+            for interval in self.intervals.iter_mut() {
+                let [yield_strain, flow] = world.plastic_yield[interval.role() as usize];
+                if flow > 0.0 {
+                    interval.yield_to(yield_strain, flow);
+                }
+            }
+        }
+        if let Some(recording) = &mut self.boundary_recording {
+            recording.record(&self.intervals, world, self.stage);
+        }
+        // collision and wind are forces like those of the intervals, so the step scales them with the rest
+        let (strut_radius, strut_stiffness) = world.strut_collision;
+        if strut_radius > 0.0 {
+            collision::separate_struts(
+                &self.intervals,
+                &mut self.joints,
+                strut_radius,
+                strut_stiffness,
+            );
+        }
+        if let (Stage::Pretenst, Some(wind)) = (self.stage, &world.wind) {
+            let velocity = wind.velocity(self.age);
+            for interval in self.intervals.iter() {
+                let length = interval.calculate_current_length(&self.joints);
+                let half_load = wind.load(&velocity, &interval.unit, length) / 2.0;
+                self.joints[interval.alpha_index].force += half_load;
+                self.joints[interval.omega_index].force += half_load;
+            }
+        }
+        for watch in self.regions.iter_mut() {
+            watch.finish();
+        }
+        if world.sleep_ticks > 0 {
+            for joint in &mut self.joints {
+                joint.update_sleep(world);
+            }
+        }
+        let time_step = self.time_step(world);
+        let steps = self.integration_steps(world, time_step, pretensing_nuance);
+        // transitions run on the same clock as the joints, whatever the step
+        let mut actuation_energy = 0.0;
+        if !self.frozen {
+            for interval in &mut self.intervals {
+                let before = interval.ideal_length_now(world, self.stage, pretensing_nuance);
+                interval.advance_transition(slew_rate, time_step);
+                let change =
+                    interval.ideal_length_now(world, self.stage, pretensing_nuance) - before;
+                actuation_energy +=
+                    interval.record_work(interval.axial_force(world, self.stage), change);
+            }
+        }
+        self.actuation_energy = actuation_energy;
+        for joint in &mut self.joints {
+            joint.acceleration = if joint.interval_mass == 0.0 {
+                zero()
+            } else {
+                joint.force / joint.interval_mass
+            };
         }
+        let realizing = self.stage >= Stage::Pretensing && world.active_damping > 0.0;
+        let boost = if realizing { self.damper.boost } else { 0.0 };
         match self.stage {
             Stage::Growing | Stage::Shaping | Stage::Pretensing => {
-                for joint in &mut self.joints {
-                    joint.velocity_physics(world, 0_f32, world.shaping_drag);
+                for (joint, step) in self.joints.iter_mut().zip(steps.iter()) {
+                    if joint.is_asleep(world) {
+                        continue;
+                    }
+                    joint.velocity_physics(world, 0.0, world.shaping_drag + boost, time_step, step);
                 }
-                self.set_altitude(1_f32)
+                self.set_altitude(1.0)
             }
             Stage::Slack => {
-                if world.gravity != 0_f32 {
-                    self.set_altitude(1_f32)
+                if world.gravity != 0.0 {
+                    self.set_altitude(1.0)
                 }
             }
             Stage::Pretenst => {
-                for joint in &mut self.joints {
-                    joint.velocity_physics(world, world.gravity, world.drag)
+                let (gravity, drag) = if self.floating {
+                    (0.0, world.drag.max(world.shaping_drag))
+                } else {
+                    (world.gravity, world.drag)
+                };
+                let ground = self.ground_acceleration(world, time_step) * time_step;
+                for (index, joint) in self.joints.iter_mut().enumerate() {
+                    if joint.is_asleep(world) {
+                        continue;
+                    }
+                    if gravity != 0.0 && self.events.len() < MAX_EVENTS {
+                        if let Some((impulse, normal)) =
+                            joint.contact_impulse(world.surface(), time_step)
+                        {
+                            trace_trace!(joint = index, impulse, tick = self.age, "contact");
+                            self.events.push(FabricEvent::Contact(ContactEvent {
+                                joint: index,
+                                normal,
+                                impulse,
+                                tick: self.age,
+                            }));
+                        }
+                    }
+                    joint.velocity_physics(world, gravity, drag + boost, time_step, &steps[index]);
+                    joint.obstacle_physics(world, time_step);
+                    joint.velocity -= &ground;
                 }
+                self.hold_anchors(world, gravity);
             }
         }
+        if world.kinetic_damping && self.damper.kinetic_peak(self.kinetic_energy()) {
+            for joint in &mut self.joints {
+                joint.velocity = zero();
+            }
+        }
+        let speed_limit = world.get_speed_limit(self.stage);
         for joint in &mut self.joints {
-            joint.location_physics();
+            if joint.is_asleep(world) {
+                continue;
+            }
+            joint.location_physics(speed_limit, time_step);
         }
-    }
-
-    pub fn iterate(&mut self, world: &World) -> bool {
-        for _tick in 0..(world.iterations_per_frame as usize) {
-            self.tick(&world);
+        for constraint in self.constraints.iter_mut() {
+            constraint.hold(&mut self.joints);
         }
-        self.calculate_strain_limits();
-        for interval in self.intervals.iter_mut() {
-            interval.strain_nuance = interval.calculate_strain_nuance(&self.strain_limits);
+        if world.sleep_ticks > 0
+            && !self.joints.is_empty()
+            && !self.is_scheduled()
+            && self.joints.iter().all(|joint| joint.is_asleep(world))
+        {
+            self.asleep = true;
+            trace_info!(tick = self.age, "fabric asleep");
+            if self.events.len() < MAX_EVENTS {
+                self.events
+                    .push(FabricEvent::FabricAsleep { tick: self.age });
+            }
         }
-        self.age += world.iterations_per_frame as u32;
-        let interval_busy_max = self
+        if let Some((_, _, current)) = &mut self.momentum {
+            *current = Momentum::of(&self.joints);
+        }
+        for sensor in self.sensors.iter_mut() {
+            sensor.update(&self.intervals[sensor.interval_index], &self.joints);
+        }
+        if let Some(readings) = &mut self.readings {
+            readings.record(&self.intervals, &self.joints, time_step);
+        }
+        if realizing {
+            self.damper
+                .observe(&self.joints, world.active_damping, world.drag);
+        }
+        if let Some(deployment) = &mut self.deployment {
+            deployment.advance(&mut self.intervals, &mut self.constraints);
+        }
+        if let Some(sequencer) = &mut self.sequencer {
+            sequencer.advance(&mut self.intervals);
+        }
+        self.break_intervals(world);
+        self.age += 1;
+        time_step
+    }
+
+    // Plain intervals go through the lanes, the rest one by one, all applied in interval order.
+    #[cfg(not(feature = "parallel"))]
+    fn interval_physics(&mut self, world: &World, pretensing_nuance: Real) {
+        self.lanes.gather(
+            &self.intervals,
+            &self.joints,
+            world,
+            self.stage,
+            pretensing_nuance,
+        );
+        self.lanes.solve();
+        for (index, interval) in self.intervals.iter_mut().enumerate() {
+            match self.lanes.lane_of(index) {
+                Some(lane) => self.lanes.apply(lane, interval, &mut self.joints),
+                None => interval.physics(world, &mut self.joints, self.stage, pretensing_nuance),
+            }
+        }
+    }
+
+    // Contributions are worked out side by side from the joint locations alone, then added up in
+    // interval order, so the result is the same as without threads.
+    #[cfg(feature = "parallel")]
+    fn interval_physics(&mut self, world: &World, pretensing_nuance: Real) {
+        use rayon::prelude::*;
+        let (joints, stage) = (&self.joints, self.stage);
+        let contributions: Vec<Contribution> = self
+            .intervals
+            .par_iter_mut()
+            .map(|interval| interval.contribution(world, joints, stage, pretensing_nuance))
+            .collect();
+        for contribution in contributions.iter() {
+            contribution.apply(&mut self.joints);
+        }
+    }
+
+    // In the frame of the moving ground, every joint feels the ground acceleration as an inertial force
+    // while the anchors stay put.
+    fn ground_acceleration(&mut self, world: &World, time_step: Real) -> Vector3<Real> {
+        if world.ground_motion.is_empty() {
+            return zero();
+        }
+        let (serial, time) = &mut self.ground_motion_clock;
+        if *serial != world.ground_motion_serial {
+            *serial = world.ground_motion_serial;
+            *time = 0.0;
+        }
+        let acceleration = world.ground_acceleration(*time);
+        *time += time_step;
+        acceleration
+    }
+
+    fn sound_alarms(&mut self, world: &World) {
+        for (index, interval) in self.intervals.iter_mut().enumerate() {
+            let role = interval.role();
+            let bands = world.strain_alarms[role as usize];
+            let Some(level) = interval.update_alarm(bands, world.alarm_hysteresis) else {
+                continue;
+            };
+            if self.events.len() < MAX_EVENTS {
+                self.events.push(FabricEvent::StrainAlarm {
+                    interval: index,
+                    level,
+                    strain: interval.strain,
+                    tick: self.age,
+                });
+            }
+        }
+    }
+
+    // From the top down, so removal only moves intervals already looked at.
+    fn break_intervals(&mut self, world: &World) {
+        if world
+            .ultimate_strains
+            .iter()
+            .all(|&ultimate| ultimate == 0.0)
+        {
+            return;
+        }
+        for index in (0..self.intervals.len()).rev() {
+            let interval = &mut self.intervals[index];
+            let ultimate = world.ultimate_strains[interval.role() as usize];
+            if interval.disabled || ultimate == 0.0 || interval.strain.abs() <= ultimate {
+                continue;
+            }
+            let strain = interval.strain;
+            trace_info!(interval = index, strain, tick = self.age, "interval broken");
+            if self.events.len() < MAX_EVENTS {
+                self.events.push(FabricEvent::IntervalBroken {
+                    interval: index,
+                    strain,
+                    tick: self.age,
+                });
+            }
+            if world.remove_broken[interval.role() as usize] {
+                self.swap_remove_interval(index);
+            } else {
+                interval.disabled = true;
+            }
+        }
+    }
+
+    fn hold_anchors(&mut self, world: &World, gravity: Real) {
+        let mut index = 0;
+        while index < self.anchors.len() {
+            let anchor = &mut self.anchors[index];
+            let acceleration = world.gravity_at(&anchor.location, gravity);
+            if anchor.hold(&mut self.joints[anchor.joint_index], &acceleration) {
+                index += 1;
+                continue;
+            }
+            let Anchor {
+                joint_index,
+                reaction,
+                ..
+            } = self.anchors.remove(index);
+            trace_info!(joint = joint_index, tick = self.age, "anchor released");
+            if self.events.len() < MAX_EVENTS {
+                self.events.push(FabricEvent::AnchorReleased {
+                    joint: joint_index,
+                    reaction,
+                    tick: self.age,
+                });
+            }
+        }
+    }
+
+    pub fn iterate(&mut self, world: &World) -> bool {
+        let world = world.in_stage(self.stage);
+        let ticks = match world.substeps {
+            0 => world.iterations_per_frame as usize,
+            substeps => substeps as usize,
+        };
+        self.iterate_staged(&world, ticks)
+    }
+
+    pub(crate) fn iterate_ticks(&mut self, world: &World, ticks: usize) -> bool {
+        self.iterate_staged(&world.in_stage(self.stage), ticks)
+    }
+
+    // The world comes with the overrides of the stage the iterate started in already applied, once.
+    fn iterate_staged(&mut self, world: &World, ticks: usize) -> bool {
+        let mut motion: Option<(Real, Real)> = None;
+        let mut elapsed = 0.0;
+        for _tick in 0..ticks {
+            self.grow();
+            self.control();
+            if self.asleep {
+                break;
+            }
+            elapsed += self.tick(world);
+            let (speed, strain_rate) = self.motion();
+            motion = Some(motion.map_or((speed, strain_rate), |(s, r)| {
+                (s.max(speed), r.max(strain_rate))
+            }));
+        }
+        if let Some(motion) = motion {
+            self.record_settling(motion);
+        }
+        self.external_forces.clear();
+        self.calculate_strain_limits();
+        for interval in self.intervals.iter_mut() {
+            interval.strain_nuance = interval.calculate_strain_nuance(&self.strain_limits);
+        }
+        self.sound_alarms(world);
+        let interval_busy_max = self
             .intervals
             .iter()
             .map(|i| i.length_nuance)
-            .fold(0_f32, f32::max);
-        if interval_busy_max > 0_f32 {
+            .fold(0.0, Real::max);
+        if interval_busy_max > 0.0
+            || self.intervals.iter().any(|interval| interval.delay > 0)
+            || self.growth.is_some()
+            || self.is_scheduled()
+        {
             return true;
         }
-        let pretensing_countdown: f32 = self.pretensing_countdown - world.iterations_per_frame;
-        self.pretensing_countdown = if pretensing_countdown < 0_f32 {
-            0_f32
+        if self.frozen {
+            return self.pretensing_countdown > 0.0;
+        }
+        let pretensing_countdown: Real = self.pretensing_countdown - elapsed;
+        self.pretensing_countdown = if pretensing_countdown < 0.0 {
+            0.0
         } else {
             pretensing_countdown
         };
-        self.pretensing_countdown > 0_f32
+        self.pretensing_countdown > 0.0
     }
 
-    pub fn get_stage(&self) -> Stage {
-        self.stage
+    // Whether the max joint speed and the max strain rate over every tick stayed within their tolerances
+    // for each of the last iterations, at most MAX_SETTLED_ITERATIONS of them. A sleeping fabric has settled.
+    pub fn is_settled(
+        &self,
+        speed_tolerance: Real,
+        strain_rate_tolerance: Real,
+        iterations: usize,
+    ) -> bool {
+        if self.asleep {
+            return true;
+        }
+        if iterations == 0 || iterations > self.settling.len() {
+            return false;
+        }
+        self.settling
+            .iter()
+            .skip(self.settling.len() - iterations)
+            .all(|&(speed, strain_rate)| {
+                speed <= speed_tolerance && strain_rate <= strain_rate_tolerance
+            })
     }
 
-    pub fn request_stage(&mut self, requested_stage: Stage, world: &World) -> Option<Stage> {
-        match self.stage {
-            Stage::Growing => match requested_stage {
-                Stage::Shaping => Some(self.set_stage(requested_stage)),
-                _ => None,
-            },
-            Stage::Shaping => match requested_stage {
-                Stage::Pretenst => Some(self.set_stage(requested_stage)),
-                Stage::Slack => Some(self.start_slack()),
-                _ => None,
+    pub fn kinetic_energy(&self) -> Real {
+        self.joints
+            .iter()
+            .map(|joint| joint.interval_mass * joint.velocity.magnitude_squared() / 2.0)
+            .sum()
+    }
+
+    // Probe a clone and extrapolate the exponential decay of the kinetic energy envelope, from its peak,
+    // to the tolerance.
+    // Returns ticks from now, or u32::MAX when the probe shows no decay.
+    pub fn estimate_settling(&self, world: &World, tolerance: Real) -> u32 {
+        let mut probe = self.clone();
+        let mut samples: Vec<(Real, Real)> = Vec::new();
+        let mut elapsed = 0;
+        while elapsed < SETTLING_PROBE_TICKS {
+            let mut envelope = 0.0;
+            for _tick in 0..SETTLING_WINDOW_TICKS {
+                probe.iterate_ticks(world, 1);
+                envelope = envelope.max(probe.kinetic_energy());
+            }
+            elapsed += SETTLING_WINDOW_TICKS;
+            if probe.asleep || envelope <= tolerance {
+                return elapsed;
+            }
+            samples.push((elapsed as Real, envelope.ln()));
+        }
+        let peak = samples
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.1.total_cmp(&b.1))
+            .map_or(0, |(index, _)| index);
+        let samples = &samples[peak..];
+        if samples.len() < 3 {
+            return u32::MAX;
+        }
+        let count = samples.len() as Real;
+        let mean_tick = samples.iter().map(|&(tick, _)| tick).sum::<Real>() / count;
+        let mean_log = samples.iter().map(|&(_, log)| log).sum::<Real>() / count;
+        let (covariance, variance) =
+            samples
+                .iter()
+                .fold((0.0, 0.0), |(covariance, variance), &(tick, log)| {
+                    let dt = tick - mean_tick;
+                    (covariance + dt * (log - mean_log), variance + dt * dt)
+                });
+        let decay = covariance / variance;
+        if decay.is_nan() || decay >= 0.0 {
+            return u32::MAX;
+        }
+        let settled = mean_tick + (tolerance.ln() - mean_log) / decay;
+        settled.max(elapsed as Real).min(u32::MAX as Real) as u32
+    }
+
+    // One call per frame for a whole buffer of encoded `Command`s.
+    pub fn apply_commands(&mut self, world: &mut World, commands: &[f32]) -> Result<usize, Error> {
+        command::apply(self, world, commands)
+    }
+
+    pub fn save_snapshot(&self, world: &World) -> Vec<u8> {
+        snapshot::encode(self, world)
+    }
+
+    pub fn load_snapshot(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let mut fabric = snapshot::decode_fabric(bytes)?;
+        // handles issued before the load go stale instead of finding whatever is loaded in their place
+        self.joint_handles.sync(0);
+        self.interval_handles.sync(0);
+        self.face_handles.sync(0);
+        fabric.joint_handles = std::mem::take(&mut self.joint_handles);
+        fabric.interval_handles = std::mem::take(&mut self.interval_handles);
+        fabric.face_handles = std::mem::take(&mut self.face_handles);
+        *self = fabric;
+        Ok(())
+    }
+
+    // One row per interval, comma or tab separated
+    pub fn interval_report(&self, world: &World, separator: char) -> String {
+        report::intervals(self, world, separator)
+    }
+
+    pub fn export_gltf(&self, world: &World) -> String {
+        export::gltf::document(self, world)
+    }
+
+    // Faces, and cylinders for the pushes when the strut radius is above zero
+    pub fn export_obj(&self, strut_radius: Real) -> String {
+        export::mesh::obj(&export::mesh::triangles(self, strut_radius))
+    }
+
+    pub fn export_stl(&self, strut_radius: Real) -> String {
+        export::mesh::stl(&export::mesh::triangles(self, strut_radius))
+    }
+
+    // The snapshot format without the world, compact enough for tens of thousands of intervals.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        snapshot::encode_fabric(self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Fabric, Error> {
+        snapshot::decode_fabric(bytes)
+    }
+
+    // Start tracking momentum every tick, measuring drift from now on.
+    pub fn set_momentum_reference(&mut self) {
+        let momentum = Momentum::of(&self.joints);
+        self.momentum = Some((self.age, momentum, momentum));
+    }
+
+    pub fn clear_momentum_reference(&mut self) {
+        self.momentum = None;
+    }
+
+    pub fn get_linear_momentum_drift(&self) -> Real {
+        self.momentum.map_or(0.0, |(_, reference, current)| {
+            (current.linear - reference.linear).magnitude()
+        })
+    }
+
+    pub fn get_angular_momentum_drift(&self) -> Real {
+        self.momentum.map_or(0.0, |(_, reference, current)| {
+            (current.angular - reference.angular).magnitude()
+        })
+    }
+
+    pub fn watch_region_tag(&mut self, tag: u16) -> usize {
+        self.regions.push(RegionWatch::new(Region::Tag(tag)));
+        self.regions.len() - 1
+    }
+
+    pub fn watch_region_box(
+        &mut self,
+        min_x: Real,
+        min_y: Real,
+        min_z: Real,
+        max_x: Real,
+        max_y: Real,
+        max_z: Real,
+    ) -> usize {
+        let min = Point3::new(min_x, min_y, min_z);
+        let max = Point3::new(max_x, max_y, max_z);
+        self.regions
+            .push(RegionWatch::new(Region::Aabb { min, max }));
+        self.regions.len() - 1
+    }
+
+    // A monitoring point at the position along the interval, from zero at alpha to one at omega.
+    pub fn add_sensor(&mut self, interval_index: usize, position: Real) -> Result<usize, Error> {
+        self.check_interval(interval_index)?;
+        if !(0.0..=1.0).contains(&position) {
+            return Err(Error::IllegalPosition { position });
+        }
+        let interval = &self.intervals[interval_index];
+        self.sensors.push(Sensor::new(
+            interval_index,
+            position,
+            interval,
+            &self.joints,
+        ));
+        Ok(self.sensors.len() - 1)
+    }
+
+    // Takes the interval's rest length over from here on, swinging it around where it is now by the
+    // amplitude fraction, at the frequency in cycles per tick, starting the phase into a cycle.
+    pub fn add_muscle(
+        &mut self,
+        interval_index: usize,
+        waveform: Waveform,
+        amplitude: Real,
+        frequency: Real,
+        phase: Real,
+    ) -> Result<usize, Error> {
+        self.attach_muscle(
+            interval_index,
+            Wave::Shape(waveform),
+            amplitude,
+            frequency,
+            phase,
+        )
+    }
+
+    // As add_muscle, with one cycle given as samples between minus one and one.
+    pub fn add_sampled_muscle(
+        &mut self,
+        interval_index: usize,
+        samples: &[f32],
+        amplitude: Real,
+        frequency: Real,
+        phase: Real,
+    ) -> Result<usize, Error> {
+        if samples.is_empty() {
+            return Err(Error::WrongLength {
+                expected: 1,
+                actual: 0,
+            });
+        }
+        if let Some(&sample) = samples
+            .iter()
+            .find(|sample| !(-1.0..=1.0).contains(*sample))
+        {
+            return Err(Error::IllegalAmplitude {
+                amplitude: sample as Real,
+            });
+        }
+        self.attach_muscle(
+            interval_index,
+            Wave::Samples(reals(samples)),
+            amplitude,
+            frequency,
+            phase,
+        )
+    }
+
+    pub fn clear_muscles(&mut self) {
+        self.muscles.clear();
+    }
+
+    pub fn get_muscle_count(&self) -> usize {
+        self.muscles.len()
+    }
+
+    // A new oscillator of the pattern generator, driving nothing until intervals are added to it.
+    pub fn add_oscillator(
+        &mut self,
+        frequency: Real,
+        amplitude: Real,
+        phase: Real,
+    ) -> Result<usize, Error> {
+        if !(0.0..1.0).contains(&amplitude) {
+            return Err(Error::IllegalAmplitude { amplitude });
+        }
+        if !(frequency.is_finite() && frequency >= 0.0) {
+            return Err(Error::IllegalFrequency { frequency });
+        }
+        if !phase.is_finite() {
+            return Err(Error::IllegalPosition { position: phase });
+        }
+        let pattern_generator = self
+            .pattern_generator
+            .get_or_insert_with(PatternGenerator::default);
+        let phase = phase.rem_euclid(1.0);
+        pattern_generator.oscillators.push(Oscillator {
+            frequency,
+            amplitude,
+            phase,
+            members: Vec::new(),
+        });
+        Ok(pattern_generator.oscillators.len() - 1)
+    }
+
+    // The interval's rest length swings around where it is now, against the others if inverted, so
+    // that pulls on opposite sides can work as a pair. It stops following any muscle.
+    pub fn add_oscillator_interval(
+        &mut self,
+        oscillator: usize,
+        interval_index: usize,
+        inverted: bool,
+    ) -> Result<(), Error> {
+        self.check_oscillator(oscillator)?;
+        self.check_interval(interval_index)?;
+        self.muscles
+            .retain(|muscle| muscle.interval_index != interval_index);
+        let interval = &self.intervals[interval_index];
+        let rest_length = interval.length_0 * (1.0 - interval.length_nuance)
+            + interval.length_1 * interval.length_nuance;
+        self.wake();
+        if let Some(pattern_generator) = &mut self.pattern_generator {
+            pattern_generator.join(oscillator, interval_index, rest_length, inverted);
+        }
+        Ok(())
+    }
+
+    // The follower settles the offset in cycles behind the leader, faster for more weight. Coupling
+    // both ways with opposite offsets holds the pair more firmly.
+    pub fn couple_oscillators(
+        &mut self,
+        leader: usize,
+        follower: usize,
+        weight: Real,
+        offset: Real,
+    ) -> Result<(), Error> {
+        self.check_oscillator(leader)?;
+        self.check_oscillator(follower)?;
+        if !(weight.is_finite() && weight >= 0.0) {
+            return Err(Error::IllegalCoupling { weight });
+        }
+        if !offset.is_finite() {
+            return Err(Error::IllegalPosition { position: offset });
+        }
+        if let Some(pattern_generator) = &mut self.pattern_generator {
+            pattern_generator
+                .couplings
+                .retain(|coupling| (coupling.leader, coupling.follower) != (leader, follower));
+            pattern_generator.couplings.push(Coupling {
+                leader,
+                follower,
+                weight,
+                offset,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn get_oscillator_phase(&self, oscillator: usize) -> Result<Real, Error> {
+        self.check_oscillator(oscillator)?;
+        Ok(self
+            .pattern_generator
+            .as_ref()
+            .map_or(0.0, |pattern_generator| {
+                pattern_generator.oscillators[oscillator].phase
+            }))
+    }
+
+    pub fn get_oscillator_count(&self) -> usize {
+        self.pattern_generator
+            .as_ref()
+            .map_or(0, |pattern_generator| pattern_generator.oscillators.len())
+    }
+
+    pub fn clear_oscillators(&mut self) {
+        self.pattern_generator = None;
+    }
+
+    pub fn clear_sensors(&mut self) {
+        self.sensors.clear();
+    }
+
+    pub fn get_sensor_count(&self) -> usize {
+        self.sensors.len()
+    }
+
+    // x, y, z per sensor, as of the last tick
+    pub fn copy_sensor_locations_to(&self, sensor_locations: &mut [f32]) {
+        for (sensor, location) in self.sensors.iter().zip(sensor_locations.chunks_mut(3)) {
+            for (to, from) in location.iter_mut().zip(sensor.location.iter()) {
+                *to = single(*from);
+            }
+        }
+    }
+
+    // Distance moved since the sensor was placed
+    pub fn get_sensor_deflection(&self, index: usize) -> Result<Real, Error> {
+        self.sensors
+            .get(index)
+            .map(|sensor| sensor.deflection().magnitude())
+            .ok_or(Error::SensorOutOfRange {
+                index,
+                count: self.sensors.len(),
+            })
+    }
+
+    // Keep this many ticks of strain for every interval and read the joints every tick, zero to stop.
+    pub fn set_reading_history(&mut self, ticks: usize) {
+        self.readings = match (ticks, self.readings.take()) {
+            (0, _) => None,
+            (_, Some(mut readings)) => {
+                for strains in readings.strains.iter_mut() {
+                    while strains.len() > ticks {
+                        strains.pop_front();
+                    }
+                }
+                readings.history = ticks;
+                Some(readings)
+            }
+            (_, None) => Some(Readings::new(ticks, &self.joints)),
+        };
+    }
+
+    // Oldest first, returning how many were copied.
+    pub fn copy_strain_history_to(
+        &self,
+        interval_index: usize,
+        strains: &mut [f32],
+    ) -> Result<usize, Error> {
+        self.check_interval(interval_index)?;
+        let mut count = 0;
+        for (strain, reading) in strains
+            .iter_mut()
+            .zip(self.sensor_view().strain_history(interval_index))
+        {
+            *strain = single(reading);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    // x, y, z per joint, the change in velocity over the last tick
+    pub fn copy_joint_accelerations_to(
+        &self,
+        joint_accelerations: &mut [f32],
+    ) -> Result<(), Error> {
+        let expected = self.joints.len() * 3;
+        if joint_accelerations.len() != expected {
+            return Err(Error::WrongLength {
+                expected,
+                actual: joint_accelerations.len(),
+            });
+        }
+        let sensors = self.sensor_view();
+        for (index, acceleration) in joint_accelerations.chunks_mut(3).enumerate() {
+            if let Some(reading) = sensors.acceleration(index) {
+                for (to, from) in acceleration.iter_mut().zip(reading.iter()) {
+                    *to = single(*from);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Packs the fabric for deployment, every joint pulled toward the centroid to the factor of its distance.
+    pub fn fold(&mut self, factor: Real) -> Result<(), Error> {
+        if !(factor > 0.0 && factor <= 1.0) {
+            return Err(Error::IllegalLength { length: factor });
+        }
+        if self.joints.is_empty() {
+            return Ok(());
+        }
+        let sum = self
+            .joints
+            .iter()
+            .fold(Vector3::zeros(), |sum, joint| sum + joint.location.coords);
+        let centroid = Point3::from(sum / self.joints.len() as Real);
+        self.wake();
+        for joint in self.joints.iter_mut() {
+            joint.location = centroid + (joint.location - centroid) * factor;
+            joint.velocity = zero();
+        }
+        Ok(())
+    }
+
+    // Deployment ticks count from the first step scheduled.
+    pub fn schedule_release(
+        &mut self,
+        tick: u32,
+        alpha_index: usize,
+        omega_index: usize,
+    ) -> Result<(), Error> {
+        self.check_joint(alpha_index)?;
+        self.check_joint(omega_index)?;
+        let action = DeployAction::Release {
+            alpha: alpha_index,
+            omega: omega_index,
+        };
+        self.deployment
+            .get_or_insert_with(Deployment::default)
+            .schedule(tick, action);
+        self.wake();
+        Ok(())
+    }
+
+    pub fn schedule_let_out(
+        &mut self,
+        tick: u32,
+        interval_index: usize,
+        rest_length: Real,
+        countdown: Real,
+    ) -> Result<(), Error> {
+        self.check_interval(interval_index)?;
+        if !(rest_length.is_finite() && rest_length > 0.0) {
+            return Err(Error::IllegalLength {
+                length: rest_length,
+            });
+        }
+        if !(countdown.is_finite() && countdown > 0.0) {
+            return Err(Error::IllegalCountdown { countdown });
+        }
+        let action = DeployAction::LetOut {
+            interval: interval_index,
+            rest_length,
+            countdown,
+        };
+        self.deployment
+            .get_or_insert_with(Deployment::default)
+            .schedule(tick, action);
+        self.wake();
+        Ok(())
+    }
+
+    pub fn get_deployment_remaining(&self) -> usize {
+        self.deployment.as_ref().map_or(0, Deployment::remaining)
+    }
+
+    // Largest strain magnitude, with its sign, since deployment started
+    pub fn get_deployment_peak_strain(&self, index: usize) -> Result<Real, Error> {
+        self.check_interval(index)?;
+        Ok(self
+            .deployment
+            .as_ref()
+            .and_then(|deployment| deployment.peak_strains.get(index).copied())
+            .unwrap_or(0.0))
+    }
+
+    pub fn clear_deployment(&mut self) {
+        self.deployment = None;
+    }
+
+    // Sequence ticks count from the start of playback. The delta multiplies the rest length, and a
+    // face stands for the intervals along its edges.
+    pub fn cue_twitch(
+        &mut self,
+        tick: u32,
+        interval_index: usize,
+        delta: Real,
+        attack: Real,
+        decay: Real,
+    ) -> Result<(), Error> {
+        self.check_interval(interval_index)?;
+        self.cue(
+            tick,
+            Cue::Twitch {
+                target: Target::Interval(interval_index),
+                delta,
+                attack,
+                decay,
             },
-            Stage::Slack => match requested_stage {
-                Stage::Pretensing => Some(self.start_pretensing(world)),
-                Stage::Shaping => Some(self.slack_to_shaping(world)),
-                _ => None,
+        )
+    }
+
+    pub fn cue_face_twitch(
+        &mut self,
+        tick: u32,
+        face_index: usize,
+        delta: Real,
+        attack: Real,
+        decay: Real,
+    ) -> Result<(), Error> {
+        self.check_face(face_index)?;
+        let target = Target::Face(self.faces[face_index].joints());
+        self.cue(
+            tick,
+            Cue::Twitch {
+                target,
+                delta,
+                attack,
+                decay,
             },
-            Stage::Pretensing => match requested_stage {
-                Stage::Pretenst => Some(self.set_stage(requested_stage)),
-                _ => None,
+        )
+    }
+
+    pub fn cue_length(
+        &mut self,
+        tick: u32,
+        interval_index: usize,
+        delta: Real,
+        countdown: Real,
+    ) -> Result<(), Error> {
+        self.check_interval(interval_index)?;
+        self.cue(
+            tick,
+            Cue::Length {
+                target: Target::Interval(interval_index),
+                delta,
+                countdown,
             },
-            Stage::Pretenst => match requested_stage {
-                Stage::Slack => Some(self.start_slack()),
-                _ => None,
+        )
+    }
+
+    pub fn cue_face_length(
+        &mut self,
+        tick: u32,
+        face_index: usize,
+        delta: Real,
+        countdown: Real,
+    ) -> Result<(), Error> {
+        self.check_face(face_index)?;
+        let target = Target::Face(self.faces[face_index].joints());
+        self.cue(
+            tick,
+            Cue::Length {
+                target,
+                delta,
+                countdown,
             },
+        )
+    }
+
+    // Rows of tick, target (0 interval, 1 face), index, delta, attack and decay, a zero decay making
+    // it a length change over the attack. Nothing is cued unless every row is good.
+    pub fn load_sequence(&mut self, rows: &[f32]) -> Result<usize, Error> {
+        let rows = reals(rows);
+        if !rows.len().is_multiple_of(SEQUENCE_ROW) {
+            return Err(Error::WrongLength {
+                expected: rows.len() - rows.len() % SEQUENCE_ROW,
+                actual: rows.len(),
+            });
+        }
+        let mut sequencer = self.sequencer.clone().unwrap_or_default();
+        let loaded = rows
+            .chunks(SEQUENCE_ROW)
+            .enumerate()
+            .try_for_each(|(row_index, row)| {
+                let malformed = Error::MalformedCommand {
+                    offset: row_index * SEQUENCE_ROW,
+                };
+                let tick = whole(row[0], MAX_WHOLE).ok_or(malformed.clone())? as u32;
+                let index = whole(row[2], MAX_WHOLE).ok_or(malformed.clone())?;
+                let target = match whole(row[1], 1).ok_or(malformed)? {
+                    0 => {
+                        self.check_interval(index)?;
+                        Target::Interval(index)
+                    }
+                    _ => {
+                        self.check_face(index)?;
+                        Target::Face(self.faces[index].joints())
+                    }
+                };
+                let (delta, attack, decay) = (row[3], row[4], row[5]);
+                let cue = if decay == 0.0 {
+                    Cue::Length {
+                        target,
+                        delta,
+                        countdown: attack,
+                    }
+                } else {
+                    Cue::Twitch {
+                        target,
+                        delta,
+                        attack,
+                        decay,
+                    }
+                };
+                cue.check()?;
+                sequencer.add(tick, cue);
+                Ok(())
+            });
+        let remaining = sequencer.remaining();
+        if loaded.is_ok() {
+            self.sequencer = Some(sequencer);
+            self.wake();
+        }
+        loaded.map(|_| remaining)
+    }
+
+    // After the period the sequence plays again from the start, zero for once.
+    pub fn set_sequence_period(&mut self, period: u32) {
+        let sequencer = self.sequencer.get_or_insert_with(Sequencer::default);
+        sequencer.period = if period == 0 { None } else { Some(period) };
+    }
+
+    pub fn rewind_sequence(&mut self) {
+        if let Some(sequencer) = &mut self.sequencer {
+            sequencer.rewind();
         }
     }
+
+    pub fn get_sequence_remaining(&self) -> usize {
+        self.sequencer.as_ref().map_or(0, Sequencer::remaining)
+    }
+
+    pub fn clear_sequence(&mut self) {
+        self.sequencer = None;
+    }
+
+    // Grows the code out of the face, which is taken away since the first twist covers it, one twist
+    // per bud every spacing ticks. Nothing changes if the code does not parse.
+    pub fn start_growth(
+        &mut self,
+        face_index: usize,
+        code: &str,
+        spacing: u32,
+    ) -> Result<(), Error> {
+        self.check_face(face_index)?;
+        let node = parse_growth(code).map_err(|error| Error::IllegalGrowth {
+            reason: error.to_string(),
+        })?;
+        let face = self.faces[face_index].joints();
+        let growth = Growth::new(face, node, spacing)?;
+        self.remove_face(face_index)?;
+        self.growth = Some(growth);
+        Ok(())
+    }
+
+    pub fn is_growing(&self) -> bool {
+        self.growth.is_some()
+    }
+
+    pub fn stop_growth(&mut self) {
+        self.growth = None;
+    }
+
+    pub fn extract_tags(&self, tags: &[u16], anchor_boundary: bool) -> Fabric {
+        let regions: Vec<Region> = tags.iter().map(|&tag| Region::Tag(tag)).collect();
+        self.extract(&regions, anchor_boundary)
+    }
+
+    // flat, for JavaScript
+    #[allow(clippy::too_many_arguments)]
+    pub fn extract_box(
+        &self,
+        min_x: Real,
+        min_y: Real,
+        min_z: Real,
+        max_x: Real,
+        max_y: Real,
+        max_z: Real,
+        anchor_boundary: bool,
+    ) -> Fabric {
+        let min = Point3::new(min_x, min_y, min_z);
+        let max = Point3::new(max_x, max_y, max_z);
+        self.extract(&[Region::Aabb { min, max }], anchor_boundary)
+    }
+
+    // Pairs of this fabric's joint index and the other fabric's, flattened.
+    pub fn warm_start_from_pairs(&mut self, other: &Fabric, pairs: &[u32]) -> Result<(), Error> {
+        if !pairs.len().is_multiple_of(2) {
+            return Err(Error::WrongLength {
+                expected: pairs.len() + 1,
+                actual: pairs.len(),
+            });
+        }
+        let mapping: Vec<(usize, usize)> = pairs
+            .chunks(2)
+            .map(|pair| (pair[0] as usize, pair[1] as usize))
+            .collect();
+        self.warm_start_from(other, &mapping)
+    }
+
+    pub fn get_region_mean_strain(&self, index: usize) -> Result<Real, Error> {
+        Ok(self.region_strain(index)?.mean)
+    }
+
+    pub fn get_region_max_strain(&self, index: usize) -> Result<Real, Error> {
+        Ok(self.region_strain(index)?.max)
+    }
+
+    pub fn get_oscillation_frequency(&self) -> Real {
+        self.damper.frequency()
+    }
+
+    pub fn is_asleep(&self) -> bool {
+        self.asleep
+    }
+
+    pub fn get_sleeping_joint_count(&self, world: &World) -> usize {
+        self.joints
+            .iter()
+            .filter(|joint| joint.is_asleep(world))
+            .count()
+    }
+
+    pub fn wake(&mut self) {
+        self.asleep = false;
+        for joint in self.joints.iter_mut() {
+            joint.still_ticks = 0;
+        }
+    }
+
+    pub fn state_digest(&self) -> u64 {
+        let mut digest = Digest::new();
+        for joint in self.joints.iter() {
+            digest.add(joint.location.x);
+            digest.add(joint.location.y);
+            digest.add(joint.location.z);
+        }
+        for interval in self.intervals.iter() {
+            digest.add(interval.strain);
+        }
+        digest.finish()
+    }
+
+    pub fn get_event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn get_stage(&self) -> Stage {
+        self.stage
+    }
+
+    pub fn request_stage(&mut self, requested_stage: Stage, world: &World) -> Option<Stage> {
+        let world = &*world.in_stage(requested_stage);
+        match self.stage {
+            Stage::Growing => match requested_stage {
+                Stage::Shaping => Some(self.set_stage(requested_stage)),
+                _ => None,
+            },
+            Stage::Shaping => match requested_stage {
+                Stage::Pretenst => Some(self.set_stage(requested_stage)),
+                Stage::Slack => Some(self.start_slack()),
+                _ => None,
+            },
+            Stage::Slack => match requested_stage {
+                Stage::Pretensing => Some(self.start_pretensing(world)),
+                Stage::Shaping => Some(self.slack_to_shaping(world)),
+                _ => None,
+            },
+            Stage::Pretensing => match requested_stage {
+                Stage::Pretenst => Some(self.set_stage(requested_stage)),
+                _ => None,
+            },
+            Stage::Pretenst => match requested_stage {
+                Stage::Slack => Some(self.start_slack()),
+                _ => None,
+            },
+        }
+    }
+}
+
+const FIT_PULL_STRAIN: Real = 0.01;
+
+impl Fabric {
+    pub(crate) fn check_degree(&self, joint: usize) -> Result<(), Error> {
+        if self.max_degree > 0 && self.degree(joint) >= self.max_degree {
+            return Err(Error::DegreeExceeded {
+                joint,
+                max: self.max_degree,
+            });
+        }
+        Ok(())
+    }
+
+    fn degree(&self, joint: usize) -> usize {
+        self.push_count(joint) + self.pull_count(joint)
+    }
+
+    fn push_count(&self, joint: usize) -> usize {
+        self.intervals
+            .iter()
+            .filter(|interval| interval.push && interval.touches(joint))
+            .count()
+    }
+
+    fn pull_count(&self, joint: usize) -> usize {
+        self.intervals
+            .iter()
+            .filter(|interval| !interval.push && interval.touches(joint))
+            .count()
+    }
+
+    // The last interval moves into the gap, and everything naming it by index follows.
+    pub(crate) fn swap_remove_interval(&mut self, index: usize) {
+        self.interval_handles
+            .swap_remove(self.intervals.len(), index);
+        self.intervals.swap_remove(index);
+        let last = self.intervals.len();
+        let moved_from = if index < last { Some(last) } else { None };
+        self.labels.remove(&index);
+        if let Some(label) = self.labels.remove(&last) {
+            self.labels.insert(index, label);
+        }
+        self.sensors.retain(|sensor| sensor.interval_index != index);
+        for sensor in self
+            .sensors
+            .iter_mut()
+            .filter(|sensor| sensor.interval_index == last)
+        {
+            sensor.interval_index = index;
+        }
+        self.muscles.retain(|muscle| muscle.interval_index != index);
+        for muscle in self
+            .muscles
+            .iter_mut()
+            .filter(|muscle| muscle.interval_index == last)
+        {
+            muscle.interval_index = index;
+        }
+        if let Some(pattern_generator) = &mut self.pattern_generator {
+            pattern_generator.interval_removed(index, last);
+        }
+        if let Some(readings) = &mut self.readings {
+            readings.interval_removed(index);
+        }
+        if let Some(deployment) = &mut self.deployment {
+            deployment.interval_removed(index, last);
+        }
+        if let Some(sequencer) = &mut self.sequencer {
+            sequencer.interval_removed(index, last);
+        }
+        if let Some(recording) = &mut self.boundary_recording {
+            recording.interval_removed(index, last);
+        }
+        if self.events.len() < MAX_EVENTS {
+            self.events
+                .push(FabricEvent::IntervalRemoved { index, moved_from });
+        }
+    }
+
+    pub(crate) fn check_joint(&self, index: usize) -> Result<(), Error> {
+        if index >= self.joints.len() {
+            return Err(Error::JointOutOfRange {
+                index,
+                count: self.joints.len(),
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_anchor(&self, index: usize) -> Result<(), Error> {
+        if index >= self.anchors.len() {
+            return Err(Error::AnchorOutOfRange {
+                index,
+                count: self.anchors.len(),
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_constraint(&self, index: usize) -> Result<(), Error> {
+        if index >= self.constraints.len() {
+            return Err(Error::ConstraintOutOfRange {
+                index,
+                count: self.constraints.len(),
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_interval(&self, index: usize) -> Result<(), Error> {
+        if index >= self.intervals.len() {
+            return Err(Error::IntervalOutOfRange {
+                index,
+                count: self.intervals.len(),
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_face(&self, index: usize) -> Result<(), Error> {
+        if index >= self.faces.len() {
+            return Err(Error::FaceOutOfRange {
+                index,
+                count: self.faces.len(),
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_oscillator(&self, index: usize) -> Result<(), Error> {
+        let count = self.get_oscillator_count();
+        if index >= count {
+            return Err(Error::OscillatorOutOfRange { index, count });
+        }
+        Ok(())
+    }
+
+    // Greedy from the shortest rest length is minimal in one dimension: a family spans at most
+    // a factor of 1 + tolerance. Pushes and pulls never share a family.
+    pub fn length_families(&self, tolerance: Real) -> Result<Vec<LengthFamily>, Error> {
+        if !(tolerance.is_finite() && tolerance >= 0.0) {
+            return Err(Error::IllegalTolerance { tolerance });
+        }
+        let mut families = Vec::new();
+        for role in [IntervalRole::Push, IntervalRole::Pull] {
+            let mut indices: Vec<usize> = (0..self.intervals.len())
+                .filter(|&index| self.intervals[index].push == (role == IntervalRole::Push))
+                .collect();
+            indices.sort_by(|&a, &b| {
+                self.intervals[a]
+                    .length_1
+                    .total_cmp(&self.intervals[b].length_1)
+            });
+            let mut family: Vec<usize> = Vec::new();
+            for index in indices {
+                let length = self.intervals[index].length_1;
+                if let Some(&first) = family.first() {
+                    if length > self.intervals[first].length_1 * (1.0 + tolerance) {
+                        families.push(self.length_family(role, family));
+                        family = Vec::new();
+                    }
+                }
+                family.push(index);
+            }
+            if !family.is_empty() {
+                families.push(self.length_family(role, family));
+            }
+        }
+        Ok(families)
+    }
+
+    fn length_family(&self, role: IntervalRole, intervals: Vec<usize>) -> LengthFamily {
+        let sum: Real = intervals
+            .iter()
+            .map(|&index| self.intervals[index].length_1)
+            .sum();
+        LengthFamily {
+            role,
+            mean: sum / intervals.len() as Real,
+            intervals,
+        }
+    }
+
+    // Joints that no hub in the catalog takes: too many members, or two of them too close together or
+    // too far apart. Joints without members need no hub.
+    pub fn check_buildability(&self, catalog: &Catalog) -> Vec<Unbuildable> {
+        let mut unbuildable = Vec::new();
+        for (joint, hub) in self.joints.iter().enumerate() {
+            let directions: Vec<Vector3<Real>> = self
+                .intervals
+                .iter()
+                .filter(|interval| interval.touches(joint))
+                .filter_map(|interval| {
+                    let other = if interval.alpha_index == joint {
+                        interval.omega_index
+                    } else {
+                        interval.alpha_index
+                    };
+                    (self.joints[other].location - hub.location).try_normalize(Real::EPSILON)
+                })
+                .collect();
+            if directions.is_empty() {
+                continue;
+            }
+            let mut min_angle = crate::real::consts::PI;
+            let mut max_angle = 0.0;
+            for (index, direction) in directions.iter().enumerate() {
+                for other in directions[index + 1..].iter() {
+                    let angle = direction.dot(other).clamp(-1.0, 1.0).acos();
+                    min_angle = min_angle.min(angle);
+                    max_angle = max_angle.max(angle);
+                }
+            }
+            let members = directions.len();
+            if !catalog
+                .hubs
+                .iter()
+                .any(|hub| hub.realizes(members, min_angle, max_angle))
+            {
+                unbuildable.push(Unbuildable {
+                    joint,
+                    members,
+                    min_angle,
+                    max_angle,
+                });
+            }
+        }
+        unbuildable
+    }
+
+    // Each new interval starts at the current joint distance and heads for its rest length over the
+    // countdown, but only after the ones before it have had stagger ticks each, so a growth step doesn't
+    // pull everywhere at once. Returns the index of the first. All of them or none: on an error the
+    // ones already added go again.
+    pub fn add_intervals_staggered(
+        &mut self,
+        specs: &[IntervalDescriptor],
+        countdown: Real,
+        stagger_ticks: u32,
+    ) -> Result<usize, Error> {
+        if !(countdown.is_finite() && countdown > 0.0) {
+            return Err(Error::IllegalCountdown { countdown });
+        }
+        for spec in specs {
+            self.check_joint(spec.alpha as usize)?;
+            self.check_joint(spec.omega as usize)?;
+        }
+        let first = self.intervals.len();
+        for (step, spec) in specs.iter().enumerate() {
+            let (alpha_index, omega_index) = (spec.alpha as usize, spec.omega as usize);
+            let length =
+                (self.joints[omega_index].location - self.joints[alpha_index].location).magnitude();
+            let push = spec.role == IntervalRole::Push;
+            let created = self.create_interval(
+                alpha_index,
+                omega_index,
+                push,
+                length,
+                spec.rest_length,
+                spec.stiffness,
+                1.0 / countdown,
+            );
+            let index = match created {
+                Ok(index) => index,
+                Err(error) => {
+                    self.intervals.truncate(first);
+                    return Err(error);
+                }
+            };
+            let interval = &mut self.intervals[index];
+            interval.tag = spec.tag;
+            interval.delay = stagger_ticks.saturating_mul(step as u32);
+        }
+        Ok(first)
+    }
+
+    // Sub-structuring: from now on, every tick records what the given intervals, the part a later run
+    // leaves out, do to the boundary joints. A new recording replaces the one under way.
+    pub fn record_boundary_forces(
+        &mut self,
+        joints: &[usize],
+        intervals: &[usize],
+    ) -> Result<(), Error> {
+        for &joint in joints {
+            self.check_joint(joint)?;
+        }
+        for &interval in intervals {
+            self.check_interval(interval)?;
+        }
+        self.boundary_recording = Some(BoundaryRecording::new(joints, intervals));
+        Ok(())
+    }
+
+    // Ends the recording and hands over what it caught.
+    pub fn export_boundary_forces(&mut self) -> Option<BoundaryForces> {
+        self.boundary_recording
+            .take()
+            .map(|recording| recording.forces)
+    }
+
+    // Replays exported forces as prescribed loads, tick by tick from the next one, on these joints
+    // standing in for the exported boundary joints in order.
+    pub fn apply_boundary_forces(
+        &mut self,
+        forces: &BoundaryForces,
+        joints: &[usize],
+    ) -> Result<(), Error> {
+        if joints.len() != forces.joints.len() {
+            return Err(Error::WrongLength {
+                expected: forces.joints.len(),
+                actual: joints.len(),
+            });
+        }
+        for &joint in joints {
+            self.check_joint(joint)?;
+        }
+        self.wake();
+        self.boundary_loads = Some(BoundaryLoads::new(joints, &forces.series));
+        Ok(())
+    }
+
+    pub fn get_boundary_ticks_remaining(&self) -> usize {
+        self.boundary_loads
+            .as_ref()
+            .map_or(0, BoundaryLoads::remaining)
+    }
+
+    // A push, drag or hanging weight on the joint for the whole of the next iterate. Forces on the same
+    // joint add up; call again before every iterate to keep it on.
+    pub fn add_force(&mut self, joint: JointId, force: Vector3<Real>) -> Result<(), Error> {
+        let index = self.get_joint_index(joint)?;
+        if !force.iter().all(|component| component.is_finite()) {
+            return Err(Error::IllegalForce {
+                force: force.magnitude(),
+            });
+        }
+        self.wake();
+        self.external_forces.push((index, force));
+        Ok(())
+    }
+
+    fn time_step(&self, world: &World) -> Real {
+        if world.acceleration_limit == 0.0 {
+            return world.time_step;
+        }
+        let acceleration = self
+            .joints
+            .iter()
+            .filter(|joint| joint.interval_mass > 0.0)
+            .map(|joint| joint.force.magnitude() / joint.interval_mass)
+            .fold(0.0, Real::max);
+        if acceleration * world.time_step > world.acceleration_limit {
+            world.acceleration_limit / acceleration
+        } else {
+            world.time_step
+        }
+    }
+
+    // Probing runs the intervals again on a copy of the joints, with copies of the intervals.
+    fn integration_steps(
+        &self,
+        world: &World,
+        time_step: Real,
+        pretensing_nuance: Real,
+    ) -> Vec<Step> {
+        let mut probe = |locations: &[Point3<Real>]| -> Vec<Vector3<Real>> {
+            let mut joints = self.joints.clone();
+            for (joint, location) in joints.iter_mut().zip(locations.iter()) {
+                joint.location = *location;
+                joint.reset();
+            }
+            for interval in self.intervals.iter() {
+                let mut interval = *interval;
+                interval
+                    .contribution(world, &joints, self.stage, pretensing_nuance)
+                    .apply(&mut joints);
+            }
+            joints.iter().map(|joint| joint.force).collect()
+        };
+        integrator::of(world.integration).steps(&self.joints, time_step, &mut probe)
+    }
+
+    fn cue(&mut self, tick: u32, cue: Cue) -> Result<(), Error> {
+        cue.check()?;
+        self.wake();
+        self.sequencer
+            .get_or_insert_with(Sequencer::default)
+            .add(tick, cue);
+        Ok(())
+    }
+
+    // One muscle per interval, a new one replacing the old, or an oscillator.
+    fn attach_muscle(
+        &mut self,
+        interval_index: usize,
+        wave: Wave,
+        amplitude: Real,
+        frequency: Real,
+        phase: Real,
+    ) -> Result<usize, Error> {
+        self.check_interval(interval_index)?;
+        if !(0.0..1.0).contains(&amplitude) {
+            return Err(Error::IllegalAmplitude { amplitude });
+        }
+        if !(frequency.is_finite() && frequency >= 0.0) {
+            return Err(Error::IllegalFrequency { frequency });
+        }
+        if !phase.is_finite() {
+            return Err(Error::IllegalPosition { position: phase });
+        }
+        self.muscles
+            .retain(|muscle| muscle.interval_index != interval_index);
+        if let Some(pattern_generator) = &mut self.pattern_generator {
+            pattern_generator.leave(interval_index);
+        }
+        let interval = &self.intervals[interval_index];
+        let rest_length = interval.length_0 * (1.0 - interval.length_nuance)
+            + interval.length_1 * interval.length_nuance;
+        self.wake();
+        self.muscles.push(Muscle {
+            interval_index,
+            rest_length,
+            wave,
+            amplitude,
+            frequency,
+            phase,
+        });
+        Ok(self.muscles.len() - 1)
+    }
+
+    fn grow(&mut self) {
+        let Some(mut growth) = self.growth.take() else {
+            return;
+        };
+        if growth.countdown > 0 {
+            growth.countdown -= 1;
+            self.growth = Some(growth);
+            return;
+        }
+        growth.countdown = growth.spacing;
+        for bud in std::mem::take(&mut growth.buds) {
+            let face = bud.face;
+            match self.extrude(bud) {
+                Ok(buds) => growth.buds.extend(buds),
+                Err(_error) => {
+                    trace_info!(error = %_error, tick = self.age, "growth stopped");
+                    if self.events.len() < MAX_EVENTS {
+                        self.events.push(FabricEvent::GrowthStopped {
+                            face,
+                            tick: self.age,
+                        });
+                    }
+                }
+            }
+        }
+        if !growth.buds.is_empty() {
+            self.growth = Some(growth);
+        }
+    }
+
+    // One twist on the face of the bud: three pushes leaning around from the base to a new triangle
+    // above it, tied by pulls around the top and straight up from the base. Everything is checked
+    // before anything is built, so a twist that can't go on leaves the fabric as it was. Returns what
+    // grows next, leaving a face on top where the growth ends.
+    fn extrude(&mut self, bud: Bud) -> Result<Vec<Bud>, Error> {
+        let base = bud.face;
+        for joint in base {
+            self.check_joint(joint)?;
+        }
+        let face = Face::new(base[0], base[1], base[2]);
+        let midpoint = Point3::from(face.midpoint(&self.joints));
+        let normal = face.normal(&self.joints);
+        let size = bud.size * bud.scale;
+        let radius = base
+            .iter()
+            .map(|&joint| (self.joints[joint].location - midpoint).magnitude())
+            .sum::<Real>()
+            / 3.0;
+        let height = normal * (radius * size * TWIST_HEIGHT);
+        let rotation = Rotation3::from_axis_angle(&Unit::new_normalize(normal), TWIST_ANGLE);
+        let locations = base.map(|joint| {
+            midpoint + height + rotation * (self.joints[joint].location - midpoint) * size
+        });
+        if let Some(&length) = locations
+            .iter()
+            .flat_map(|location| location.iter())
+            .find(|coordinate| !coordinate.is_finite())
+        {
+            return Err(Error::IllegalLength { length });
+        }
+        let closed: Vec<bool> = (0..3)
+            .map(|side| {
+                self.intervals
+                    .iter()
+                    .any(|interval| interval.connects(base[side], base[(side + 1) % 3]))
+            })
+            .collect();
+        // each base joint gets a push, a vertical and whichever base pulls are missing
+        for side in 0..3 {
+            let added = 2 + usize::from(!closed[side]) + usize::from(!closed[(side + 2) % 3]);
+            if self.max_degree > 0 && self.degree(base[side]) + added > self.max_degree {
+                return Err(Error::DegreeExceeded {
+                    joint: base[side],
+                    max: self.max_degree,
+                });
+            }
+        }
+        // and each top joint a push, a vertical and two pulls around the top
+        if self.max_degree > 0 && self.max_degree < 4 {
+            return Err(Error::DegreeExceeded {
+                joint: self.joints.len(),
+                max: self.max_degree,
+            });
+        }
+        let mut top = [0_usize; 3];
+        for (side, location) in locations.iter().enumerate() {
+            top[side] = self.create_joint(location.x, location.y, location.z)?;
+        }
+        for side in 0..3 {
+            let next = (side + 1) % 3;
+            if !closed[side] {
+                self.create_interval_here(base[side], base[next], false)?;
+            }
+            self.create_interval_here(base[side], top[next], true)?;
+            self.create_interval_here(base[side], top[side], false)?;
+            self.create_interval_here(top[side], top[next], false)?;
+        }
+        if bud.remaining > 1 {
+            return Ok(vec![Bud {
+                face: top,
+                remaining: bud.remaining - 1,
+                size,
+                ..bud
+            }]);
+        }
+        let Some(node) = bud.then else {
+            self.create_face(top[0], top[1], top[2])?;
+            return Ok(Vec::new());
+        };
+        let center = midpoint + height / 2.0;
+        let mut buds = Bud::sprout(&node, &Twist { base, top }, size);
+        for bud in buds.iter_mut() {
+            // wound to face away from the middle of the twist
+            let outward = Face::new(bud.face[0], bud.face[1], bud.face[2]);
+            let out = Point3::from(outward.midpoint(&self.joints)) - center;
+            if outward.normal(&self.joints).dot(&out) < 0.0 {
+                bud.face.swap(1, 2);
+            }
+        }
+        Ok(buds)
+    }
+
+    // At the distance the joints are apart now.
+    fn create_interval_here(
+        &mut self,
+        alpha_index: usize,
+        omega_index: usize,
+        push: bool,
+    ) -> Result<usize, Error> {
+        let length =
+            (self.joints[omega_index].location - self.joints[alpha_index].location).magnitude();
+        self.create_interval(alpha_index, omega_index, push, length, length, 1.0, 0.0)
+    }
+
+    // Whatever is still to be played keeps the fabric from falling asleep.
+    fn is_scheduled(&self) -> bool {
+        self.sequencer.as_ref().is_some_and(Sequencer::is_playing)
+            || self.get_deployment_remaining() > 0
+    }
+
+    // A controller that commands anything wakes the fabric.
+    fn control(&mut self) {
+        let Some(mut controller) = self.controller.take() else {
+            return;
+        };
+        let mut actuators = Actuators::new(self.intervals.len());
+        controller.control(&self.sensor_view(), &mut actuators);
+        if !actuators.is_empty() {
+            actuators.apply(&mut self.intervals);
+            self.wake();
+        }
+        self.controller = Some(controller);
+    }
+
+    // Max joint speed and max strain rate of the tick just taken.
+    fn motion(&self) -> (Real, Real) {
+        let speed = self
+            .joints
+            .iter()
+            .map(|joint| joint.velocity.magnitude())
+            .fold(0.0, Real::max);
+        let strain_rate = self
+            .intervals
+            .iter()
+            .map(|interval| interval.strain_rate.abs())
+            .fold(0.0, Real::max);
+        (speed, strain_rate)
+    }
+
+    fn record_settling(&mut self, motion: (Real, Real)) {
+        if self.settling.len() == MAX_SETTLED_ITERATIONS {
+            self.settling.pop_front();
+        }
+        self.settling.push_back(motion);
+    }
+
+    // Struts at their rest lengths nested onto the stock, see cutting::plan.
+    pub fn cutting_plan(&self, stock_lengths: &[Real], kerf: Real) -> Result<Vec<StockBar>, Error> {
+        let struts: Vec<Cut> = self
+            .intervals
+            .iter()
+            .enumerate()
+            .filter(|(_, interval)| interval.push)
+            .map(|(interval, strut)| Cut {
+                interval,
+                length: strut.length_1,
+            })
+            .collect();
+        cutting::plan(&struts, stock_lengths, kerf)
+    }
+
+    // A point mass held at an offset in the face's frame by a push and a pull to each face joint,
+    // so it stays put either way. Returns the payload joint.
+    pub fn attach_payload(
+        &mut self,
+        face_index: usize,
+        mass: Real,
+        offset: Vector3<Real>,
+    ) -> Result<usize, Error> {
+        self.check_face(face_index)?;
+        if !(mass.is_finite() && mass >= 0.0) {
+            return Err(Error::IllegalMass { mass });
+        }
+        let face = self.faces[face_index];
+        let location = face.frame(&self.joints).world_point(&offset);
+        let payload = self.create_joint(location.x, location.y, location.z)?;
+        self.joints[payload].payload_mass = mass;
+        for joint_index in face.joints() {
+            let length = (self.joints[joint_index].location - location).magnitude();
+            for push in [true, false] {
+                let index = self.create_interval(
+                    payload,
+                    joint_index,
+                    push,
+                    length,
+                    length,
+                    PAYLOAD_STIFFNESS,
+                    0.0,
+                )?;
+                self.intervals[index].linear_density = 0.0;
+            }
+        }
+        Ok(payload)
+    }
+
+    // Reference tick, reference momentum and latest momentum, while tracking
+    pub fn momentum_drift(&self) -> Option<(u32, Momentum, Momentum)> {
+        self.momentum
+    }
+
+    // A standalone copy of the intervals in any of the regions and the joints they need.
+    // Boundary joints are the ones that also had an interval left behind; anchoring holds them
+    // where the rest of the structure had them.
+    pub fn extract(&self, regions: &[Region], anchor_boundary: bool) -> Fabric {
+        let selected: Vec<bool> = self
+            .intervals
+            .iter()
+            .map(|interval| {
+                regions
+                    .iter()
+                    .any(|region| region.contains(interval, &self.joints))
+            })
+            .collect();
+        let mut kept = vec![false; self.joints.len()];
+        let mut boundary = vec![false; self.joints.len()];
+        for (interval, &selected) in self.intervals.iter().zip(selected.iter()) {
+            for index in [interval.alpha_index, interval.omega_index] {
+                if selected {
+                    kept[index] = true;
+                } else {
+                    boundary[index] = true;
+                }
+            }
+        }
+        let mut map: Vec<Option<usize>> = vec![None; self.joints.len()];
+        let mut joints = Vec::new();
+        for (index, joint) in self.joints.iter().enumerate() {
+            if kept[index] {
+                map[index] = Some(joints.len());
+                joints.push(*joint);
+            }
+        }
+        let labels = (0..self.intervals.len())
+            .filter(|&index| selected[index])
+            .enumerate()
+            .filter_map(|(new_index, index)| Some((new_index, self.labels.get(&index)?.clone())))
+            .collect();
+        let intervals = self
+            .intervals
+            .iter()
+            .zip(selected.iter())
+            .filter(|(_, &selected)| selected)
+            .map(|(interval, _)| {
+                let mut interval = *interval;
+                interval.alpha_index = map[interval.alpha_index].unwrap();
+                interval.omega_index = map[interval.omega_index].unwrap();
+                for end in interval.attachments.iter_mut() {
+                    *end = end.and_then(|attachment| {
+                        let hub_index = map[attachment.hub_index]?;
+                        Some(Attachment {
+                            hub_index,
+                            ..attachment
+                        })
+                    });
+                }
+                interval
+            })
+            .collect();
+        let faces = self
+            .faces
+            .iter()
+            .filter_map(|face| {
+                let [joint0, joint1, joint2] = face.joints();
+                Some(Face::new(map[joint0]?, map[joint1]?, map[joint2]?))
+            })
+            .collect();
+        let mut anchors: Vec<Anchor> = self
+            .anchors
+            .iter()
+            .filter_map(|anchor| {
+                let joint_index = map[anchor.joint_index]?;
+                Some(Anchor {
+                    joint_index,
+                    ..*anchor
+                })
+            })
+            .collect();
+        if anchor_boundary {
+            for (index, joint) in self.joints.iter().enumerate() {
+                let Some(joint_index) = map[index] else {
+                    continue;
+                };
+                if boundary[index]
+                    && !anchors
+                        .iter()
+                        .any(|anchor| anchor.joint_index == joint_index)
+                {
+                    anchors.push(Anchor::new(
+                        joint_index,
+                        joint.location,
+                        Real::INFINITY,
+                        Real::INFINITY,
+                    ));
+                }
+            }
+        }
+        let constraints = self
+            .constraints
+            .iter()
+            .filter_map(|constraint| {
+                let alpha_index = map[constraint.alpha_index]?;
+                let omega_index = map[constraint.omega_index]?;
+                Some(Constraint {
+                    alpha_index,
+                    omega_index,
+                    ..*constraint
+                })
+            })
+            .collect();
+        let mut fabric = Fabric {
+            joints,
+            intervals,
+            faces,
+            anchors,
+            constraints,
+            labels,
+            external_forces: Vec::new(),
+            boundary_recording: None,
+            boundary_loads: None,
+            settling: VecDeque::new(),
+            #[cfg(not(feature = "parallel"))]
+            lanes: Lanes::default(),
+            readings: None,
+            controller: None,
+            joint_handles: Handles::default(),
+            interval_handles: Handles::default(),
+            face_handles: Handles::default(),
+            sensors: Vec::new(),
+            muscles: Vec::new(),
+            pattern_generator: None,
+            deployment: None,
+            sequencer: None,
+            growth: None,
+            events: Vec::new(),
+            momentum: None,
+            ..self.clone()
+        };
+        fabric.damper.reset();
+        fabric.wake();
+        fabric
+    }
+
+    // As of the last tick
+    pub fn region_strain(&self, index: usize) -> Result<RegionStrain, Error> {
+        self.regions
+            .get(index)
+            .map(|watch| watch.latest)
+            .ok_or(Error::RegionOutOfRange {
+                index,
+                count: self.regions.len(),
+            })
+    }
+
+    pub fn drain_events(&mut self) -> Vec<FabricEvent> {
+        self.events.drain(..).collect()
+    }
+
+    // Settling a variation converges much faster from where a similar fabric already settled, so matched
+    // joints take over the other's location and velocity. Mapping is this joint index to the other's.
+    pub fn warm_start_from(
+        &mut self,
+        other: &Fabric,
+        mapping: &[(usize, usize)],
+    ) -> Result<(), Error> {
+        for &(joint_index, other_index) in mapping {
+            self.check_joint(joint_index)?;
+            other.check_joint(other_index)?;
+        }
+        self.wake();
+        for &(joint_index, other_index) in mapping {
+            let source = &other.joints[other_index];
+            let joint = &mut self.joints[joint_index];
+            joint.location = source.location;
+            joint.velocity = source.velocity;
+        }
+        Ok(())
+    }
+
+    // Rest lengths which make the target geometry a self-equilibrium: the force densities come from the
+    // least-squares null space of the joint equilibrium matrix, scaled to a mean pull strain.
+    pub fn fit_rest_lengths(
+        &mut self,
+        targets: &[(usize, Point3<Real>)],
+        world: &World,
+    ) -> Result<Real, Error> {
+        for &(joint_index, location) in targets {
+            self.check_joint(joint_index)?;
+            if let Some(&position) = location.iter().find(|position| !position.is_finite()) {
+                return Err(Error::IllegalPosition { position });
+            }
+        }
+        // a strain comes from dividing the tension by the stiffness
+        let stiffnesses: Vec<Real> = self
+            .intervals
+            .iter()
+            .map(|interval| {
+                interval.stiffness
+                    * RoleDefaults::of(world, interval.role(), Stage::Pretenst).stiffness
+            })
+            .collect();
+        if let Some(&stiffness) = stiffnesses
+            .iter()
+            .find(|stiffness| !(stiffness.is_finite() && **stiffness > 0.0))
+        {
+            return Err(Error::IllegalStiffness { stiffness });
+        }
+        self.wake();
+        for &(joint_index, location) in targets {
+            self.joints[joint_index].location = location;
+        }
+        let interval_count = self.intervals.len();
+        if interval_count == 0 {
+            return Ok(0.0);
+        }
+        let mut equilibrium = DMatrix::<Real>::zeros(self.joints.len() * 3, interval_count);
+        for (index, interval) in self.intervals.iter_mut().enumerate() {
+            interval.calculate_current_length_mut(&self.joints);
+            for axis in 0..3 {
+                equilibrium[(interval.alpha_index * 3 + axis, index)] += interval.unit[axis];
+                equilibrium[(interval.omega_index * 3 + axis, index)] -= interval.unit[axis];
+            }
+        }
+        let eigen = (equilibrium.transpose() * &equilibrium).symmetric_eigen();
+        let (smallest, residual) = eigen
+            .eigenvalues
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(index, value)| (index, value.max(0.0).sqrt()))
+            .unwrap();
+        let mut tensions: Vec<Real> = eigen
+            .eigenvectors
+            .column(smallest)
+            .iter()
+            .copied()
+            .collect();
+        let pull_sum: Real = self
+            .intervals
+            .iter()
+            .zip(&tensions)
+            .filter(|(interval, _)| !interval.push)
+            .map(|(_, tension)| tension)
+            .sum();
+        if pull_sum < 0.0 {
+            tensions.iter_mut().for_each(|tension| *tension = -*tension);
+        }
+        let strains: Vec<Real> = tensions
+            .iter()
+            .zip(&stiffnesses)
+            .map(|(tension, stiffness)| tension / stiffness)
+            .collect();
+        let pull_strains: Vec<Real> = self
+            .intervals
+            .iter()
+            .zip(&strains)
+            .filter(|(interval, _)| !interval.push)
+            .map(|(_, strain)| *strain)
+            .collect();
+        let mean_pull_strain =
+            pull_strains.iter().sum::<Real>() / pull_strains.len().max(1) as Real;
+        let scale = if mean_pull_strain > 0.0 {
+            FIT_PULL_STRAIN / mean_pull_strain
+        } else {
+            0.0
+        };
+        for (interval, strain) in self.intervals.iter_mut().zip(strains) {
+            let strain = if interval.push {
+                (strain * scale).clamp(-0.5, 0.0)
+            } else {
+                (strain * scale).max(0.0)
+            };
+            let ideal_length = interval.calculate_current_length(&self.joints) / (1.0 + strain);
+            let rest_length = if interval.push {
+                ideal_length / (1.0 + world.pretenst_factor)
+            } else {
+                ideal_length
+            };
+            interval.length_0 = rest_length;
+            interval.length_1 = rest_length;
+            interval.length_nuance = 0.0;
+            interval.attack = 0.0;
+            interval.decay = 0.0;
+        }
+        Ok(residual)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::{chain, joints};
+
+    use super::*;
+
+    fn spec(alpha: u32, omega: u32, rest_length: Real) -> IntervalDescriptor {
+        IntervalDescriptor {
+            alpha,
+            omega,
+            role: IntervalRole::Pull,
+            rest_length,
+            stiffness: 1.0,
+            tag: 0,
+        }
+    }
+
+    #[test]
+    fn staggered_intervals_are_added_all_or_none() {
+        let mut fabric = joints(3);
+        let bad = [spec(0, 1, 1.0), spec(1, 2, Real::NAN)];
+        assert!(fabric.add_intervals_staggered(&bad, 10.0, 5).is_err());
+        assert!(fabric.intervals.is_empty());
+        fabric.set_topology_rules(1, 0);
+        assert!(fabric
+            .add_intervals_staggered(&[spec(0, 1, 1.0), spec(1, 2, 1.0)], 10.0, 5)
+            .is_err());
+        assert!(fabric.intervals.is_empty());
+        fabric.set_topology_rules(0, 0);
+        assert_eq!(
+            fabric
+                .add_intervals_staggered(&[spec(0, 1, 1.0), spec(1, 2, 1.0)], 10.0, 5)
+                .unwrap(),
+            0
+        );
+        assert_eq!(fabric.intervals[1].delay, 5);
+        assert!(fabric
+            .add_intervals_staggered(&[spec(0, 1, 1.0)], Real::INFINITY, 5)
+            .is_err());
+    }
+
+    #[test]
+    fn settles_on_the_iterations_asked_for_with_separate_tolerances() {
+        let mut world = World::new();
+        world.gravity = 0.0;
+        let mut fabric = chain(2, 0.5);
+        fabric.iterate_ticks(&world, 10);
+        assert!(!fabric.is_settled(1e-6, 1e-6, 1));
+        assert!(fabric.is_settled(Real::MAX, Real::MAX, 1));
+        assert!(!fabric.is_settled(Real::MAX, Real::MAX, 2));
+        let (speed, strain_rate) = fabric.settling[0];
+        assert!(speed > 0.0 && strain_rate > 0.0);
+        assert!(!fabric.is_settled(Real::MAX, strain_rate / 2.0, 1));
+        assert!(!fabric.is_settled(speed / 2.0, Real::MAX, 1));
+        for _iteration in 0..MAX_SETTLED_ITERATIONS + 5 {
+            fabric.iterate_ticks(&world, 1);
+        }
+        assert_eq!(fabric.settling.len(), MAX_SETTLED_ITERATIONS);
+        assert!(!fabric.is_settled(Real::MAX, Real::MAX, MAX_SETTLED_ITERATIONS + 1));
+    }
+
+    #[test]
+    fn broken_intervals_are_removed_or_disabled_by_role() {
+        let mut world = World::new();
+        world.gravity = 0.0;
+        world
+            .set_ultimate_strain(IntervalRole::Push, 0.1, false)
+            .unwrap();
+        world
+            .set_ultimate_strain(IntervalRole::Pull, 0.1, true)
+            .unwrap();
+        let mut fabric = joints(4);
+        fabric
+            .add_interval_safe(0, 1, IntervalRole::Push, 1.0, 2.0, 0.0)
+            .unwrap();
+        fabric
+            .add_interval_safe(2, 3, IntervalRole::Pull, 1.0, 0.5, 0.0)
+            .unwrap();
+        fabric.iterate_ticks(&world, 1);
+        assert_eq!(fabric.intervals.len(), 1);
+        assert!(fabric.intervals[0].push && fabric.intervals[0].disabled);
+    }
+
+    #[test]
+    fn fitting_rejects_targets_at_no_real_place_and_intervals_without_stiffness() {
+        let mut fabric = chain(3, 1.0);
+        let targets = [(2, Point3::new(Real::NAN, 0.0, 0.0))];
+        assert!(matches!(
+            fabric.fit_rest_lengths(&targets, &World::new()),
+            Err(Error::IllegalPosition { .. })
+        ));
+        assert_eq!(fabric.joints[2].location.x, 2.0);
+        assert!(fabric
+            .fit_rest_lengths(&[(2, Point3::new(2.0, 1.0, 0.0))], &World::new())
+            .is_ok());
+        fabric.intervals[1].stiffness = 0.0;
+        let targets = [(2, Point3::new(2.0, 0.5, 0.0))];
+        assert!(matches!(
+            fabric.fit_rest_lengths(&targets, &World::new()),
+            Err(Error::IllegalStiffness { .. })
+        ));
+        assert_eq!(fabric.joints[2].location.y, 1.0);
+        assert!(fabric
+            .intervals
+            .iter()
+            .all(|interval| interval.length_0.is_finite()));
+    }
+
+    #[test]
+    fn a_floating_fabric_stays_where_it_was_posed() {
+        let world = World::new();
+        let mut fabric = chain(2, 1.0);
+        fabric.stage = Stage::Pretenst;
+        fabric.joints[0].velocity = Vector3::new(0.0, -1.0, 0.0);
+        fabric.set_floating(true);
+        assert_eq!(fabric.joints[0].velocity, Vector3::zeros());
+        fabric.iterate_ticks(&world, 100);
+        assert!(
+            (fabric.joints[0].location.y - 1.0).abs() < 1e-4,
+            "{}",
+            fabric.joints[0].location.y
+        );
+        fabric.set_floating(false);
+        fabric.iterate_ticks(&world, 1000);
+        assert!(
+            fabric.joints[0].location.y < 0.99,
+            "{}",
+            fabric.joints[0].location.y
+        );
+    }
+
+    #[test]
+    fn a_joint_hitting_the_ground_reports_its_impulse() {
+        let mut fabric = Fabric::new(0);
+        fabric.create_joint(0.0, 0.05, 0.0).unwrap();
+        fabric.stage = Stage::Pretenst;
+        fabric.joints[0].velocity = Vector3::new(0.0, -0.01, 0.0);
+        fabric.iterate_ticks(&World::new(), 100);
+        let contacts: Vec<ContactEvent> = fabric
+            .drain_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                FabricEvent::Contact(contact) => Some(contact),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(contacts.len(), 1);
+        assert!(contacts[0].impulse > 0.0, "{}", contacts[0].impulse);
+        assert_eq!(contacts[0].normal, Vector3::y());
+        assert!(fabric.drain_events().is_empty());
+    }
+
+    #[test]
+    fn a_still_fabric_falls_asleep_and_wakes_when_changed() {
+        let mut world = World::new();
+        world.gravity = 0.0;
+        world.set_sleep(1e-3, 1e-3, 10).unwrap();
+        let mut fabric = chain(2, 1.0);
+        fabric.iterate_ticks(&world, 20);
+        assert!(fabric.is_asleep());
+        assert_eq!(fabric.get_sleeping_joint_count(&world), 2);
+        assert!(fabric
+            .drain_events()
+            .iter()
+            .any(|event| matches!(event, FabricEvent::FabricAsleep { .. })));
+        fabric.change_rest_length(0, 0.5, 10.0).unwrap();
+        assert!(!fabric.is_asleep());
+        assert_eq!(fabric.get_sleeping_joint_count(&world), 0);
+        // a host that never drains gets no more than the buffer holds
+        let mut still = chain(2, 1.0);
+        still.events = vec![FabricEvent::JointRemoved { index: 0 }; MAX_EVENTS];
+        still.iterate_ticks(&world, 20);
+        assert!(still.is_asleep());
+        assert_eq!(still.events.len(), MAX_EVENTS);
+    }
+
+    #[test]
+    fn joints_keep_to_the_speed_limit_of_the_stage() {
+        let mut world = World::new();
+        world.gravity = 0.0;
+        world.set_speed_limit(Stage::Pretenst, 0.01).unwrap();
+        let mut fabric = Fabric::new(0);
+        fabric.create_joint(0.0, 1.0, 0.0).unwrap();
+        fabric.joints[0].velocity = Vector3::new(1.0, 0.0, 0.0);
+        fabric.iterate_ticks(&world, 1);
+        assert!(fabric.joints[0].velocity.magnitude() > 0.5);
+        fabric.stage = Stage::Pretenst;
+        fabric.iterate_ticks(&world, 1);
+        assert!(
+            fabric.joints[0].velocity.magnitude() <= 0.01 + 1e-6,
+            "{}",
+            fabric.joints[0].velocity.magnitude()
+        );
+    }
+
+    #[test]
+    fn a_safely_added_interval_starts_at_the_distance_between_its_joints() {
+        let mut world = World::new();
+        world.gravity = 0.0;
+        let mut fabric = Fabric::new(0);
+        fabric.create_joint(0.0, 1.0, 0.0).unwrap();
+        fabric.create_joint(2.0, 1.0, 0.0).unwrap();
+        let index = fabric
+            .add_interval_safe(0, 1, IntervalRole::Pull, 3.0, 0.5, 10.0)
+            .unwrap();
+        let interval = &fabric.intervals[index];
+        assert_eq!(
+            (interval.length_0, interval.length_1, interval.stiffness),
+            (2.0, 1.0, 3.0)
+        );
+        fabric.iterate_ticks(&world, 1);
+        assert!(
+            fabric.intervals[index].strain.abs() < 0.01,
+            "{}",
+            fabric.intervals[index].strain
+        );
+        assert!(fabric
+            .add_interval_safe(0, 2, IntervalRole::Pull, 1.0, 1.0, 0.0)
+            .is_err());
+    }
+
+    #[test]
+    fn retriangulates_a_closed_rim_and_drops_faces_with_a_removed_joint() {
+        let mut fabric = joints(5);
+        for (a, b) in [(1, 2), (2, 3), (3, 4), (4, 1)] {
+            fabric.create_face(0, a, b).unwrap();
+        }
+        fabric.create_face(1, 2, 3).unwrap();
+        assert_eq!(fabric.retriangulate_around(0).unwrap(), 2);
+        let faces: Vec<[usize; 3]> = fabric.faces.iter().map(Face::joints).collect();
+        assert_eq!(faces, [[1, 2, 3], [1, 2, 3], [1, 3, 4]]);
+        fabric.remove_joint(4).unwrap();
+        assert_eq!(fabric.faces.len(), 2);
+        assert_eq!(fabric.retriangulate_around(1).unwrap(), 0);
+    }
+
+    #[test]
+    fn estimates_settling_on_a_probe_and_leaves_the_fabric_as_it_was() {
+        let mut world = World::new();
+        world.gravity = 0.0;
+        let mut fabric = chain(2, 0.5);
+        assert_eq!(
+            fabric.estimate_settling(&world, Real::MAX),
+            SETTLING_WINDOW_TICKS
+        );
+        let ticks = fabric.estimate_settling(&world, 1e-12);
+        assert!(
+            ticks > SETTLING_WINDOW_TICKS && ticks < u32::MAX,
+            "{}",
+            ticks
+        );
+        assert_eq!(fabric.age, 0);
+        assert_eq!(fabric.joints[1].location, Point3::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn groups_lengths_by_role_within_the_tolerance_and_snaps_them_to_the_mean() {
+        let mut fabric = joints(5);
+        for (alpha, rest_factor) in [(0, 1.0), (1, 1.04), (2, 1.5)] {
+            fabric
+                .add_interval_safe(alpha, alpha + 1, IntervalRole::Pull, 1.0, rest_factor, 0.0)
+                .unwrap();
+        }
+        fabric
+            .add_interval_safe(3, 4, IntervalRole::Push, 1.0, 1.0, 0.0)
+            .unwrap();
+        let families = fabric.length_families(0.05).unwrap();
+        let grouped: Vec<(IntervalRole, Vec<usize>)> = families
+            .iter()
+            .map(|family| (family.role, family.intervals.clone()))
+            .collect();
+        assert_eq!(
+            grouped,
+            [
+                (IntervalRole::Push, vec![3]),
+                (IntervalRole::Pull, vec![0, 1]),
+                (IntervalRole::Pull, vec![2])
+            ]
+        );
+        assert!(fabric.length_families(-0.1).is_err());
+        assert_eq!(fabric.snap_length_families(0.05, 10.0).unwrap(), 3);
+        assert!((fabric.intervals[0].length_1 - 1.02).abs() < 1e-6);
+        assert!((fabric.intervals[1].length_1 - 1.02).abs() < 1e-6);
+        assert!((fabric.intervals[2].length_1 - 1.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn frozen_transitions_and_pretensing_wait_while_physics_runs() {
+        let world = World::new();
+        let mut fabric = chain(2, 1.0);
+        fabric.multiply_rest_length(0, 2.0, 100.0).unwrap();
+        fabric.set_transitions_frozen(true);
+        fabric.iterate_ticks(&world, 50);
+        assert_eq!(fabric.intervals[0].length_nuance, 0.0);
+        fabric.set_transitions_frozen(false);
+        fabric.iterate_ticks(&world, 1);
+        assert!(fabric.intervals[0].length_nuance > 0.0);
+
+        let mut fabric = joints(2);
+        fabric.pretensing_countdown = 100.0;
+        fabric.set_transitions_frozen(true);
+        assert!(fabric.iterate(&world));
+        assert_eq!(fabric.pretensing_countdown, 100.0);
+        fabric.set_transitions_frozen(false);
+        fabric.iterate(&world);
+        assert!(fabric.pretensing_countdown < 100.0);
+    }
+
+    #[test]
+    fn extracts_the_tagged_part_anchored_where_it_was_cut() {
+        let mut fabric = chain(4, 1.0);
+        fabric.set_interval_tag(0, 1).unwrap();
+        fabric.set_interval_tag(1, 1).unwrap();
+        fabric.set_interval_tag(2, 2).unwrap();
+        fabric.set_interval_label(1, "kept").unwrap();
+        fabric.create_face(0, 1, 2).unwrap();
+        fabric.create_face(1, 2, 3).unwrap();
+        let part = fabric.extract_tags(&[1], true);
+        assert_eq!(
+            (part.joints.len(), part.intervals.len(), part.faces.len()),
+            (3, 2, 1)
+        );
+        assert_eq!(part.anchors.len(), 1);
+        assert_eq!(part.anchors[0].joint_index, 2);
+        assert_eq!(part.get_interval_label(1).unwrap().as_deref(), Some("kept"));
+        assert!(fabric.extract_tags(&[1], false).anchors.is_empty());
+        let boxed = fabric.extract_box(1.5, 0.0, -1.0, 3.0, 2.0, 1.0, false);
+        assert_eq!((boxed.joints.len(), boxed.intervals.len()), (3, 2));
+    }
+
+    #[test]
+    fn topology_rules_cap_the_degree_and_keep_push_ends_tied() {
+        let mut fabric = joints(4);
+        fabric
+            .add_interval_safe(0, 1, IntervalRole::Push, 1.0, 1.0, 0.0)
+            .unwrap();
+        fabric
+            .add_interval_safe(0, 2, IntervalRole::Pull, 1.0, 1.0, 0.0)
+            .unwrap();
+        fabric
+            .add_interval_safe(0, 3, IntervalRole::Pull, 1.0, 1.0, 0.0)
+            .unwrap();
+        fabric.set_topology_rules(3, 2);
+        assert!(matches!(
+            fabric.add_interval_safe(0, 2, IntervalRole::Pull, 1.0, 1.0, 0.0),
+            Err(Error::DegreeExceeded { joint: 0, max: 3 })
+        ));
+        assert!(matches!(
+            fabric.remove_interval(1),
+            Err(Error::Undertriangulated {
+                joint: 0,
+                pulls: 1,
+                min: 2
+            })
+        ));
+        assert!(matches!(
+            fabric.check_topology(),
+            Err(Error::Undertriangulated {
+                joint: 1,
+                pulls: 0,
+                min: 2
+            })
+        ));
+        fabric.set_topology_rules(0, 0);
+        fabric.remove_interval(1).unwrap();
+        assert!(fabric.check_topology().is_ok());
+    }
+
+    #[test]
+    fn a_warm_start_takes_over_only_the_matched_joints_and_checks_them_all_first() {
+        let mut settled = joints(3);
+        settled.joints[2].location = Point3::new(5.0, 2.0, 0.0);
+        settled.joints[2].velocity = Vector3::new(0.0, 0.1, 0.0);
+        let mut variation = joints(2);
+        assert!(variation
+            .warm_start_from_pairs(&settled, &[1, 2, 0])
+            .is_err());
+        assert!(matches!(
+            variation.warm_start_from(&settled, &[(1, 2), (0, 3)]),
+            Err(Error::JointOutOfRange { index: 3, .. })
+        ));
+        assert_eq!(variation.joints[1].location, Point3::new(1.0, 1.0, 0.0));
+        variation.warm_start_from_pairs(&settled, &[1, 2]).unwrap();
+        assert_eq!(variation.joints[1].location, Point3::new(5.0, 2.0, 0.0));
+        assert_eq!(variation.joints[1].velocity, Vector3::new(0.0, 0.1, 0.0));
+        assert_eq!(variation.joints[0].location, Point3::new(0.0, 1.0, 0.0));
+    }
+
+    // Deserializing needs a default for every skipped field, which only shows when the derives expand.
+    #[cfg(feature = "serde")]
+    #[test]
+    fn the_fabric_and_its_parts_derive_serde() {
+        fn serializable<T: serde::Serialize + serde::de::DeserializeOwned>() {}
+        serializable::<Fabric>();
+        serializable::<Joint>();
+        serializable::<Interval>();
+        serializable::<Face>();
+    }
+
+    #[test]
+    fn removing_a_joint_takes_its_intervals_and_renumbers_the_rest() {
+        let mut fabric = chain(4, 1.0);
+        fabric
+            .add_interval_safe(0, 3, IntervalRole::Pull, 1.0, 1.0, 0.0)
+            .unwrap();
+        fabric.create_anchor(3, Real::MAX, Real::MAX).unwrap();
+        fabric.remove_joint(1).unwrap();
+        let ends: Vec<(usize, usize)> = fabric
+            .intervals
+            .iter()
+            .map(|interval| (interval.alpha_index, interval.omega_index))
+            .collect();
+        assert_eq!(ends, [(1, 2), (0, 2)]);
+        assert_eq!(fabric.anchors[0].joint_index, 2);
+        let events = fabric.drain_events();
+        assert_eq!(
+            events
+                .iter()
+                .filter(|event| matches!(event, FabricEvent::IntervalRemoved { .. }))
+                .count(),
+            2
+        );
+        assert!(matches!(
+            events.last(),
+            Some(FabricEvent::JointRemoved { index: 1 })
+        ));
+    }
+
+    #[test]
+    fn shortening_a_pull_against_its_tension_costs_work() {
+        let mut fabric = chain(2, 1.0);
+        for joint in 0..2 {
+            fabric.set_joint_fixed(joint, true).unwrap();
+        }
+        fabric.stage = Stage::Pretenst;
+        fabric.multiply_rest_length(0, 0.8, 10.0).unwrap();
+        fabric.iterate_ticks(&World::new(), 5);
+        assert!(fabric.get_actuation_energy() > 0.0);
+        let halfway = fabric.get_actuation_work();
+        fabric.iterate_ticks(&World::new(), 20);
+        assert!(fabric.get_actuation_work() > halfway && halfway > 0.0);
+        assert_eq!(fabric.get_actuation_energy(), 0.0);
+        fabric.reset_actuation_work();
+        assert_eq!(fabric.get_actuation_work(), 0.0);
+    }
+
+    #[test]
+    fn an_added_force_pushes_its_joint_for_one_iterate_only() {
+        let mut fabric = joints(2);
+        let id = fabric.get_joint_id(0).unwrap();
+        let mut bad = Vector3::zeros();
+        bad.x = Real::NAN;
+        assert!(matches!(
+            fabric.add_force(id, bad),
+            Err(Error::IllegalForce { .. })
+        ));
+        fabric.add_force(id, Vector3::new(1.0, 0.0, 0.0)).unwrap();
+        fabric.iterate(&World::new());
+        assert!(fabric.joints[0].location.x > 0.0);
+        assert_eq!(fabric.joints[1].location.x, 1.0);
+        assert!(fabric.external_forces.is_empty());
+    }
+
+    #[test]
+    fn an_iterate_runs_the_ticks_of_its_stage() {
+        let mut world = World::new();
+        let mut fabric = chain(2, 1.0);
+        world.set_stage_feature(fabric.stage, WorldFeature::IterationsPerFrame, 3.0);
+        fabric.iterate(&world);
+        assert_eq!(fabric.age, 3);
+    }
 }
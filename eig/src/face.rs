@@ -5,9 +5,27 @@
 use nalgebra::*;
 
 use crate::joint::Joint;
+use crate::real::{single, Real};
 use crate::view::View;
 
+// Origin at the midpoint, tangent along the first edge, normal as in `Face::normal`.
+#[derive(Clone, Copy, Debug)]
+pub struct FaceFrame {
+    pub origin: Point3<Real>,
+    pub tangent: Vector3<Real>,
+    pub bitangent: Vector3<Real>,
+    pub normal: Vector3<Real>,
+}
+
+impl FaceFrame {
+    // offset given in tangent, bitangent, normal components
+    pub fn world_point(&self, offset: &Vector3<Real>) -> Point3<Real> {
+        self.origin + self.tangent * offset.x + self.bitangent * offset.y + self.normal * offset.z
+    }
+}
+
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Face {
     joints: [usize; 3],
 }
@@ -27,6 +45,23 @@ impl Face {
         })
     }
 
+    pub fn joints(&self) -> [usize; 3] {
+        self.joints
+    }
+
+    pub fn contains(&self, joint_index: usize) -> bool {
+        self.joints.contains(&joint_index)
+    }
+
+    // the edge facing away from the joint, in winding order
+    pub fn opposite_edge(&self, joint_index: usize) -> Option<(usize, usize)> {
+        let position = self.joints.iter().position(|&index| index == joint_index)?;
+        Some((
+            self.joints[(position + 1) % 3],
+            self.joints[(position + 2) % 3],
+        ))
+    }
+
     pub fn _joint<'a>(&self, joints: &'a Vec<Joint>, index: usize) -> &'a Joint {
         &joints[self.joints[index]]
     }
@@ -35,13 +70,26 @@ impl Face {
         &mut joints[self.joints[index]]
     }
 
-    pub fn midpoint(&self, joints: &Vec<Joint>) -> Vector3<f32> {
-        (&joints[self.joints[0]].location.coords +
-            &joints[self.joints[1]].location.coords +
-            &joints[self.joints[2]].location.coords) / 3.0
+    pub fn midpoint(&self, joints: &Vec<Joint>) -> Vector3<Real> {
+        (&joints[self.joints[0]].location.coords
+            + &joints[self.joints[1]].location.coords
+            + &joints[self.joints[2]].location.coords)
+            / 3.0
     }
 
-    pub fn normal(&self, joints: &Vec<Joint>) -> Vector3<f32> {
+    pub fn frame(&self, joints: &Vec<Joint>) -> FaceFrame {
+        let normal = self.normal(joints);
+        let edge = joints[self.joints[1]].location - joints[self.joints[0]].location;
+        let tangent = (edge - normal * edge.dot(&normal)).normalize();
+        FaceFrame {
+            origin: Point3::from(self.midpoint(joints)),
+            tangent,
+            bitangent: normal.cross(&tangent),
+            normal,
+        }
+    }
+
+    pub fn normal(&self, joints: &Vec<Joint>) -> Vector3<Real> {
         let location0 = &joints[self.joints[0]].location;
         let location1 = &joints[self.joints[1]].location;
         let location2 = &joints[self.joints[2]].location;
@@ -52,18 +100,60 @@ impl Face {
 
     pub fn project_features(&self, joints: &Vec<Joint>, view: &mut View) {
         let midpoint = self.midpoint(joints);
-        view.face_midpoints.push(midpoint.x);
-        view.face_midpoints.push(midpoint.y);
-        view.face_midpoints.push(midpoint.z);
+        view.face_midpoints.push(single(midpoint.x));
+        view.face_midpoints.push(single(midpoint.y));
+        view.face_midpoints.push(single(midpoint.z));
         let normal = self.normal(joints);
         for index in 0..3 {
             let location = &joints[self.joints[index]].location;
-            view.face_vertex_locations.push(location.x);
-            view.face_vertex_locations.push(location.y);
-            view.face_vertex_locations.push(location.z);
-            view.face_normals.push(normal.x);
-            view.face_normals.push(normal.y);
-            view.face_normals.push(normal.z);
+            view.face_vertex_locations.push(single(location.x));
+            view.face_vertex_locations.push(single(location.y));
+            view.face_vertex_locations.push(single(location.z));
+            view.face_normals.push(single(normal.x));
+            view.face_normals.push(single(normal.y));
+            view.face_normals.push(single(normal.z));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::fabric::Fabric;
+
+    use super::*;
+
+    #[test]
+    fn a_payload_sits_at_its_offset_in_the_face_frame_braced_to_every_corner() {
+        let mut fabric = Fabric::new(0);
+        for (x, z) in [(0.0, 0.0), (3.0, 0.0), (0.0, 3.0)] {
+            fabric.create_joint(x, 1.0, z).unwrap();
         }
+        let face = fabric.create_face(0, 1, 2).unwrap();
+        let frame = fabric.faces[face].frame(&fabric.joints);
+        assert!((frame.tangent - Vector3::x()).magnitude() < 1e-6);
+        assert!(
+            frame.tangent.dot(&frame.bitangent).abs() < 1e-6
+                && frame.normal.dot(&frame.bitangent).abs() < 1e-6
+        );
+        assert!(matches!(
+            fabric.attach_payload(face, -1.0, Vector3::zeros()),
+            Err(Error::IllegalMass { .. })
+        ));
+        let payload = fabric
+            .attach_payload(face, 2.0, Vector3::new(0.0, 0.0, 0.5))
+            .unwrap();
+        let expected = frame.origin + frame.normal * 0.5;
+        assert!((fabric.joints[payload].location - expected).magnitude() < 1e-6);
+        assert_eq!(fabric.joints[payload].payload_mass, 2.0);
+        assert_eq!(fabric.intervals.len(), 6);
+        assert_eq!(
+            fabric
+                .intervals
+                .iter()
+                .filter(|interval| interval.push)
+                .count(),
+            3
+        );
     }
 }
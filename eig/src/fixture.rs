@@ -0,0 +1,30 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+// Small fabrics the unit tests start from, so each test only says what differs.
+
+use crate::constants::IntervalRole;
+use crate::fabric::Fabric;
+use crate::real::Real;
+
+// Joints a unit apart along x, all a unit off the ground.
+pub(crate) fn joints(count: usize) -> Fabric {
+    let mut fabric = Fabric::new(0);
+    for x in 0..count {
+        fabric.create_joint(x as Real, 1.0, 0.0).unwrap();
+    }
+    fabric
+}
+
+// The same joints with each pulled to the next, resting at the given factor of the unit between them.
+pub(crate) fn chain(count: usize, rest_factor: Real) -> Fabric {
+    let mut fabric = joints(count);
+    for alpha in 1..count {
+        fabric
+            .add_interval_safe(alpha - 1, alpha, IntervalRole::Pull, 1.0, rest_factor, 0.0)
+            .unwrap();
+    }
+    fabric
+}
@@ -0,0 +1,124 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use nalgebra::*;
+
+use crate::constants::*;
+use crate::fabric::Fabric;
+use crate::real::Real;
+use crate::world::World;
+
+#[derive(Clone, Copy, Debug)]
+pub struct FrameConfig {
+    pub ticks_per_frame: usize,
+    pub frame_limit: Option<usize>,
+}
+
+impl Default for FrameConfig {
+    fn default() -> Self {
+        FrameConfig {
+            ticks_per_frame: 100,
+            frame_limit: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FrameSummary {
+    pub age: u32,
+    pub stage: Stage,
+    pub busy: bool,
+    pub midpoint: Point3<Real>,
+    pub strain_limits: [Real; 4],
+    pub event_count: usize,
+}
+
+impl FrameSummary {
+    pub fn of(fabric: &Fabric, busy: bool) -> FrameSummary {
+        let mut midpoint: Vector3<Real> = zero();
+        for joint in fabric.joints.iter() {
+            midpoint += &joint.location.coords;
+        }
+        if !fabric.joints.is_empty() {
+            midpoint /= fabric.joints.len() as Real;
+        }
+        FrameSummary {
+            age: fabric.age,
+            stage: fabric.stage,
+            busy,
+            midpoint: Point3::from(midpoint),
+            strain_limits: fabric.strain_limits,
+            event_count: fabric.events.len(),
+        }
+    }
+}
+
+pub struct Frames<'a> {
+    fabric: &'a mut Fabric,
+    world: &'a World,
+    config: FrameConfig,
+    count: usize,
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = FrameSummary;
+
+    fn next(&mut self) -> Option<FrameSummary> {
+        if matches!(self.config.frame_limit, Some(limit) if self.count >= limit) {
+            return None;
+        }
+        self.count += 1;
+        let busy = self
+            .fabric
+            .iterate_ticks(self.world, self.config.ticks_per_frame);
+        Some(FrameSummary::of(self.fabric, busy))
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<'a> futures_core::Stream for Frames<'a> {
+    type Item = FrameSummary;
+
+    fn poll_next(
+        self: std::pin::Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<FrameSummary>> {
+        std::task::Poll::Ready(self.get_mut().next())
+    }
+}
+
+impl Fabric {
+    pub fn frames<'a>(&'a mut self, world: &'a World, config: FrameConfig) -> Frames<'a> {
+        Frames {
+            fabric: self,
+            world,
+            config,
+            count: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_run_the_ticks_of_each_and_stop_at_the_limit() {
+        let world = World::new();
+        let mut fabric = Fabric::new(0);
+        fabric.create_joint(0.0, 1.0, 0.0).unwrap();
+        fabric.create_joint(2.0, 1.0, 0.0).unwrap();
+        let config = FrameConfig {
+            ticks_per_frame: 5,
+            frame_limit: Some(3),
+        };
+        let frames: Vec<FrameSummary> = fabric.frames(&world, config).collect();
+        assert_eq!(
+            frames.iter().map(|frame| frame.age).collect::<Vec<_>>(),
+            [5, 10, 15]
+        );
+        assert_eq!(frames[2].midpoint.x, 1.0);
+    }
+}
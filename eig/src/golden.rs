@@ -0,0 +1,185 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::fabric::Fabric;
+use crate::real::Real;
+use crate::world::World;
+
+const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+const DIGEST_RESOLUTION: Real = 1e4;
+
+pub struct Digest(u64);
+
+impl Digest {
+    pub fn new() -> Digest {
+        Digest(FNV_OFFSET)
+    }
+
+    pub fn add(&mut self, value: Real) {
+        let rounded = (value * DIGEST_RESOLUTION).round() as i64;
+        for byte in rounded.to_le_bytes() {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for Digest {
+    fn default() -> Self {
+        Digest::new()
+    }
+}
+
+#[derive(Debug)]
+pub enum GoldenError {
+    Io(io::Error),
+    Missing {
+        name: String,
+    },
+    Mismatch {
+        name: String,
+        expected: u64,
+        actual: u64,
+    },
+}
+
+impl Display for GoldenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GoldenError::Io(err) => write!(f, "golden file: {err}"),
+            GoldenError::Missing { name } => write!(f, "no golden digest for {name}"),
+            GoldenError::Mismatch {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{name}: expected digest {expected:016x}, got {actual:016x}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {}
+
+pub fn scenario_digest(fabric: &mut Fabric, world: &World, iterations: usize) -> u64 {
+    for _ in 0..iterations {
+        fabric.iterate(world);
+    }
+    fabric.state_digest()
+}
+
+pub fn load(path: &Path) -> io::Result<BTreeMap<String, u64>> {
+    let mut digests = BTreeMap::new();
+    if !path.exists() {
+        return Ok(digests);
+    }
+    for line in fs::read_to_string(path)?.lines() {
+        let Some((name, digest)) = line.split_once(' ') else {
+            continue;
+        };
+        if let Ok(digest) = u64::from_str_radix(digest.trim(), 16) {
+            digests.insert(name.to_string(), digest);
+        }
+    }
+    Ok(digests)
+}
+
+pub fn record(path: &Path, name: &str, digest: u64) -> io::Result<()> {
+    let mut digests = load(path)?;
+    digests.insert(name.to_string(), digest);
+    let contents: String = digests
+        .iter()
+        .map(|(name, digest)| format!("{name} {digest:016x}\n"))
+        .collect();
+    fs::write(path, contents)
+}
+
+pub fn compare(path: &Path, name: &str, digest: u64) -> Result<(), GoldenError> {
+    let digests = load(path).map_err(GoldenError::Io)?;
+    match digests.get(name) {
+        None => Err(GoldenError::Missing {
+            name: name.to_string(),
+        }),
+        Some(&expected) if expected != digest => Err(GoldenError::Mismatch {
+            name: name.to_string(),
+            expected,
+            actual: digest,
+        }),
+        Some(_) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::{IntervalRole, Stage};
+
+    use super::*;
+
+    // A strut held by a tetrahedron of pulls set a little short, so it moves as it settles.
+    fn braced() -> Fabric {
+        let mut fabric = Fabric::new(0);
+        for (x, y, z) in [
+            (1.0, 1.0, 1.0),
+            (-1.0, -1.0, 1.0),
+            (-1.0, 1.0, -1.0),
+            (1.0, -1.0, -1.0),
+        ] {
+            fabric.create_joint(x, y + 3.0, z).unwrap();
+        }
+        for alpha in 0..4 {
+            for omega in alpha + 1..4 {
+                let role = if (alpha, omega) == (0, 1) {
+                    IntervalRole::Push
+                } else {
+                    IntervalRole::Pull
+                };
+                fabric
+                    .add_interval_safe(alpha, omega, role, 1.0, 0.9, 0.0)
+                    .unwrap();
+            }
+        }
+        fabric
+    }
+
+    fn scenarios() -> Vec<(&'static str, Fabric, World, usize)> {
+        let mut dropped = braced();
+        let world = World::new();
+        dropped.request_stage(Stage::Shaping, &world).unwrap();
+        dropped.request_stage(Stage::Pretenst, &world).unwrap();
+        vec![
+            ("braced_shaping", braced(), World::new(), 5),
+            ("braced_dropped", dropped, world, 20),
+        ]
+    }
+
+    // With GOLDEN_RECORD set the digests are written instead, after a change meant to move them.
+    // recorded in f32, so a run in f64 is a different physics
+    #[test]
+    #[cfg(not(feature = "f64"))]
+    fn scenarios_keep_their_golden_digests() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("golden.txt");
+        let recording = std::env::var_os("GOLDEN_RECORD").is_some();
+        for (name, mut fabric, world, iterations) in scenarios() {
+            let digest = scenario_digest(&mut fabric, &world, iterations);
+            if recording {
+                record(&path, name, digest).unwrap();
+            } else if let Err(error) = compare(&path, name, digest) {
+                panic!("{}", error);
+            }
+        }
+    }
+}
@@ -0,0 +1,261 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use wasm_bindgen::prelude::*;
+
+use crate::constants::IntervalRole;
+use crate::error::Error;
+use crate::fabric::Fabric;
+use crate::real::Real;
+
+macro_rules! handle {
+    ($name:ident) => {
+        #[wasm_bindgen]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub struct $name {
+            pub(crate) slot: u32,
+            pub(crate) generation: u32,
+        }
+
+        #[wasm_bindgen]
+        impl $name {
+            pub fn get_slot(&self) -> u32 {
+                self.slot
+            }
+
+            pub fn get_generation(&self) -> u32 {
+                self.generation
+            }
+        }
+    };
+}
+
+handle!(JointId);
+handle!(IntervalId);
+handle!(FaceId);
+
+// The fabric by handle alone, for callers that hold on to elements while others come and go. Every
+// call checks its handles first, so a stale one is an error rather than a different element.
+impl Fabric {
+    pub fn add_joint(&mut self, x: Real, y: Real, z: Real) -> Result<JointId, Error> {
+        let index = self.create_joint(x, y, z)?;
+        self.get_joint_id(index)
+    }
+
+    pub fn add_interval(
+        &mut self,
+        alpha: JointId,
+        omega: JointId,
+        role: IntervalRole,
+        stiffness: Real,
+        target_rest_factor: Real,
+        countdown: Real,
+    ) -> Result<IntervalId, Error> {
+        let alpha_index = self.get_joint_index(alpha)?;
+        let omega_index = self.get_joint_index(omega)?;
+        let index = self.add_interval_safe(
+            alpha_index,
+            omega_index,
+            role,
+            stiffness,
+            target_rest_factor,
+            countdown,
+        )?;
+        self.get_interval_id(index)
+    }
+
+    pub fn add_face(&mut self, joints: [JointId; 3]) -> Result<FaceId, Error> {
+        let [joint0, joint1, joint2] = joints;
+        let (joint0, joint1, joint2) = (
+            self.get_joint_index(joint0)?,
+            self.get_joint_index(joint1)?,
+            self.get_joint_index(joint2)?,
+        );
+        let index = self.create_face(joint0, joint1, joint2)?;
+        self.get_face_id(index)
+    }
+
+    pub fn interval_ends(&mut self, interval: IntervalId) -> Result<[JointId; 2], Error> {
+        let index = self.get_interval_index(interval)?;
+        let (alpha_index, omega_index) = (
+            self.intervals[index].alpha_index,
+            self.intervals[index].omega_index,
+        );
+        Ok([
+            self.get_joint_id(alpha_index)?,
+            self.get_joint_id(omega_index)?,
+        ])
+    }
+
+    pub fn face_joints(&mut self, face: FaceId) -> Result<[JointId; 3], Error> {
+        let index = self.get_face_index(face)?;
+        let [joint0, joint1, joint2] = self.faces[index].joints();
+        Ok([
+            self.get_joint_id(joint0)?,
+            self.get_joint_id(joint1)?,
+            self.get_joint_id(joint2)?,
+        ])
+    }
+
+    pub fn remove_joint_by_id(&mut self, joint: JointId) -> Result<(), Error> {
+        let index = self.get_joint_index(joint)?;
+        self.remove_joint(index)
+    }
+
+    pub fn remove_interval_by_id(&mut self, interval: IntervalId) -> Result<(), Error> {
+        let index = self.get_interval_index(interval)?;
+        self.remove_interval(index)
+    }
+
+    pub fn remove_face_by_id(&mut self, face: FaceId) -> Result<(), Error> {
+        let index = self.get_face_index(face)?;
+        self.remove_face(index)
+    }
+}
+
+// Slots outlive the elements they were issued for: removing an element bumps its slot's generation,
+// so a handle still held for it resolves to nothing instead of to whatever moved into its index.
+// Elements pushed straight onto storage (snapshots, generators) get their slots lazily from sync.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Handles {
+    // generation of each slot and the index of its element while it lives
+    slots: Vec<(u32, Option<usize>)>,
+    // slot of each element
+    elements: Vec<usize>,
+    free: Vec<usize>,
+}
+
+impl Handles {
+    pub(crate) fn sync(&mut self, count: usize) {
+        while self.elements.len() > count {
+            let slot = self.elements.pop().unwrap();
+            self.release(slot);
+        }
+        while self.elements.len() < count {
+            let index = self.elements.len();
+            let slot = match self.free.pop() {
+                Some(slot) => slot,
+                None => {
+                    self.slots.push((0, None));
+                    self.slots.len() - 1
+                }
+            };
+            self.slots[slot].1 = Some(index);
+            self.elements.push(slot);
+        }
+    }
+
+    pub(crate) fn issue(&mut self, index: usize, count: usize) -> (u32, u32) {
+        self.sync(count);
+        let slot = self.elements[index];
+        (slot as u32, self.slots[slot].0)
+    }
+
+    pub(crate) fn resolve(&self, slot: u32, generation: u32) -> Option<usize> {
+        match self.slots.get(slot as usize) {
+            Some(&(current, index)) if current == generation => index,
+            _ => None,
+        }
+    }
+
+    // Storage of count elements keeps only those passing keep, in order.
+    pub(crate) fn retain(&mut self, count: usize, keep: impl Fn(usize) -> bool) {
+        self.sync(count);
+        let elements = std::mem::take(&mut self.elements);
+        for (index, slot) in elements.into_iter().enumerate() {
+            if keep(index) {
+                self.slots[slot].1 = Some(self.elements.len());
+                self.elements.push(slot);
+            } else {
+                self.release(slot);
+            }
+        }
+    }
+
+    pub(crate) fn swap_remove(&mut self, count: usize, index: usize) {
+        self.sync(count);
+        let slot = self.elements.swap_remove(index);
+        self.release(slot);
+        if let Some(&moved) = self.elements.get(index) {
+            self.slots[moved].1 = Some(index);
+        }
+    }
+
+    fn release(&mut self, slot: usize) {
+        let (generation, index) = &mut self.slots[slot];
+        *generation = generation.wrapping_add(1);
+        *index = None;
+        self.free.push(slot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::IntervalRole;
+    use crate::error::Error;
+    use crate::fabric::Fabric;
+    use crate::real::Real;
+
+    fn chain() -> (Fabric, Vec<super::JointId>, Vec<super::IntervalId>) {
+        let mut fabric = Fabric::new(0);
+        let joints: Vec<_> = (0..4)
+            .map(|x| fabric.add_joint(x as Real, 0.0, 0.0).unwrap())
+            .collect();
+        let intervals = joints
+            .windows(2)
+            .map(|pair| {
+                fabric
+                    .add_interval(pair[0], pair[1], IntervalRole::Pull, 1.0, 1.0, 0.0)
+                    .unwrap()
+            })
+            .collect();
+        (fabric, joints, intervals)
+    }
+
+    #[test]
+    fn handles_follow_their_elements_through_removals() {
+        let (mut fabric, joints, intervals) = chain();
+        fabric.remove_interval_by_id(intervals[0]).unwrap();
+        // the last interval was swapped into the first one's index
+        assert_eq!(fabric.get_interval_index(intervals[2]).unwrap(), 0);
+        assert_eq!(
+            fabric.interval_ends(intervals[2]).unwrap(),
+            [joints[2], joints[3]]
+        );
+        fabric.remove_joint_by_id(joints[0]).unwrap();
+        assert_eq!(fabric.get_joint_index(joints[3]).unwrap(), 2);
+    }
+
+    #[test]
+    fn removed_elements_leave_stale_handles() {
+        let (mut fabric, joints, intervals) = chain();
+        fabric.remove_joint_by_id(joints[1]).unwrap();
+        assert!(matches!(
+            fabric.get_joint_index(joints[1]),
+            Err(Error::StaleHandle { .. })
+        ));
+        assert!(matches!(
+            fabric.get_interval_index(intervals[0]),
+            Err(Error::StaleHandle { .. })
+        ));
+        assert!(fabric.remove_interval_by_id(intervals[1]).is_err());
+        // a new joint reuses the slot under a new generation
+        let joint = fabric.add_joint(9.0, 0.0, 0.0).unwrap();
+        assert_eq!(joint.slot, joints[1].slot);
+        assert_ne!(joint.generation, joints[1].generation);
+        assert!(fabric.get_joint_index(joints[1]).is_err());
+    }
+
+    #[test]
+    fn loading_a_snapshot_makes_every_earlier_handle_stale() {
+        let (mut fabric, joints, intervals) = chain();
+        let bytes = fabric.to_bytes();
+        fabric.load_snapshot(&bytes).unwrap();
+        assert!(fabric.get_joint_index(joints[0]).is_err());
+        assert!(fabric.get_interval_index(intervals[0]).is_err());
+        let joint = fabric.get_joint_id(0).unwrap();
+        assert_eq!(fabric.get_joint_index(joint).unwrap(), 0);
+    }
+}
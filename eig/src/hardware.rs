@@ -0,0 +1,79 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use crate::real::Real;
+
+// A hub that can be bought: how many members it takes and the angles, in radians, it allows between
+// any two of them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HubType {
+    pub name: String,
+    pub max_members: usize,
+    pub min_angle: Real,
+    pub max_angle: Real,
+}
+
+impl HubType {
+    pub fn realizes(&self, members: usize, min_angle: Real, max_angle: Real) -> bool {
+        members <= self.max_members && min_angle >= self.min_angle && max_angle <= self.max_angle
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Catalog {
+    pub hubs: Vec<HubType>,
+}
+
+// A joint no hub in the catalog can realize, with what it would take.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Unbuildable {
+    pub joint: usize,
+    pub members: usize,
+    pub min_angle: Real,
+    pub max_angle: Real,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::IntervalRole;
+    use crate::fixture::joints;
+
+    use super::*;
+
+    #[test]
+    fn flags_joints_no_hub_takes_by_members_or_angle_and_skips_loose_ones() {
+        let mut fabric = joints(4);
+        fabric
+            .add_interval_safe(0, 1, IntervalRole::Pull, 1.0, 1.0, 0.0)
+            .unwrap();
+        fabric
+            .add_interval_safe(1, 2, IntervalRole::Pull, 1.0, 1.0, 0.0)
+            .unwrap();
+        let hub = |max_members, max_angle| HubType {
+            name: String::new(),
+            max_members,
+            min_angle: 0.0,
+            max_angle,
+        };
+        let flagged = |hubs: Vec<HubType>| -> Vec<usize> {
+            fabric
+                .check_buildability(&Catalog { hubs })
+                .iter()
+                .map(|unbuildable| unbuildable.joint)
+                .collect()
+        };
+        assert_eq!(flagged(Vec::new()), [0, 1, 2]);
+        assert_eq!(flagged(vec![hub(1, 4.0)]), [1]);
+        assert_eq!(flagged(vec![hub(1, 4.0), hub(2, 3.0)]), [1]);
+        assert!(flagged(vec![hub(2, 4.0)]).is_empty());
+        let straight = fabric.check_buildability(&Catalog::default())[1];
+        assert_eq!(straight.members, 2);
+        assert!(
+            (straight.max_angle - crate::real::consts::PI).abs() < 1e-3,
+            "{}",
+            straight.max_angle
+        );
+    }
+}
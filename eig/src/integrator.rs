@@ -0,0 +1,208 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use nalgebra::*;
+
+use crate::constants::Integration;
+use crate::joint::Joint;
+use crate::real::Real;
+
+// What the force does to a joint over one time step: the kick is the acceleration taken into the
+// velocity, and the lead is added to the kicked velocity to move the location. Gravity, drag and
+// contact are left to the joint, on top.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Step {
+    pub kick: Vector3<Real>,
+    pub lead: Vector3<Real>,
+}
+
+// The forces on the joints, were they at the locations given.
+pub type Probe<'a> = dyn FnMut(&[Point3<Real>]) -> Vec<Vector3<Real>> + 'a;
+
+// Turns the forces of a tick into a step for every joint. The forces at the start of the step are on
+// the joints already, and for other locations the probe returns them, anything but the intervals
+// held as it was at the start.
+pub trait Integrator {
+    fn steps(&self, joints: &[Joint], time_step: Real, probe: &mut Probe<'_>) -> Vec<Step>;
+}
+
+pub fn of(integration: Integration) -> &'static dyn Integrator {
+    match integration {
+        Integration::SemiImplicitEuler => &SemiImplicitEuler,
+        Integration::VelocityVerlet => &VelocityVerlet,
+        Integration::RungeKutta4 => &RungeKutta4,
+    }
+}
+
+fn acceleration(force: &Vector3<Real>, joint: &Joint) -> Vector3<Real> {
+    if joint.interval_mass == 0.0 {
+        zero()
+    } else {
+        force / joint.interval_mass
+    }
+}
+
+// Velocity first, then the location with the new velocity. One force evaluation.
+pub struct SemiImplicitEuler;
+
+impl Integrator for SemiImplicitEuler {
+    fn steps(&self, joints: &[Joint], _time_step: Real, _probe: &mut Probe<'_>) -> Vec<Step> {
+        joints
+            .iter()
+            .map(|joint| Step {
+                kick: acceleration(&joint.force, joint),
+                lead: zero(),
+            })
+            .collect()
+    }
+}
+
+// Half the previous acceleration and half the new one into the velocity, and the location led by half
+// the new one. Still one force evaluation, using the acceleration each joint kept from the last tick.
+pub struct VelocityVerlet;
+
+impl Integrator for VelocityVerlet {
+    fn steps(&self, joints: &[Joint], time_step: Real, _probe: &mut Probe<'_>) -> Vec<Step> {
+        joints
+            .iter()
+            .map(|joint| {
+                let now = acceleration(&joint.force, joint);
+                Step {
+                    kick: (joint.acceleration + now) / 2.0,
+                    lead: now * (time_step / 2.0),
+                }
+            })
+            .collect()
+    }
+}
+
+// The classic four evaluations, at the start, twice halfway and at the end of the step.
+pub struct RungeKutta4;
+
+impl Integrator for RungeKutta4 {
+    fn steps(&self, joints: &[Joint], time_step: Real, probe: &mut Probe<'_>) -> Vec<Step> {
+        let starts: Vec<Point3<Real>> = joints.iter().map(|joint| joint.location).collect();
+        let at_start = probe(&starts);
+        // the start acceleration plus how the probed force changed from the start
+        let mut evaluate = |velocities: &[Vector3<Real>], fraction: Real| -> Vec<Vector3<Real>> {
+            let locations: Vec<Point3<Real>> = joints
+                .iter()
+                .zip(velocities.iter())
+                .map(|(joint, velocity)| joint.location + velocity * (time_step * fraction))
+                .collect();
+            let probed = probe(&locations);
+            joints
+                .iter()
+                .enumerate()
+                .map(|(index, joint)| {
+                    acceleration(&(joint.force + probed[index] - at_start[index]), joint)
+                })
+                .collect()
+        };
+        let velocities: Vec<Vector3<Real>> = joints.iter().map(|joint| joint.velocity).collect();
+        let a1: Vec<Vector3<Real>> = joints
+            .iter()
+            .map(|joint| acceleration(&joint.force, joint))
+            .collect();
+        let ahead = |accelerations: &[Vector3<Real>], fraction: Real| -> Vec<Vector3<Real>> {
+            velocities
+                .iter()
+                .zip(accelerations.iter())
+                .map(|(velocity, acceleration)| velocity + acceleration * (time_step * fraction))
+                .collect()
+        };
+        let a2 = evaluate(&velocities, 0.5);
+        let a3 = evaluate(&ahead(&a1, 0.5), 0.5);
+        let a4 = evaluate(&ahead(&a2, 0.5), 1.0);
+        (0..joints.len())
+            .map(|index| Step {
+                kick: (a1[index] + (a2[index] + a3[index]) * 2.0 + a4[index]) / 6.0,
+                // the mean of the four velocities, less the kicked one
+                lead: -(a2[index] + a3[index] + a4[index]) * (time_step / 6.0),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STIFFNESS: Real = 1.0;
+
+    fn weight(x: Real) -> Joint {
+        let mut joint = Joint::new(x, 0.0, 0.0);
+        joint.interval_mass = 1.0;
+        joint
+    }
+
+    fn spring(locations: &[Point3<Real>]) -> Vec<Vector3<Real>> {
+        locations
+            .iter()
+            .map(|location| -location.coords * STIFFNESS)
+            .collect()
+    }
+
+    // A weight on a spring from the origin, released at one, moved as the joint would move it.
+    fn oscillate(integration: Integration, time_step: Real, ticks: usize) -> Joint {
+        let mut joints = vec![weight(1.0)];
+        joints[0].force = spring(&[joints[0].location])[0];
+        joints[0].acceleration = joints[0].force;
+        for _ in 0..ticks {
+            let steps = of(integration).steps(&joints, time_step, &mut spring);
+            let joint = &mut joints[0];
+            joint.acceleration = acceleration(&joint.force, joint);
+            joint.velocity += steps[0].kick * time_step;
+            joint.location += (joint.velocity + steps[0].lead) * time_step;
+            joint.force = spring(&[joint.location])[0];
+        }
+        joints.remove(0)
+    }
+
+    #[test]
+    fn all_kick_by_the_force_when_it_is_constant() {
+        let mut joint = weight(0.0);
+        joint.force = Vector3::new(0.0, -2.0, 0.0);
+        joint.acceleration = joint.force;
+        let mut constant =
+            |locations: &[Point3<Real>]| vec![Vector3::new(0.0, -2.0, 0.0); locations.len()];
+        for integration in [
+            Integration::SemiImplicitEuler,
+            Integration::VelocityVerlet,
+            Integration::RungeKutta4,
+        ] {
+            let steps = of(integration).steps(&[joint], 0.1, &mut constant);
+            assert!(
+                (steps[0].kick - joint.force).magnitude() < 1e-6,
+                "{:?} {:?}",
+                integration,
+                steps[0].kick
+            );
+        }
+    }
+
+    #[test]
+    fn runge_kutta_follows_an_oscillator_closest() {
+        let time_step = 0.1;
+        let ticks = 100;
+        let exact = (time_step * ticks as Real).cos();
+        let error =
+            |integration| (oscillate(integration, time_step, ticks).location.x - exact).abs();
+        let euler = error(Integration::SemiImplicitEuler);
+        let runge_kutta = error(Integration::RungeKutta4);
+        assert!(runge_kutta < 0.01, "{}", runge_kutta);
+        assert!(runge_kutta < euler, "{} {}", runge_kutta, euler);
+    }
+
+    // The velocity is half a step off the location, so the energy wobbles, but it doesn't drift.
+    #[test]
+    fn verlet_keeps_the_oscillator_energy() {
+        let joint = oscillate(Integration::VelocityVerlet, 0.1, 10000);
+        let energy = (joint.velocity.magnitude_squared()
+            + STIFFNESS * joint.location.coords.magnitude_squared())
+            / 2.0;
+        assert!((energy - 0.5).abs() < 0.1, "{}", energy);
+    }
+}
@@ -3,31 +3,230 @@
  * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
  */
 
-extern crate fast_inv_sqrt;
-
-use fast_inv_sqrt::InvSqrt32;
 use nalgebra::*;
 
 use crate::constants::*;
+use crate::error::Error;
 use crate::joint::Joint;
+use crate::real::{inv_sqrt, single, Real};
+use crate::role::{RoleDefaults, PULL_LINEAR_DENSITY, PUSH_LINEAR_DENSITY};
 use crate::view::View;
 use crate::world::World;
 
+// Where one end of the interval really grabs: shifted along the strut toward its far hub joint and
+// sideways toward the interval's other end.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attachment {
+    pub(crate) hub_index: usize,
+    pub(crate) axial: Real,
+    pub(crate) radial: Real,
+}
+
+const DISABLED_DIM: Real = 0.25;
+pub const MAX_CURVE_POINTS: usize = 8;
+
+// What one interval does to the joints in a tick: forces at its ends and at any hubs its ends
+// are attached along, and half its mass on each end.
+pub struct Contribution {
+    forces: [(usize, Vector3<Real>); 4],
+    ends: [usize; 2],
+    half_mass: Real,
+}
+
+impl Contribution {
+    pub fn apply(&self, joints: &mut [Joint]) {
+        for &(joint_index, force) in self.forces.iter() {
+            joints[joint_index].force += force;
+        }
+        for &joint_index in self.ends.iter() {
+            joints[joint_index].interval_mass += self.half_mass;
+        }
+    }
+}
+
+// An imperfect winch between the commands and the rest length: travel within the dead band is ignored,
+// and the motor has to take up the play in the backlash before the length follows a reversal.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Actuator {
+    pub(crate) dead_band: Real,
+    pub(crate) backlash: Real,
+    // where the motor is relative to the output, within half the backlash either way
+    pub(crate) play: Real,
+}
+
+impl Actuator {
+    pub fn new(dead_band: Real, backlash: Real) -> Actuator {
+        Actuator {
+            dead_band,
+            backlash,
+            play: 0.0,
+        }
+    }
+
+    // The rest length really reached when commanded from one to the other, if it moves at all.
+    pub fn command(&mut self, from: Real, to: Real) -> Option<Real> {
+        let travel = to - from;
+        if travel.abs() <= self.dead_band {
+            return None;
+        }
+        let half = self.backlash / 2.0;
+        let motor = self.play + travel;
+        self.play = motor.clamp(-half, half);
+        let moved = motor - self.play;
+        if moved == 0.0 {
+            return None;
+        }
+        Some(from + moved)
+    }
+}
+
+// A material's stress against strain, standing in for strain times stiffness. Fixed size so intervals
+// stay Copy.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Curve {
+    // strain and stress points with the strains rising, carried on straight past either end
+    Piecewise {
+        points: [(Real, Real); MAX_CURVE_POINTS],
+        count: usize,
+    },
+    // coefficients of strain to the first through fourth power, so no stress without strain
+    Polynomial([Real; 4]),
+}
+
+impl Curve {
+    pub fn piecewise(strains: &[Real], stresses: &[Real]) -> Result<Curve, Error> {
+        if strains.len() != stresses.len() {
+            return Err(Error::WrongLength {
+                expected: strains.len(),
+                actual: stresses.len(),
+            });
+        }
+        let count = strains.len();
+        if !(2..=MAX_CURVE_POINTS).contains(&count) {
+            return Err(Error::WrongLength {
+                expected: count.clamp(2, MAX_CURVE_POINTS),
+                actual: count,
+            });
+        }
+        let mut points = [(0.0, 0.0); MAX_CURVE_POINTS];
+        for (point, (&strain, &stress)) in strains.iter().zip(stresses.iter()).enumerate() {
+            let rising = point == 0 || strain > strains[point - 1];
+            if !(strain.is_finite() && stress.is_finite() && rising) {
+                return Err(Error::IllegalCurve { point });
+            }
+            points[point] = (strain, stress);
+        }
+        Ok(Curve::Piecewise { points, count })
+    }
+
+    pub fn polynomial(coefficients: &[Real]) -> Result<Curve, Error> {
+        if coefficients.is_empty() || coefficients.len() > 4 {
+            return Err(Error::WrongLength {
+                expected: 4,
+                actual: coefficients.len(),
+            });
+        }
+        let mut terms = [0.0; 4];
+        for (point, &coefficient) in coefficients.iter().enumerate() {
+            if !coefficient.is_finite() {
+                return Err(Error::IllegalCurve { point });
+            }
+            terms[point] = coefficient;
+        }
+        Ok(Curve::Polynomial(terms))
+    }
+
+    pub fn stress(&self, strain: Real) -> Real {
+        match self {
+            Curve::Piecewise { points, count } => {
+                let points = &points[..*count];
+                let segment = points[1..count - 1]
+                    .iter()
+                    .position(|&(point_strain, _)| strain < point_strain)
+                    .unwrap_or(count - 2);
+                let ((strain_0, stress_0), (strain_1, stress_1)) =
+                    (points[segment], points[segment + 1]);
+                stress_0 + (stress_1 - stress_0) * (strain - strain_0) / (strain_1 - strain_0)
+            }
+            Curve::Polynomial(terms) => terms
+                .iter()
+                .rev()
+                .fold(0.0, |sum, term| (sum + term) * strain),
+        }
+    }
+
+    // The stress integrated over the strain from none to this, per unit of ideal length. Straight
+    // between the inner points, so the trapezoids are exact.
+    pub fn energy(&self, strain: Real) -> Real {
+        match self {
+            Curve::Piecewise { points, count } => {
+                let (low, high) = (strain.min(0.0), strain.max(0.0));
+                let mut strains = vec![low];
+                strains.extend(
+                    points[1..count - 1]
+                        .iter()
+                        .map(|&(point, _)| point)
+                        .filter(|&point| low < point && point < high),
+                );
+                strains.push(high);
+                let area: Real = strains
+                    .windows(2)
+                    .map(|pair| {
+                        (pair[1] - pair[0]) * (self.stress(pair[0]) + self.stress(pair[1])) / 2.0
+                    })
+                    .sum();
+                if strain < 0.0 {
+                    -area
+                } else {
+                    area
+                }
+            }
+            Curve::Polynomial(terms) => terms
+                .iter()
+                .enumerate()
+                .map(|(power, term)| term * strain.powi(power as i32 + 2) / (power as Real + 2.0))
+                .sum(),
+        }
+    }
+}
+
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Interval {
     pub(crate) alpha_index: usize,
     pub(crate) omega_index: usize,
     pub(crate) push: bool,
-    pub(crate) length_0: f32,
-    pub(crate) length_1: f32,
-    pub(crate) length_nuance: f32,
-    pub(crate) attack: f32,
-    pub(crate) decay: f32,
-    pub(crate) stiffness: f32,
-    pub(crate) linear_density: f32,
-    pub(crate) unit: Vector3<f32>,
-    pub(crate) strain: f32,
-    pub(crate) strain_nuance: f32,
+    pub(crate) length_0: Real,
+    pub(crate) length_1: Real,
+    pub(crate) length_nuance: Real,
+    pub(crate) attack: Real,
+    pub(crate) decay: Real,
+    pub(crate) stiffness: Real,
+    pub(crate) linear_density: Real,
+    pub(crate) unit: Vector3<Real>,
+    pub(crate) strain: Real,
+    pub(crate) strain_nuance: Real,
+    pub(crate) tag: u16,
+    pub(crate) alarm: AlarmLevel,
+    pub(crate) attachments: [Option<Attachment>; 2],
+    // a hard stop: no force above this length, as stiff as a push once compressed below it
+    pub(crate) gap: Option<Real>,
+    // mechanical work done so far changing the ideal length under load
+    pub(crate) work: Real,
+    pub(crate) actuator: Option<Actuator>,
+    // ticks to hold before the transition starts
+    pub(crate) delay: u32,
+    // still there and drawn, dimmed, but pulling and pushing on nothing
+    pub(crate) disabled: bool,
+    pub(crate) curve: Option<Curve>,
+    // multiplies the role damping, resisting the change of strain per tick
+    pub(crate) damping: Real,
+    pub(crate) strain_rate: Real,
+    // how much longer than built the rest length has become from yielding, as a fraction
+    pub(crate) plastic_strain: Real,
 }
 
 impl Interval {
@@ -35,10 +234,10 @@ impl Interval {
         alpha_index: usize,
         omega_index: usize,
         push: bool,
-        length_0: f32,
-        length_1: f32,
-        stiffness: f32,
-        attack: f32,
+        length_0: Real,
+        length_1: Real,
+        stiffness: Real,
+        attack: Real,
     ) -> Interval {
         Interval {
             alpha_index,
@@ -46,14 +245,30 @@ impl Interval {
             push,
             length_0,
             length_1,
-            length_nuance: 0_f32,
+            length_nuance: 0.0,
             attack,
-            decay: 0_f32,
+            decay: 0.0,
             stiffness: stiffness,
-            linear_density: if push { 1_f32 } else { 0.05_f32 },
+            linear_density: if push {
+                PUSH_LINEAR_DENSITY
+            } else {
+                PULL_LINEAR_DENSITY
+            },
             unit: zero(),
-            strain: 0_f32,
-            strain_nuance: 0_f32,
+            strain: 0.0,
+            strain_nuance: 0.0,
+            tag: 0,
+            alarm: AlarmLevel::Clear,
+            attachments: [None, None],
+            gap: None,
+            work: 0.0,
+            actuator: None,
+            delay: 0,
+            disabled: false,
+            curve: None,
+            damping: 1.0,
+            strain_rate: 0.0,
+            plastic_strain: 0.0,
         }
     }
 
@@ -64,6 +279,30 @@ impl Interval {
         if self.omega_index > index {
             self.omega_index = self.omega_index - 1;
         }
+        for end in self.attachments.iter_mut() {
+            match end {
+                Some(attachment) if attachment.hub_index == index => *end = None,
+                Some(attachment) if attachment.hub_index > index => attachment.hub_index -= 1,
+                _ => {}
+            }
+        }
+    }
+
+    pub fn role(&self) -> IntervalRole {
+        if self.push {
+            IntervalRole::Push
+        } else {
+            IntervalRole::Pull
+        }
+    }
+
+    pub fn touches(&self, joint_index: usize) -> bool {
+        self.alpha_index == joint_index || self.omega_index == joint_index
+    }
+
+    pub fn connects(&self, alpha_index: usize, omega_index: usize) -> bool {
+        (self.alpha_index == alpha_index && self.omega_index == omega_index)
+            || (self.alpha_index == omega_index && self.omega_index == alpha_index)
     }
 
     pub fn alpha<'a>(&self, joints: &'a Vec<Joint>) -> &'a Joint {
@@ -74,29 +313,85 @@ impl Interval {
         &joints[self.omega_index]
     }
 
-    pub fn calculate_current_length_mut(&mut self, joints: &Vec<Joint>) -> f32 {
+    pub fn calculate_current_length_mut(&mut self, joints: &[Joint]) -> Real {
         let alpha_location = &joints[self.alpha_index].location;
         let omega_location = &joints[self.omega_index].location;
         self.unit = omega_location - alpha_location;
         let magnitude_squared = self.unit.magnitude_squared();
-        if magnitude_squared < 0.00001_f32 {
-            return 0.00001_f32;
+        if magnitude_squared < 0.00001 {
+            return 0.00001;
         }
-        let inverse_square_root = magnitude_squared.inv_sqrt32();
+        let inverse_square_root = inv_sqrt(magnitude_squared);
         self.unit *= inverse_square_root;
-        1_f32 / inverse_square_root
+        1.0 / inverse_square_root
+    }
+
+    fn end_indices(&self, end: usize) -> (usize, usize) {
+        if end == 0 {
+            (self.alpha_index, self.omega_index)
+        } else {
+            (self.omega_index, self.alpha_index)
+        }
+    }
+
+    // The attachment point of an end, and how much of the end's force the hub joint carries.
+    fn attachment_point(&self, joints: &[Joint], end: usize) -> (Point3<Real>, Real) {
+        let (joint_index, other_index) = self.end_indices(end);
+        let location = joints[joint_index].location;
+        let Some(attachment) = self.attachments[end] else {
+            return (location, 0.0);
+        };
+        let strut = joints[attachment.hub_index].location - location;
+        let strut_length = strut.magnitude();
+        if strut_length < 0.00001 {
+            return (location, 0.0);
+        }
+        let axis = strut / strut_length;
+        let toward = joints[other_index].location - location;
+        let sideways = toward - axis * toward.dot(&axis);
+        let radial = sideways.try_normalize(0.00001).unwrap_or_else(zero);
+        let point = location + axis * attachment.axial + radial * attachment.radial;
+        (point, (attachment.axial / strut_length).clamp(0.0, 1.0))
     }
 
-    pub fn calculate_current_length(&self, joints: &Vec<Joint>) -> f32 {
+    fn calculate_attached_length_mut(&mut self, joints: &[Joint]) -> Real {
+        let (alpha_point, _) = self.attachment_point(joints, 0);
+        let (omega_point, _) = self.attachment_point(joints, 1);
+        self.unit = omega_point - alpha_point;
+        let length = self.unit.magnitude();
+        if length < 0.00001 {
+            return 0.00001;
+        }
+        self.unit /= length;
+        length
+    }
+
+    // A force at a point along the strut is shared between its two joints by the lever rule.
+    fn end_forces(
+        &self,
+        joints: &[Joint],
+        end: usize,
+        force: Vector3<Real>,
+    ) -> [(usize, Vector3<Real>); 2] {
+        let (joint_index, _) = self.end_indices(end);
+        let (_, lever) = self.attachment_point(joints, end);
+        let hub = match self.attachments[end] {
+            Some(attachment) => (attachment.hub_index, force * lever),
+            None => (joint_index, zero()),
+        };
+        [(joint_index, force * (1.0 - lever)), hub]
+    }
+
+    pub fn calculate_current_length(&self, joints: &Vec<Joint>) -> Real {
         let alpha_location = &joints[self.alpha_index].location;
         let omega_location = &joints[self.omega_index].location;
         let unit = omega_location - alpha_location;
         let magnitude_squared = unit.magnitude_squared();
-        if magnitude_squared < 0.00001_f32 {
-            return 0.00001_f32;
+        if magnitude_squared < 0.00001 {
+            return 0.00001;
         }
-        let inverse_square_root = magnitude_squared.inv_sqrt32();
-        1_f32 / inverse_square_root
+        let inverse_square_root = inv_sqrt(magnitude_squared);
+        1.0 / inverse_square_root
     }
 
     pub fn physics(
@@ -104,154 +399,537 @@ impl Interval {
         world: &World,
         joints: &mut Vec<Joint>,
         stage: Stage,
-        pretensing_nuance: f32,
+        pretensing_nuance: Real,
     ) {
+        self.contribution(world, joints, stage, pretensing_nuance)
+            .apply(joints);
+    }
+
+    // Updates the interval's own unit and strain, but only reads the joints, so intervals can do this
+    // side by side and have their contributions added afterwards.
+    pub fn contribution(
+        &mut self,
+        world: &World,
+        joints: &[Joint],
+        stage: Stage,
+        pretensing_nuance: Real,
+    ) -> Contribution {
         let ideal_length = self.ideal_length_now(world, stage, pretensing_nuance);
-        let real_length = self.calculate_current_length_mut(joints);
-        self.strain = (real_length - ideal_length) / ideal_length;
-        if !world.push_and_pull
-            && (self.push && self.strain > 0_f32 || !self.push && self.strain < 0_f32)
+        let eccentric = self.attachments.iter().any(Option::is_some);
+        let real_length = if eccentric {
+            self.calculate_attached_length_mut(joints)
+        } else {
+            self.calculate_current_length_mut(joints)
+        };
+        let previous_strain = self.strain;
+        self.strain = match self.gap {
+            Some(gap) => ((real_length - gap) / gap).min(0.0),
+            None => (real_length - ideal_length) / ideal_length,
+        };
+        if self.gap.is_none()
+            && !world.push_and_pull
+            && (self.push && self.strain > 0.0 || !self.push && self.strain < 0.0)
         {
-            self.strain = 0_f32;
+            self.strain = 0.0;
+        }
+        self.strain_rate = self.strain - previous_strain;
+        let force = self.axial_force(world, stage);
+        let force_vector: Vector3<Real> = self.unit.clone() * force / 2.0;
+        let [alpha, alpha_hub] = self.end_forces(joints, 0, force_vector);
+        let [omega, omega_hub] = self.end_forces(joints, 1, -force_vector);
+        Contribution {
+            forces: [alpha, alpha_hub, omega, omega_hub],
+            ends: [self.alpha_index, self.omega_index],
+            half_mass: ideal_length * self.linear_density / 2.0,
+        }
+    }
+
+    // Both ends of any transition move along, so the ideal length carries the deformation whatever the
+    // nuance.
+    pub fn yield_to(&mut self, yield_strain: Real, flow: Real) {
+        let excess = self.strain.abs() - yield_strain;
+        if excess <= 0.0 || self.disabled {
+            return;
+        }
+        let factor = 1.0 + flow * excess * self.strain.signum();
+        self.length_0 *= factor;
+        self.length_1 *= factor;
+        self.plastic_strain = (1.0 + self.plastic_strain) * factor - 1.0;
+    }
+
+    // Positive in tension, negative in compression.
+    pub fn axial_force(&self, world: &World, stage: Stage) -> Real {
+        if self.disabled {
+            return 0.0;
+        }
+        let role = if self.gap.is_some() {
+            IntervalRole::Push
+        } else {
+            self.role()
+        };
+        let stress = match self.curve {
+            Some(curve) => curve.stress(self.strain),
+            None => self.strain * self.stiffness,
+        };
+        let defaults = RoleDefaults::of(world, role, stage);
+        stress * defaults.stiffness + self.strain_rate * self.damping_factor(world, &defaults)
+    }
+
+    // What the strain rate is multiplied by for the damping force, worked out here for the lanes too so
+    // that both round the same way. The strain rate is per tick, so it goes over the time step for the
+    // same damping whatever the step.
+    pub fn damping_factor(&self, world: &World, defaults: &RoleDefaults) -> Real {
+        self.damping * defaults.damping / world.time_step
+    }
+
+    // Elastic energy stored, the stress integrated over the strain and nothing for the damping.
+    pub fn strain_energy(&self, world: &World, stage: Stage, ideal_length: Real) -> Real {
+        if self.disabled {
+            return 0.0;
         }
-        let push_over_pull = if self.push {
-            world.push_over_pull
+        let role = if self.gap.is_some() {
+            IntervalRole::Push
         } else {
-            1_f32
+            self.role()
         };
-        let stiffness_factor = match stage {
-            Stage::Slack => 0_f32,
-            Stage::Growing | Stage::Shaping => world.shaping_stiffness_factor,
-            Stage::Pretensing | Stage::Pretenst => world.stiffness_factor,
+        let density = match self.curve {
+            Some(curve) => curve.energy(self.strain),
+            None => self.stiffness * self.strain * self.strain / 2.0,
         };
-        let force = self.strain * self.stiffness * push_over_pull * stiffness_factor;
-        let force_vector: Vector3<f32> = self.unit.clone() * force / 2_f32;
-        joints[self.alpha_index].force += &force_vector;
-        joints[self.omega_index].force -= &force_vector;
-        let half_mass = ideal_length * self.linear_density / 2_f32;
-        joints[self.alpha_index].interval_mass += half_mass;
-        joints[self.omega_index].interval_mass += half_mass;
-        if self.attack > 0_f32 {
-            self.length_nuance += self.attack;
-            if self.length_nuance > 1_f32 {
-                self.attack = 0_f32; // done attacking
-                if self.decay == 0_f32 {
+        density * RoleDefaults::of(world, role, stage).stiffness * ideal_length
+    }
+
+    // Attack and decay are per unit of time, one tick's worth at a time step of one, and the delay is
+    // in ticks. A slew rate above zero caps the change of ideal length per unit of time, as a fraction
+    // of the length.
+    pub fn advance_transition(&mut self, slew_rate: Real, time_step: Real) {
+        let span = (self.length_1 - self.length_0).abs();
+        let limit = |rate: Real| {
+            if slew_rate > 0.0 && span > 0.0 {
+                rate.min(slew_rate * self.length_0 / span) * time_step
+            } else {
+                rate * time_step
+            }
+        };
+        if self.delay > 0 {
+            self.delay -= 1;
+        } else if self.attack > 0.0 {
+            self.length_nuance += limit(self.attack);
+            if self.length_nuance > 1.0 {
+                self.attack = 0.0; // done attacking
+                if self.decay == 0.0 {
                     self.length_0 = self.length_1; // both the same now
-                    self.length_nuance = 0_f32; // reset to zero
+                    self.length_nuance = 0.0; // reset to zero
                 } else {
-                    self.length_nuance = 1_f32 - self.decay; // first step back
+                    self.length_nuance = 1.0 - self.decay; // first step back
                 }
             }
-        } else if self.decay > 0_f32 {
-            self.length_nuance -= self.decay;
-            if self.length_nuance <= 0_f32 {
-                self.length_nuance = 0_f32; // exactly zero
-                self.decay = 0_f32; // done decaying
+        } else if self.decay > 0.0 {
+            self.length_nuance -= limit(self.decay);
+            if self.length_nuance <= 0.0 {
+                self.length_nuance = 0.0; // exactly zero
+                self.decay = 0.0; // done decaying
             }
         }
     }
 
-    pub fn calculate_strain_nuance(&self, limits: &[f32; 4]) -> f32 {
+    // Shortening in tension or lengthening in compression takes work from the actuator. Work the load
+    // does back is counted against it, but a winch or jack can't store it, so only spending is energy.
+    pub fn record_work(&mut self, force: Real, length_change: Real) -> Real {
+        let work = -force * length_change;
+        self.work += work;
+        work.max(0.0)
+    }
+
+    // The level rises as soon as a band is reached but only falls once the strain is hysteresis below it.
+    // Returns the new level when it changed.
+    pub fn update_alarm(&mut self, bands: [Real; 2], hysteresis: Real) -> Option<AlarmLevel> {
+        let strain = self.strain.abs();
+        let reached = |band: Real, margin: Real| band > 0.0 && strain >= band - margin;
+        let level = match self.alarm {
+            AlarmLevel::Critical if reached(bands[1], hysteresis) => AlarmLevel::Critical,
+            _ if reached(bands[1], 0.0) => AlarmLevel::Critical,
+            AlarmLevel::Clear if !reached(bands[0], 0.0) => AlarmLevel::Clear,
+            _ if reached(bands[0], hysteresis) => AlarmLevel::Warning,
+            _ => AlarmLevel::Clear,
+        };
+        if level == self.alarm {
+            return None;
+        }
+        self.alarm = level;
+        Some(level)
+    }
+
+    pub fn calculate_strain_nuance(&self, limits: &[Real; 4]) -> Real {
         let unsafe_nuance = if self.push {
             (self.strain - limits[1]) / (limits[0] - limits[1])
         } else {
             (self.strain - limits[2]) / (limits[3] - limits[2])
         };
-        if unsafe_nuance < 0_f32 {
-            0_f32
-        } else if unsafe_nuance > 1_f32 {
-            1_f32
+        if unsafe_nuance < 0.0 {
+            0.0
+        } else if unsafe_nuance > 1.0 {
+            1.0
         } else {
             unsafe_nuance
         }
     }
 
-    pub fn ideal_length_now(&self, world: &World, stage: Stage, pretensing_nuance: f32) -> f32 {
-        let ideal =
-            self.length_0 * (1_f32 - self.length_nuance) + self.length_1 * self.length_nuance;
+    pub fn ideal_length_now(&self, world: &World, stage: Stage, pretensing_nuance: Real) -> Real {
+        let ideal = self.length_0 * (1.0 - self.length_nuance) + self.length_1 * self.length_nuance;
         if self.push {
             match stage {
                 Stage::Slack => ideal,
                 Stage::Growing | Stage::Shaping => {
-                    let nuance = if self.attack == 0_f32 {
-                        1_f32
+                    let nuance = if self.attack == 0.0 {
+                        1.0
                     } else {
                         self.length_nuance
                     };
-                    ideal * (1_f32 + world.shaping_pretenst_factor * nuance)
+                    ideal * (1.0 + world.shaping_pretenst_factor * nuance)
                 }
-                Stage::Pretensing => ideal * (1_f32 + world.pretenst_factor * pretensing_nuance),
-                Stage::Pretenst => ideal * (1_f32 + world.pretenst_factor),
+                Stage::Pretensing => ideal * (1.0 + world.pretenst_factor * pretensing_nuance),
+                Stage::Pretenst => ideal * (1.0 + world.pretenst_factor),
             }
         } else {
             ideal
         }
     }
 
-    pub fn change_rest_length(&mut self, rest_length: f32, countdown: f32) {
+    pub fn change_rest_length(&mut self, rest_length: Real, countdown: Real) {
         self.length_0 = self.length_1;
         self.length_1 = rest_length;
-        self.length_nuance = 0_f32;
-        self.attack = 1_f32 / countdown;
-        self.decay = 0_f32;
+        self.length_nuance = 0.0;
+        self.attack = 1.0 / countdown;
+        self.decay = 0.0;
+    }
+
+    // A commanded change, through the actuator when there is one.
+    pub fn actuate(&mut self, rest_length: Real, countdown: Real) {
+        let rest_length = match &mut self.actuator {
+            Some(actuator) => match actuator.command(self.length_1, rest_length) {
+                Some(rest_length) => rest_length,
+                None => return,
+            },
+            None => rest_length,
+        };
+        self.change_rest_length(rest_length, countdown)
     }
 
-    pub fn twitch(&mut self, attack_countdown: f32, decay_countdown: f32, delta_size_nuance: f32) {
-        if self.length_nuance != 0_f32 {
+    // A twitch goes out and comes back, so the play never settles: only the dead band counts.
+    pub fn actuate_twitch(
+        &mut self,
+        attack_countdown: Real,
+        decay_countdown: Real,
+        delta_size_nuance: Real,
+    ) {
+        if let Some(actuator) = &self.actuator {
+            if (self.length_0 * (delta_size_nuance - 1.0)).abs() <= actuator.dead_band {
+                return;
+            }
+        }
+        self.twitch(attack_countdown, decay_countdown, delta_size_nuance)
+    }
+
+    pub fn twitch(
+        &mut self,
+        attack_countdown: Real,
+        decay_countdown: Real,
+        delta_size_nuance: Real,
+    ) {
+        if self.length_nuance != 0.0 {
             // while changing? ignore!
             return;
         }
         self.length_1 = self.length_0 * delta_size_nuance;
-        self.length_nuance = 0_f32;
-        self.attack = 1_f32 / attack_countdown;
-        self.decay = 1_f32 / decay_countdown;
+        self.length_nuance = 0.0;
+        self.attack = 1.0 / attack_countdown;
+        self.decay = 1.0 / decay_countdown;
     }
 
-    pub fn multiply_rest_length(&mut self, factor: f32, countdown: f32) {
+    pub fn multiply_rest_length(&mut self, factor: Real, countdown: Real) {
         self.change_rest_length(self.length_1 * factor, countdown)
     }
 
-    pub fn project_line_locations<'a>(&self, view: &mut View, joints: &'a Vec<Joint>, extend: f32) {
+    pub fn project_line_locations<'a>(
+        &self,
+        view: &mut View,
+        joints: &'a Vec<Joint>,
+        extend: Real,
+    ) {
         let alpha = &self.alpha(joints).location;
         let omega = &self.omega(joints).location;
-        view.line_locations.push(alpha.x - self.unit.x * extend);
-        view.line_locations.push(alpha.y - self.unit.y * extend);
-        view.line_locations.push(alpha.z - self.unit.z * extend);
-        view.line_locations.push(omega.x + self.unit.x * extend);
-        view.line_locations.push(omega.y + self.unit.y * extend);
-        view.line_locations.push(omega.z + self.unit.z * extend);
-    }
-
-    pub fn project_line_features<'a>(&self, view: &mut View, ideal_length: f32) {
-        view.unit_vectors.push(self.unit.x);
-        view.unit_vectors.push(self.unit.y);
-        view.unit_vectors.push(self.unit.z);
-        view.ideal_lengths.push(ideal_length);
-        view.strains.push(self.strain);
-        view.strain_nuances.push(self.strain_nuance);
-        view.stiffnesses.push(self.stiffness);
-        view.linear_densities.push(self.linear_density);
-    }
-
-    pub fn project_line_color_nuance(&self, view: &mut View) {
-        let nuance = self.strain_nuance;
-        let anti = 1_f32 - self.strain_nuance;
-        let slack = 0.1_f32;
-        if self.push {
-            Interval::project_line_rgb(view, 0_f32, anti, nuance)
-        } else if self.strain == 0_f32 {
-            Interval::project_line_rgb(view, slack, slack, slack)
+        view.line_locations
+            .push(single(alpha.x - self.unit.x * extend));
+        view.line_locations
+            .push(single(alpha.y - self.unit.y * extend));
+        view.line_locations
+            .push(single(alpha.z - self.unit.z * extend));
+        view.line_locations
+            .push(single(omega.x + self.unit.x * extend));
+        view.line_locations
+            .push(single(omega.y + self.unit.y * extend));
+        view.line_locations
+            .push(single(omega.z + self.unit.z * extend));
+        if view.shadows {
+            view.shadow_locations
+                .push(single(alpha.x - self.unit.x * extend));
+            view.shadow_locations.push(0.0);
+            view.shadow_locations
+                .push(single(alpha.z - self.unit.z * extend));
+            view.shadow_locations
+                .push(single(omega.x + self.unit.x * extend));
+            view.shadow_locations.push(0.0);
+            view.shadow_locations
+                .push(single(omega.z + self.unit.z * extend));
+        }
+    }
+
+    pub fn project_line_features<'a>(
+        &self,
+        view: &mut View,
+        ideal_length: Real,
+        strain_nuance: Real,
+    ) {
+        view.unit_vectors.push(single(self.unit.x));
+        view.unit_vectors.push(single(self.unit.y));
+        view.unit_vectors.push(single(self.unit.z));
+        view.ideal_lengths.push(single(ideal_length));
+        view.strains.push(single(self.strain));
+        view.strain_nuances.push(single(strain_nuance));
+        view.stiffnesses.push(single(self.stiffness));
+        view.linear_densities.push(single(self.linear_density));
+        view.alarms.push(self.alarm as u8);
+    }
+
+    pub fn project_force(&self, view: &mut View, world: &World, stage: Stage) {
+        let force = self.axial_force(world, stage);
+        view.axial_forces.push(single(force));
+        view.force_vectors.push(single(self.unit.x * force));
+        view.force_vectors.push(single(self.unit.y * force));
+        view.force_vectors.push(single(self.unit.z * force));
+    }
+
+    pub fn project_line_color_nuance(&self, view: &mut View, nuance: Real) {
+        let anti = 1.0 - nuance;
+        let slack = 0.1;
+        let [r, g, b] = if self.push {
+            [0.0, anti, nuance]
+        } else if self.strain == 0.0 {
+            [slack, slack, slack]
         } else {
-            Interval::project_line_rgb(view, nuance, anti, 0_f32)
+            [nuance, anti, 0.0]
+        };
+        let dim = if self.disabled { DISABLED_DIM } else { 1.0 };
+        Interval::project_line_rgb(view, r * dim, g * dim, b * dim)
+    }
+
+    pub fn project_line_rgb(view: &mut View, r: Real, g: Real, b: Real) {
+        view.line_colors.push(single(r));
+        view.line_colors.push(single(g));
+        view.line_colors.push(single(b));
+        view.line_colors.push(single(r));
+        view.line_colors.push(single(g));
+        view.line_colors.push(single(b));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fabric::Fabric;
+    use crate::fixture::chain;
+
+    use super::*;
+
+    #[test]
+    fn damping_resists_the_strain_rate_per_unit_of_time() {
+        let mut interval = Interval::new(0, 1, false, 1.0, 1.0, 1.0, 0.0);
+        interval.damping = 1.0;
+        interval.strain_rate = 0.01;
+        let mut world = World::new();
+        world.pull_damping = 1.0;
+        let whole = interval.axial_force(&world, Stage::Pretenst);
+        world.set_time_step(0.5, 0, 0.0).unwrap();
+        let half = interval.axial_force(&world, Stage::Pretenst);
+        assert!((half - 2.0 * whole).abs() < 1e-6);
+        assert!(whole > 0.0);
+    }
+
+    #[test]
+    fn strain_energy_integrates_the_curve_and_leaves_out_damping() {
+        let linear = Curve::polynomial(&[3.0]).unwrap();
+        assert!((linear.energy(0.2) - 3.0 * 0.04 / 2.0).abs() < 1e-6);
+        // stiff to 0.1, then soft: 10 * 0.1^2 / 2 plus 1 * 0.1 plus 0.1 * 0.1^2 / 2
+        let kinked = Curve::piecewise(&[0.0, 0.1, 1.1], &[0.0, 1.0, 1.1]).unwrap();
+        assert!((kinked.energy(0.2) - 0.1505).abs() < 1e-5);
+        assert!((kinked.energy(-0.1) - 0.05).abs() < 1e-6);
+        let world = World::new();
+        let mut interval = Interval::new(0, 1, false, 1.0, 1.0, 1.0, 0.0);
+        interval.strain = 0.1;
+        let still = interval.strain_energy(&world, Stage::Pretenst, 1.0);
+        interval.damping = 1.0;
+        interval.strain_rate = 0.05;
+        assert_eq!(interval.strain_energy(&world, Stage::Pretenst, 1.0), still);
+        interval.curve = Some(Curve::polynomial(&[1.0]).unwrap());
+        assert!((interval.strain_energy(&world, Stage::Pretenst, 1.0) - still).abs() < 1e-6);
+    }
+
+    #[test]
+    fn transitions_take_as_long_in_time_whatever_the_step() {
+        let ticks = |time_step: Real| {
+            let mut interval = Interval::new(0, 1, false, 1.0, 1.0, 1.0, 0.0);
+            interval.actuate(2.0, 4.0);
+            let mut ticks = 0_i32;
+            while interval.length_nuance > 0.0 || interval.attack > 0.0 {
+                interval.advance_transition(0.0, time_step);
+                ticks += 1;
+            }
+            ticks
+        };
+        // the nuance goes just past one either way
+        assert!((ticks(0.5) - 2 * ticks(1.0)).abs() <= 1);
+    }
+
+    #[test]
+    fn alarms_rise_at_the_band_and_fall_only_below_the_hysteresis() {
+        let mut interval = Interval::new(0, 1, false, 1.0, 1.0, 1.0, 0.0);
+        let bands = [0.01, 0.02];
+        let mut at = |strain: Real| {
+            interval.strain = strain;
+            interval.update_alarm(bands, 0.005)
+        };
+        assert_eq!(at(0.009), None);
+        assert_eq!(at(-0.01), Some(AlarmLevel::Warning));
+        assert_eq!(at(0.021), Some(AlarmLevel::Critical));
+        assert_eq!(at(0.016), None);
+        assert_eq!(at(0.014), Some(AlarmLevel::Warning));
+        assert_eq!(at(0.006), None);
+        assert_eq!(at(0.004), Some(AlarmLevel::Clear));
+        assert_eq!(at(0.009), None);
+    }
+
+    #[test]
+    fn an_offset_end_measures_from_along_the_strut_and_shares_its_force_with_the_hub() {
+        let joints = vec![
+            Joint::new(0.0, 0.0, 0.0),
+            Joint::new(3.0, 0.0, 0.0),
+            Joint::new(0.0, 2.0, 0.0),
+        ];
+        let mut interval = Interval::new(0, 1, false, 2.0, 2.0, 1.0, 0.0);
+        interval.attachments[0] = Some(Attachment {
+            hub_index: 2,
+            axial: 1.0,
+            radial: 0.5,
+        });
+        let (point, lever) = interval.attachment_point(&joints, 0);
+        assert!((point - Point3::new(0.5, 1.0, 0.0)).magnitude() < 1e-6);
+        assert_eq!(lever, 0.5);
+        let contribution = interval.contribution(&World::new(), &joints, Stage::Pretenst, 0.0);
+        assert!((interval.strain - (7.25_f64.sqrt() as Real - 2.0) / 2.0).abs() < 1e-5);
+        let [(alpha, at_alpha), (hub, at_hub), _, _] = contribution.forces;
+        assert_eq!((alpha, hub), (0, 2));
+        assert!((at_alpha - at_hub).magnitude() < 1e-6 && at_alpha.magnitude() > 0.0);
+        interval.joint_removed(2);
+        assert!(interval.attachments[0].is_none());
+    }
+
+    #[test]
+    fn a_slew_rate_caps_the_change_per_tick_as_a_fraction_of_the_length() {
+        let mut interval = Interval::new(0, 1, false, 1.0, 1.0, 1.0, 0.0);
+        interval.change_rest_length(2.0, 1.0);
+        interval.advance_transition(0.1, 1.0);
+        assert!((interval.length_nuance - 0.1).abs() < 1e-6);
+        interval.advance_transition(0.0, 1.0);
+        assert_eq!((interval.length_0, interval.length_nuance), (2.0, 0.0));
+    }
+
+    #[test]
+    fn a_gap_pushes_back_only_once_closed_past_its_length() {
+        let world = World::new();
+        let mut interval = Interval::new(0, 1, false, 1.0, 1.0, 1.0, 0.0);
+        interval.gap = Some(1.5);
+        let open = vec![Joint::new(0.0, 0.0, 0.0), Joint::new(2.0, 0.0, 0.0)];
+        interval.contribution(&world, &open, Stage::Pretenst, 0.0);
+        assert_eq!(interval.strain, 0.0);
+        assert_eq!(interval.axial_force(&world, Stage::Pretenst), 0.0);
+        let closed = vec![Joint::new(0.0, 0.0, 0.0), Joint::new(1.2, 0.0, 0.0)];
+        interval.contribution(&world, &closed, Stage::Pretenst, 0.0);
+        assert!((interval.strain + 0.2).abs() < 1e-3, "{}", interval.strain);
+        assert!(interval.axial_force(&world, Stage::Pretenst) < 0.0);
+    }
+
+    #[test]
+    fn an_actuator_ignores_the_dead_band_and_takes_up_the_backlash_on_reversal() {
+        let mut actuator = Actuator::new(0.01, 0.1);
+        assert_eq!(actuator.command(1.0, 1.005), None);
+        let out = actuator.command(1.0, 1.2).unwrap();
+        assert!((out - 1.15).abs() < 1e-6, "{}", out);
+        assert_eq!(actuator.command(out, out - 0.05), None);
+        let back = actuator.command(out, 1.0).unwrap();
+        assert!((back - 1.05).abs() < 1e-6, "{}", back);
+    }
+
+    #[test]
+    fn a_contribution_only_reads_the_joints_until_applied() {
+        let mut joints = vec![Joint::new(0.0, 1.0, 0.0), Joint::new(2.0, 1.0, 0.0)];
+        let mut interval = Interval::new(0, 1, false, 1.0, 1.0, 1.0, 0.0);
+        let contribution = interval.contribution(&World::new(), &joints, Stage::Pretenst, 0.0);
+        assert!(interval.strain > 0.0);
+        assert!(joints.iter().all(|joint| joint.force == Vector3::zeros()));
+        let masses: Vec<Real> = joints.iter().map(|joint| joint.interval_mass).collect();
+        contribution.apply(&mut joints);
+        assert!(joints[0].force.x > 0.0, "{}", joints[0].force);
+        assert_eq!(joints[0].force, -joints[1].force);
+        for (joint, mass) in joints.iter().zip(masses) {
+            assert_eq!(joint.interval_mass, mass + contribution.half_mass);
         }
     }
 
-    pub fn project_line_rgb(view: &mut View, r: f32, g: f32, b: f32) {
-        view.line_colors.push(r);
-        view.line_colors.push(g);
-        view.line_colors.push(b);
-        view.line_colors.push(r);
-        view.line_colors.push(g);
-        view.line_colors.push(b);
+    #[test]
+    fn yielding_takes_up_the_excess_strain_for_good_and_accumulates() {
+        let mut interval = Interval::new(0, 1, false, 1.0, 2.0, 1.0, 0.0);
+        interval.strain = 0.05;
+        interval.yield_to(0.1, 0.5);
+        assert_eq!((interval.length_0, interval.plastic_strain), (1.0, 0.0));
+        interval.strain = 0.3;
+        interval.yield_to(0.1, 0.5);
+        assert!((interval.length_0 - 1.1).abs() < 1e-6 && (interval.length_1 - 2.2).abs() < 1e-6);
+        interval.yield_to(0.1, 0.5);
+        assert!(
+            (interval.plastic_strain - 0.21).abs() < 1e-6,
+            "{}",
+            interval.plastic_strain
+        );
+        let mut push = Interval::new(0, 1, true, 1.0, 1.0, 1.0, 0.0);
+        push.strain = -0.3;
+        push.yield_to(0.1, 0.5);
+        assert!(
+            (push.plastic_strain + 0.1).abs() < 1e-6,
+            "{}",
+            push.plastic_strain
+        );
+        push.disabled = true;
+        push.yield_to(0.1, 0.5);
+        assert!((push.length_0 - 0.9).abs() < 1e-6);
+    }
+
+    #[test]
+    fn a_disabled_interval_stays_but_pulls_on_nothing_until_enabled() {
+        let world = World::new();
+        let mut fabric = chain(2, 0.5);
+        fabric.stage = Stage::Pretenst;
+        fabric.set_interval_disabled(0, true).unwrap();
+        assert!(fabric.is_interval_disabled(0).unwrap());
+        assert!(fabric.set_interval_disabled(1, true).is_err());
+        fabric.iterate_ticks(&world, 10);
+        assert_eq!(fabric.intervals.len(), 1);
+        assert_eq!(fabric.intervals[0].axial_force(&world, fabric.stage), 0.0);
+        let span =
+            |fabric: &Fabric| (fabric.joints[1].location - fabric.joints[0].location).magnitude();
+        assert!((span(&fabric) - 1.0).abs() < 1e-6, "{}", span(&fabric));
+        fabric.set_interval_disabled(0, false).unwrap();
+        fabric.iterate_ticks(&world, 1);
+        assert!(fabric.intervals[0].axial_force(&world, fabric.stage) > 0.0);
+        assert!(span(&fabric) < 1.0, "{}", span(&fabric));
     }
 }
@@ -4,93 +4,327 @@
  */
 
 use crate::constants::*;
+use crate::integrator::Step;
+use crate::real::{single, Real};
+use crate::surface::Surface;
 use crate::view::View;
 use crate::world::World;
 use nalgebra::*;
 
-const RESURFACE: f32 = 0.01;
-const STICKY_UP_DRAG: f32 = 0.03;
-const STICKY_DOWN_DRAG: f32 = 0.3;
-const AMBIENT_MASS: f32 = 0.001_f32;
+const RESURFACE: Real = 0.01;
+const STICKY_UP_DRAG: Real = 0.03;
+const STICKY_DOWN_DRAG: Real = 0.3;
+const AMBIENT_MASS: Real = 0.001;
+const CONTACT_ALTITUDE: Real = 0.001;
+
+// Ground friction differing along and across a horizontal axis, as fractions of the velocity taken
+// per unit of time while in contact. On a slope the axis is laid onto the surface.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Friction {
+    pub(crate) axis: Vector3<Real>,
+    pub(crate) along: Real,
+    pub(crate) across: Real,
+}
+
+impl Friction {
+    // The axis is turned by the angle from x toward z.
+    pub fn new(angle: Real, along: Real, across: Real) -> Friction {
+        Friction {
+            axis: Vector3::new(angle.cos(), 0.0, angle.sin()),
+            along,
+            across,
+        }
+    }
+
+    // Only the sliding part, in the tangent plane of the normal, is slowed.
+    fn apply(&self, velocity: &mut Vector3<Real>, normal: &Vector3<Real>, time_step: Real) {
+        let normal_velocity = normal * velocity.dot(normal);
+        let sliding = *velocity - normal_velocity;
+        let axis = self.axis - normal * self.axis.dot(normal);
+        let along = match axis.try_normalize(Real::EPSILON) {
+            Some(axis) => axis * sliding.dot(&axis),
+            None => zero(),
+        };
+        let across = sliding - along;
+        let keep = |fraction: Real| (1.0 - fraction).powf(time_step);
+        *velocity = normal_velocity + along * keep(self.along) + across * keep(self.across);
+    }
+}
 
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Joint {
-    pub(crate) location: Point3<f32>,
-    pub(crate) force: Vector3<f32>,
-    pub(crate) velocity: Vector3<f32>,
-    pub(crate) interval_mass: f32,
+    pub(crate) location: Point3<Real>,
+    pub(crate) force: Vector3<Real>,
+    pub(crate) velocity: Vector3<Real>,
+    pub(crate) interval_mass: Real,
+    pub(crate) still_ticks: u32,
+    pub(crate) payload_mass: Real,
+    pub(crate) friction: Option<Friction>,
+    // bolted down: never moves, whatever the forces or the stage
+    pub(crate) fixed: bool,
+    // from the force alone, kept for the next tick's integration
+    pub(crate) acceleration: Vector3<Real>,
+    // on top of the velocity when moving the location, see integrator::Step
+    pub(crate) lead: Vector3<Real>,
 }
 
 impl Joint {
-    pub fn new(x: f32, y: f32, z: f32) -> Joint {
+    pub fn new(x: Real, y: Real, z: Real) -> Joint {
         Joint {
             location: Point3::new(x, y, z),
             force: zero(),
             velocity: zero(),
             interval_mass: AMBIENT_MASS,
+            still_ticks: 0,
+            payload_mass: 0.0,
+            friction: None,
+            fixed: false,
+            acceleration: zero(),
+            lead: zero(),
         }
     }
 
     pub fn reset(&mut self) {
         self.force = zero();
-        self.interval_mass = AMBIENT_MASS;
+        self.lead = zero();
+        self.interval_mass = AMBIENT_MASS + self.payload_mass;
     }
 
     pub fn is_connected(&self) -> bool {
         self.interval_mass > AMBIENT_MASS
     }
 
-    pub fn velocity_physics(&mut self, world: &World, gravity: f32, drag: f32) {
-        let altitude = self.location.y;
-        if self.interval_mass == 0_f32 {
+    pub fn update_sleep(&mut self, world: &World) {
+        if self.velocity.magnitude() < world.sleep_velocity
+            && self.force.magnitude() < world.sleep_force
+        {
+            self.still_ticks = self.still_ticks.saturating_add(1);
+        } else {
+            self.still_ticks = 0;
+        }
+        if self.is_asleep(world) {
             self.velocity = zero();
-        } else if altitude >= 0_f32 || gravity == 0_f32 {
-            self.velocity.y -= gravity;
-            self.velocity += &self.force / self.interval_mass;
-            self.velocity *= 1_f32 - drag;
+        }
+    }
+
+    pub fn is_asleep(&self, world: &World) -> bool {
+        world.sleep_ticks > 0 && self.still_ticks >= world.sleep_ticks
+    }
+
+    // The impulse into the surface and the surface normal, on the tick the joint goes through it.
+    pub fn contact_impulse(
+        &self,
+        surface: &dyn Surface,
+        time_step: Real,
+    ) -> Option<(Real, Vector3<Real>)> {
+        let altitude = self.location.y - surface.height(self.location.x, self.location.z);
+        let previous = self.location - self.velocity * time_step;
+        let previous_altitude = previous.y - surface.height(previous.x, previous.z);
+        if altitude < 0.0 && previous_altitude >= 0.0 {
+            let normal = surface.normal(self.location.x, self.location.z);
+            Some((self.interval_mass * -self.velocity.dot(&normal), normal))
         } else {
-            let degree_submerged: f32 = if -altitude < 1_f32 { -altitude } else { 0_f32 };
+            None
+        }
+    }
+
+    // Only joints in free flight get the lead of the step, the surface has the last word otherwise.
+    pub fn velocity_physics(
+        &mut self,
+        world: &World,
+        gravity: Real,
+        drag: Real,
+        time_step: Real,
+        step: &Step,
+    ) {
+        let surface = world.surface();
+        let ground = surface.height(self.location.x, self.location.z);
+        let altitude = self.location.y - ground;
+        if self.fixed || self.interval_mass == 0.0 {
+            self.velocity = zero();
+        } else if gravity == 0.0 {
+            // without gravity nothing floats, but a medium still drags
+            let (medium_drag, _) = world.immersion(altitude);
+            self.velocity += step.kick * time_step;
+            self.lead = step.lead;
+            self.velocity *= 1.0 - ((drag + medium_drag) * time_step).min(1.0);
+        } else if altitude >= 0.0 {
+            let (medium_drag, buoyancy) = world.immersion(altitude);
+            self.velocity +=
+                world.gravity_at(&self.location, gravity) * ((1.0 - buoyancy) * time_step);
+            self.velocity += step.kick * time_step;
+            self.velocity *= 1.0 - ((drag + medium_drag) * time_step).min(1.0);
+            self.lead = step.lead;
+        } else {
+            let degree_submerged: Real = if -altitude < 1.0 { -altitude } else { 0.0 };
             let antigravity = world.antigravity * degree_submerged;
-            self.velocity += &self.force / self.interval_mass;
+            let normal = surface.normal(self.location.x, self.location.z);
+            self.velocity += step.kick * time_step;
             match world.surface_character {
                 SurfaceCharacter::Frozen => {
                     self.velocity = zero();
-                    self.location.y = -RESURFACE;
+                    self.location.y = ground - RESURFACE;
                 }
                 SurfaceCharacter::Sticky => {
-                    if self.velocity.y < 0_f32 {
-                        let sticky_drag = 1_f32 - STICKY_DOWN_DRAG;
-                        self.velocity.x *= sticky_drag;
-                        self.velocity.y += antigravity;
-                        self.velocity.z *= sticky_drag;
+                    let normal_speed = self.velocity.dot(&normal);
+                    let sticky_drag = if normal_speed < 0.0 {
+                        1.0 - STICKY_DOWN_DRAG
                     } else {
-                        let sticky_drag = 1_f32 - STICKY_UP_DRAG;
-                        self.velocity.x *= sticky_drag;
-                        self.velocity.y += antigravity;
-                        self.velocity.z *= sticky_drag;
-                    }
+                        1.0 - STICKY_UP_DRAG
+                    };
+                    let sliding = self.velocity - normal * normal_speed;
+                    self.velocity = sliding * sticky_drag.powf(time_step)
+                        + normal * (normal_speed + antigravity * time_step);
                 }
                 SurfaceCharacter::Bouncy => {
-                    let degree_cushioned: f32 = 1_f32 - degree_submerged;
-                    self.velocity *= degree_cushioned;
-                    self.velocity.y += antigravity;
+                    let degree_cushioned: Real = 1.0 - degree_submerged;
+                    self.velocity *= degree_cushioned.powf(time_step);
+                    self.velocity += normal * (antigravity * time_step);
+                }
+            }
+            if let Some(friction) = self.friction {
+                if world.surface_character != SurfaceCharacter::Frozen {
+                    friction.apply(&mut self.velocity, &normal, time_step);
                 }
             }
         }
     }
 
-    pub fn location_physics(&mut self) {
-        self.location += &self.velocity
+    // Pushed back out of every obstacle it went into, in proportion to the depth, and slowed while sliding.
+    pub fn obstacle_physics(&mut self, world: &World, time_step: Real) {
+        if self.fixed || self.interval_mass == 0.0 {
+            return;
+        }
+        for obstacle in world.obstacles.iter() {
+            let Some((depth, normal)) = obstacle.penetration(&self.location) else {
+                continue;
+            };
+            self.velocity +=
+                normal * (world.obstacle_stiffness * depth / self.interval_mass * time_step);
+            let normal_speed = self.velocity.dot(&normal);
+            let sliding = self.velocity - normal * normal_speed;
+            self.velocity -= sliding * (world.obstacle_friction * time_step).min(1.0);
+        }
+    }
+
+    pub fn location_physics(&mut self, speed_limit: Real, time_step: Real) {
+        if speed_limit > 0.0 {
+            let speed_squared = self.velocity.magnitude_squared();
+            if speed_squared > speed_limit * speed_limit {
+                self.velocity *= speed_limit / speed_squared.sqrt();
+            }
+        }
+        self.location += (self.velocity + self.lead) * time_step
     }
 
     pub fn project(&self, view: &mut View) {
         view.midpoint += &self.location.coords * self.interval_mass;
         view.mass += self.interval_mass;
-        view.joint_locations.push(self.location.x);
-        view.joint_locations.push(self.location.y);
-        view.joint_locations.push(self.location.z);
-        view.joint_velocities.push(self.velocity.x);
-        view.joint_velocities.push(self.velocity.y);
-        view.joint_velocities.push(self.velocity.z);
+        view.joint_locations.push(single(self.location.x));
+        view.joint_locations.push(single(self.location.y));
+        view.joint_locations.push(single(self.location.z));
+        view.joint_velocities.push(single(self.velocity.x));
+        view.joint_velocities.push(single(self.velocity.y));
+        view.joint_velocities.push(single(self.velocity.z));
+    }
+
+    pub fn is_in_contact(&self, surface: &dyn Surface) -> bool {
+        self.location.y - surface.height(self.location.x, self.location.z) <= CONTACT_ALTITUDE
+    }
+
+    pub fn project_color(
+        &self,
+        view: &mut View,
+        degree: usize,
+        max_degree: usize,
+        anchored: bool,
+        in_contact: bool,
+    ) {
+        let slack = 0.1;
+        let (rgb, size) = if !self.is_connected() {
+            ([slack, slack, slack], 0.5)
+        } else {
+            match view.joint_coloring {
+                JointColoring::Off => return,
+                JointColoring::Degree => {
+                    let nuance = degree as Real / max_degree as Real;
+                    ([nuance, 1.0 - nuance, 0.0], 0.5 + nuance)
+                }
+                JointColoring::Contact => {
+                    if in_contact {
+                        ([1.0, 0.0, 0.0], 1.5)
+                    } else {
+                        ([0.5, 0.5, 0.5], 1.0)
+                    }
+                }
+                JointColoring::Anchored => {
+                    if anchored {
+                        ([0.0, 0.5, 1.0], 1.5)
+                    } else {
+                        ([0.5, 0.5, 0.5], 1.0)
+                    }
+                }
+            }
+        };
+        view.joint_colors.extend(rgb.map(single));
+        view.joint_sizes.push(single(size));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::fabric::Fabric;
+
+    use super::*;
+
+    #[test]
+    fn friction_slows_only_the_sliding_on_a_slope() {
+        let normal = Vector3::new(1.0, 1.0, 0.0).normalize();
+        let mut velocity = Vector3::new(1.0, 0.0, 0.5);
+        let into = velocity.dot(&normal);
+        Friction::new(0.0, 1.0, 1.0).apply(&mut velocity, &normal, 1.0);
+        assert!((velocity - normal * into).magnitude() < 1e-6);
+    }
+
+    #[test]
+    fn friction_keeps_along_and_across_apart_on_the_flat() {
+        let up = Vector3::new(0.0, 1.0, 0.0);
+        let mut velocity = Vector3::new(1.0, -0.5, 1.0);
+        Friction::new(0.0, 1.0, 0.0).apply(&mut velocity, &up, 1.0);
+        assert!((velocity - Vector3::new(0.0, -0.5, 1.0)).magnitude() < 1e-6);
+    }
+
+    #[test]
+    fn a_medium_drags_without_gravity() {
+        let mut world = World::new();
+        world.add_medium(2.0, 0.5, 1.0).unwrap();
+        let mut joint = Joint::new(0.0, 1.0, 0.0);
+        joint.interval_mass = 1.0;
+        joint.velocity = Vector3::new(1.0, 0.0, 0.0);
+        joint.velocity_physics(&world, 0.0, 0.0, 1.0, &Step::default());
+        assert_eq!(joint.velocity.x, 0.5);
+    }
+
+    #[test]
+    fn joint_friction_holds_the_slide_along_its_axis_only() {
+        let mut fabric = Fabric::new(0);
+        fabric.create_joint(0.0, -0.001, 0.0).unwrap();
+        assert!(matches!(
+            fabric.set_joint_friction(0, 0.0, 1.5, 0.0),
+            Err(Error::IllegalFriction { .. })
+        ));
+        fabric.set_joint_friction(0, 0.0, 1.0, 0.0).unwrap();
+        fabric.joints[0].velocity = Vector3::new(0.01, 0.0, 0.01);
+        fabric.stage = Stage::Pretenst;
+        fabric.iterate_ticks(&World::new(), 1);
+        let velocity = fabric.joints[0].velocity;
+        assert!(
+            velocity.x.abs() < 1e-6 && velocity.z > 0.001,
+            "{:?}",
+            velocity
+        );
     }
 }
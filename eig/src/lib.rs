@@ -1,10 +1,60 @@
 #![feature(let_else)]
+#![cfg_attr(feature = "simd", feature(portable_simd))]
 
-mod constants;
-mod fabric;
+pub use error::Error;
+
+#[macro_use]
+mod trace;
+
+pub mod analysis;
+mod anchor;
+pub mod api;
+pub mod boundary;
+mod collision;
+pub mod command;
+pub mod constants;
+mod constraint;
+pub mod controller;
+pub mod cutting;
+mod damper;
+pub mod deploy;
+pub mod error;
+pub mod event;
+pub mod export;
+pub mod fabric;
 mod face;
+#[cfg(test)]
+mod fixture;
+pub mod frames;
+pub mod golden;
+pub mod handle;
+pub mod hardware;
+mod integrator;
 mod interval;
 mod joint;
+#[cfg(not(feature = "parallel"))]
+mod lanes;
+mod medium;
+pub mod momentum;
+mod muscle;
+mod obstacle;
+mod oscillator;
+pub mod reader;
+pub mod real;
+pub mod region;
+pub mod report;
+pub mod role;
+pub mod scenario;
+mod sensor;
+pub mod sensors;
+mod sequencer;
+mod skin;
+pub mod snapshot;
+pub mod stats;
+pub mod surface;
+#[cfg(feature = "metrics")]
+pub mod telemetry;
+mod tenscript;
 mod view;
-mod world;
-mod tenscript;
\ No newline at end of file
+mod wind;
+pub mod world;
@@ -0,0 +1,42 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use crate::real::Real;
+
+// A layer resting on the ground (or on the layer below) up to its top altitude.
+// Drag comes on top of the world's, density is relative to the fabric so one floats neutrally.
+#[derive(Clone, Copy, Debug)]
+pub struct Medium {
+    pub(crate) top: Real,
+    pub(crate) drag: Real,
+    pub(crate) density: Real,
+}
+
+fn smoothstep(edge: Real, width: Real, altitude: Real) -> Real {
+    if width <= 0.0 {
+        return if altitude < edge { 0.0 } else { 1.0 };
+    }
+    let t = ((altitude - edge) / width + 0.5).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// Extra drag and buoyancy at an altitude, blended across each interface over the transition width.
+pub fn immersion(media: &[Medium], transition: Real, altitude: Real) -> (Real, Real) {
+    let mut bottom = 0.0;
+    let mut drag = 0.0;
+    let mut buoyancy = 0.0;
+    for medium in media {
+        let entered = if bottom == 0.0 {
+            1.0
+        } else {
+            smoothstep(bottom, transition, altitude)
+        };
+        let weight = entered * (1.0 - smoothstep(medium.top, transition, altitude));
+        drag += medium.drag * weight;
+        buoyancy += medium.density * weight;
+        bottom = medium.top;
+    }
+    (drag, buoyancy)
+}
@@ -0,0 +1,81 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use nalgebra::*;
+
+use crate::joint::Joint;
+use crate::real::Real;
+
+// Without external forces both stay constant, so any drift comes from the integrator or asymmetric forces.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Momentum {
+    pub linear: Vector3<Real>,
+    // about the center of mass
+    pub angular: Vector3<Real>,
+}
+
+impl Momentum {
+    pub fn of(joints: &[Joint]) -> Momentum {
+        let mut mass = 0.0;
+        let mut weighted: Vector3<Real> = zero();
+        let mut linear: Vector3<Real> = zero();
+        for joint in joints {
+            mass += joint.interval_mass;
+            weighted += joint.location.coords * joint.interval_mass;
+            linear += joint.velocity * joint.interval_mass;
+        }
+        if mass == 0.0 {
+            return Momentum {
+                linear,
+                angular: zero(),
+            };
+        }
+        let center = weighted / mass;
+        let angular = joints.iter().fold(zero(), |angular: Vector3<Real>, joint| {
+            angular
+                + (joint.location.coords - center).cross(&(joint.velocity * joint.interval_mass))
+        });
+        Momentum { linear, angular }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::Stage;
+    use crate::fixture::chain;
+    use crate::world::World;
+
+    use super::*;
+
+    #[test]
+    fn a_spinning_pair_has_angular_momentum_about_its_center_and_no_linear() {
+        let mut joints = vec![Joint::new(-1.0, 5.0, 0.0), Joint::new(1.0, 5.0, 0.0)];
+        for (joint, speed) in joints.iter_mut().zip([1.0, -1.0]) {
+            joint.interval_mass = 1.0;
+            joint.velocity = Vector3::new(0.0, 0.0, speed);
+        }
+        let momentum = Momentum::of(&joints);
+        assert_eq!(momentum.linear, Vector3::zeros());
+        assert!((momentum.angular - Vector3::new(0.0, 2.0, 0.0)).magnitude() < 1e-6);
+    }
+
+    #[test]
+    fn gravity_drifts_the_momentum_far_more_than_internal_forces() {
+        let mut world = World::new();
+        world.gravity = 0.0;
+        let mut fabric = chain(2, 0.5);
+        fabric.stage = Stage::Pretenst;
+        fabric.set_momentum_reference();
+        fabric.iterate_ticks(&world, 100);
+        let internal = fabric.get_linear_momentum_drift();
+        world.gravity = World::new().gravity;
+        fabric.iterate_ticks(&world, 100);
+        let external = fabric.get_linear_momentum_drift();
+        assert!(external > 1000.0 * internal, "{} {}", internal, external);
+        fabric.clear_momentum_reference();
+        assert_eq!(fabric.get_linear_momentum_drift(), 0.0);
+    }
+}
@@ -0,0 +1,132 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use crate::real::consts::TAU;
+use crate::real::Real;
+
+use crate::constants::Waveform;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Wave {
+    Shape(Waveform),
+    // one cycle, looped and interpolated, each between minus one and one
+    Samples(Vec<Real>),
+}
+
+// Drives an interval's rest length continuously: around where it was when the muscle was attached,
+// by the amplitude as a fraction of it, with frequency in cycles per tick and phase in cycles.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Muscle {
+    pub(crate) interval_index: usize,
+    pub(crate) rest_length: Real,
+    pub(crate) wave: Wave,
+    pub(crate) amplitude: Real,
+    pub(crate) frequency: Real,
+    pub(crate) phase: Real,
+}
+
+impl Muscle {
+    pub fn rest_length(&self, tick: u32) -> Real {
+        self.rest_length * (1.0 + self.amplitude * self.value(tick))
+    }
+
+    // From minus one to one.
+    fn value(&self, tick: u32) -> Real {
+        let cycle = (self.phase + self.frequency * tick as Real).rem_euclid(1.0);
+        match &self.wave {
+            Wave::Shape(Waveform::Sine) => (TAU * cycle).sin(),
+            Wave::Shape(Waveform::Square) => {
+                if cycle < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Wave::Shape(Waveform::Triangle) => 4.0 * (cycle - 0.5).abs() - 1.0,
+            Wave::Samples(samples) => {
+                let position = cycle * samples.len() as Real;
+                let index = (position as usize).min(samples.len() - 1);
+                let next = samples[(index + 1) % samples.len()];
+                let along = position - index as Real;
+                samples[index] * (1.0 - along) + next * along
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::chain;
+    use crate::world::World;
+
+    use super::*;
+
+    fn muscle(wave: Wave) -> Muscle {
+        Muscle {
+            interval_index: 0,
+            rest_length: 2.0,
+            wave,
+            amplitude: 0.5,
+            frequency: 0.25,
+            phase: 0.0,
+        }
+    }
+
+    #[test]
+    fn waves_run_from_minus_one_to_one_over_a_cycle() {
+        let values = |wave: Wave| {
+            (0..4)
+                .map(|tick| muscle(wave.clone()).value(tick))
+                .collect::<Vec<Real>>()
+        };
+        let sine = values(Wave::Shape(Waveform::Sine));
+        assert!(
+            sine.iter()
+                .zip([0.0, 1.0, 0.0, -1.0])
+                .all(|(a, e)| (a - e).abs() < 1e-6),
+            "{:?}",
+            sine
+        );
+        assert_eq!(
+            values(Wave::Shape(Waveform::Square)),
+            [1.0, 1.0, -1.0, -1.0]
+        );
+        assert_eq!(
+            values(Wave::Shape(Waveform::Triangle)),
+            [1.0, 0.0, -1.0, 0.0]
+        );
+        let mut sampled = muscle(Wave::Samples(vec![0.0, 1.0]));
+        sampled.frequency = 0.125;
+        assert_eq!(
+            [0, 2, 4, 6]
+                .iter()
+                .map(|&tick| sampled.value(tick))
+                .collect::<Vec<Real>>(),
+            [0.0, 0.5, 1.0, 0.5]
+        );
+        assert_eq!(muscle(Wave::Shape(Waveform::Square)).rest_length(0), 3.0);
+        assert_eq!(muscle(Wave::Shape(Waveform::Square)).rest_length(2), 1.0);
+    }
+
+    #[test]
+    fn an_interval_takes_one_muscle_and_follows_it_every_tick() {
+        let mut fabric = chain(2, 1.0);
+        assert!(fabric.add_muscle(0, Waveform::Sine, 1.0, 0.1, 0.0).is_err());
+        fabric.add_muscle(0, Waveform::Sine, 0.2, 0.1, 0.0).unwrap();
+        fabric
+            .add_muscle(0, Waveform::Square, 0.2, 0.1, 0.0)
+            .unwrap();
+        assert_eq!(fabric.get_muscle_count(), 1);
+        fabric.iterate_ticks(&World::new(), 1);
+        let interval = &fabric.intervals[0];
+        assert!(
+            (interval.length_1 - 1.2).abs() < 1e-6 || (interval.length_1 - 0.8).abs() < 1e-6,
+            "{}",
+            interval.length_1
+        );
+    }
+}
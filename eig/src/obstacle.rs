@@ -0,0 +1,145 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use nalgebra::*;
+
+use crate::real::Real;
+
+// Solid things in the world for joints to run into. A plane is solid on the side away from its normal.
+#[derive(Clone, Copy, Debug)]
+pub enum Obstacle {
+    Sphere {
+        center: Point3<Real>,
+        radius: Real,
+    },
+    Box {
+        min: Point3<Real>,
+        max: Point3<Real>,
+    },
+    Plane {
+        point: Point3<Real>,
+        normal: Vector3<Real>,
+    },
+}
+
+impl Obstacle {
+    // How deep the location is inside, and the shortest way out.
+    pub fn penetration(&self, location: &Point3<Real>) -> Option<(Real, Vector3<Real>)> {
+        match *self {
+            Obstacle::Sphere { center, radius } => {
+                let outward = location - center;
+                let distance = outward.magnitude();
+                if distance >= radius {
+                    return None;
+                }
+                let normal = if distance > Real::EPSILON {
+                    outward / distance
+                } else {
+                    Vector3::y()
+                };
+                Some((radius - distance, normal))
+            }
+            Obstacle::Box { min, max } => {
+                let inside =
+                    (0..3).all(|axis| location[axis] > min[axis] && location[axis] < max[axis]);
+                if !inside {
+                    return None;
+                }
+                let mut exit = (Real::INFINITY, Vector3::y());
+                for axis in 0..3 {
+                    for (depth, sign) in [
+                        (location[axis] - min[axis], -1.0),
+                        (max[axis] - location[axis], 1.0),
+                    ] {
+                        if depth < exit.0 {
+                            let mut normal: Vector3<Real> = zero();
+                            normal[axis] = sign;
+                            exit = (depth, normal);
+                        }
+                    }
+                }
+                Some(exit)
+            }
+            Obstacle::Plane { point, normal } => {
+                let height = (location - point).dot(&normal);
+                if height >= 0.0 {
+                    return None;
+                }
+                Some((-height, normal))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::joint::Joint;
+    use crate::world::World;
+
+    use super::*;
+
+    #[test]
+    fn finds_the_depth_and_the_shortest_way_out_of_each_shape() {
+        let sphere = Obstacle::Sphere {
+            center: Point3::origin(),
+            radius: 1.0,
+        };
+        assert_eq!(sphere.penetration(&Point3::new(0.0, 0.0, 2.0)), None);
+        let (depth, normal) = sphere.penetration(&Point3::new(0.0, 0.0, 0.75)).unwrap();
+        assert!(
+            (depth - 0.25).abs() < 1e-6 && normal == Vector3::z(),
+            "{} {}",
+            depth,
+            normal
+        );
+        let solid = Obstacle::Box {
+            min: Point3::origin(),
+            max: Point3::new(4.0, 2.0, 4.0),
+        };
+        assert_eq!(solid.penetration(&Point3::new(5.0, 1.0, 1.0)), None);
+        assert_eq!(
+            solid
+                .penetration(&Point3::new(1.0, 1.9, 2.0))
+                .map(|(_, normal)| normal),
+            Some(Vector3::y())
+        );
+        assert_eq!(
+            solid
+                .penetration(&Point3::new(0.5, 1.0, 2.0))
+                .map(|(_, normal)| normal),
+            Some(-Vector3::x())
+        );
+        let plane = Obstacle::Plane {
+            point: Point3::new(0.0, 1.0, 0.0),
+            normal: Vector3::y(),
+        };
+        assert_eq!(plane.penetration(&Point3::new(3.0, 1.5, 3.0)), None);
+        let (depth, normal) = plane.penetration(&Point3::new(3.0, 0.5, 3.0)).unwrap();
+        assert!(
+            (depth - 0.5).abs() < 1e-6 && normal == Vector3::y(),
+            "{} {}",
+            depth,
+            normal
+        );
+    }
+
+    #[test]
+    fn a_joint_inside_is_pushed_out_and_slowed_along_the_surface() {
+        let mut world = World::new();
+        world
+            .add_plane_obstacle(0.0, 1.0, 0.0, 0.0, 1.0, 0.0)
+            .unwrap();
+        world.set_obstacle_contact(0.1, 0.5).unwrap();
+        let mut joint = Joint::new(0.0, 0.9, 0.0);
+        joint.velocity = Vector3::new(1.0, 0.0, 0.0);
+        joint.obstacle_physics(&world, 1.0);
+        assert!(joint.velocity.y > 0.0, "{}", joint.velocity);
+        assert!((joint.velocity.x - 0.5).abs() < 1e-6, "{}", joint.velocity);
+        joint.fixed = true;
+        joint.velocity = Vector3::zeros();
+        joint.obstacle_physics(&world, 1.0);
+        assert_eq!(joint.velocity, Vector3::zeros());
+    }
+}
@@ -0,0 +1,170 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use crate::real::consts::TAU;
+use crate::real::Real;
+
+use crate::interval::Interval;
+
+// One unit of the pattern generator, swinging the rest lengths of its intervals around where they
+// were when they joined it. Phase is in cycles and frequency in cycles per tick.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Oscillator {
+    pub(crate) frequency: Real,
+    pub(crate) amplitude: Real,
+    pub(crate) phase: Real,
+    // interval, its rest length, and whether it shortens while the others lengthen
+    pub(crate) members: Vec<(usize, Real, bool)>,
+}
+
+// The follower is pulled toward being the offset, in cycles, behind the leader, by the weight in
+// cycles per tick at most.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Coupling {
+    pub(crate) leader: usize,
+    pub(crate) follower: usize,
+    pub(crate) weight: Real,
+    pub(crate) offset: Real,
+}
+
+// Phase oscillators coupled in the Kuramoto way, so that with enough weight they lock into the pattern
+// of offsets whatever phases they started with, which is what makes a gait out of separate swings.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PatternGenerator {
+    pub(crate) oscillators: Vec<Oscillator>,
+    pub(crate) couplings: Vec<Coupling>,
+}
+
+impl PatternGenerator {
+    // Every phase moves on together, from the phases as they were at the start of the tick.
+    pub fn advance(&mut self, intervals: &mut [Interval]) {
+        let mut steps: Vec<Real> = self
+            .oscillators
+            .iter()
+            .map(|oscillator| oscillator.frequency)
+            .collect();
+        for coupling in self.couplings.iter() {
+            let leader = self.oscillators[coupling.leader].phase;
+            let follower = self.oscillators[coupling.follower].phase;
+            steps[coupling.follower] +=
+                coupling.weight * (TAU * (leader - follower - coupling.offset)).sin();
+        }
+        for (oscillator, step) in self.oscillators.iter_mut().zip(steps) {
+            oscillator.phase = (oscillator.phase + step).rem_euclid(1.0);
+            let swing = oscillator.amplitude * (TAU * oscillator.phase).sin();
+            for &(index, rest_length, inverted) in oscillator.members.iter() {
+                let interval = &mut intervals[index];
+                let rest_length = rest_length * (1.0 + if inverted { -swing } else { swing });
+                interval.length_0 = rest_length;
+                interval.length_1 = rest_length;
+                interval.length_nuance = 0.0;
+                interval.attack = 0.0;
+                interval.decay = 0.0;
+            }
+        }
+    }
+
+    // An interval follows one oscillator only, joining another leaves the first.
+    pub fn join(
+        &mut self,
+        oscillator: usize,
+        interval_index: usize,
+        rest_length: Real,
+        inverted: bool,
+    ) {
+        self.leave(interval_index);
+        self.oscillators[oscillator]
+            .members
+            .push((interval_index, rest_length, inverted));
+    }
+
+    pub fn interval_removed(&mut self, index: usize, last: usize) {
+        self.leave(index);
+        for oscillator in self.oscillators.iter_mut() {
+            for member in oscillator
+                .members
+                .iter_mut()
+                .filter(|member| member.0 == last)
+            {
+                member.0 = index;
+            }
+        }
+    }
+
+    pub fn leave(&mut self, interval_index: usize) {
+        for oscillator in self.oscillators.iter_mut() {
+            oscillator
+                .members
+                .retain(|member| member.0 != interval_index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn oscillator(phase: Real) -> Oscillator {
+        Oscillator {
+            frequency: 0.01,
+            amplitude: 0.1,
+            phase,
+            members: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn coupled_oscillators_lock_into_the_offset_whatever_they_started_at() {
+        let mut generator = PatternGenerator {
+            oscillators: vec![oscillator(0.0), oscillator(0.6)],
+            couplings: vec![Coupling {
+                leader: 0,
+                follower: 1,
+                weight: 0.02,
+                offset: 0.25,
+            }],
+        };
+        for _ in 0..2000 {
+            generator.advance(&mut []);
+        }
+        let lag = (generator.oscillators[0].phase - generator.oscillators[1].phase).rem_euclid(1.0);
+        assert!((lag - 0.25).abs() < 1e-3, "{}", lag);
+    }
+
+    #[test]
+    fn members_swing_opposite_when_inverted_and_follow_one_oscillator_only() {
+        let mut intervals = vec![Interval::new(0, 1, false, 1.0, 1.0, 1.0, 0.0); 3];
+        let mut generator = PatternGenerator {
+            oscillators: vec![oscillator(0.24), oscillator(0.0)],
+            couplings: Vec::new(),
+        };
+        generator.join(0, 0, 1.0, false);
+        generator.join(0, 1, 2.0, true);
+        generator.join(1, 2, 1.0, false);
+        generator.join(0, 2, 1.0, false);
+        assert!(generator.oscillators[1].members.is_empty());
+        generator.advance(&mut intervals);
+        assert!(
+            (intervals[0].length_0 - 1.1).abs() < 1e-6,
+            "{}",
+            intervals[0].length_0
+        );
+        assert!(
+            (intervals[1].length_0 - 1.8).abs() < 1e-6,
+            "{}",
+            intervals[1].length_0
+        );
+        generator.interval_removed(0, 2);
+        let members: Vec<(usize, bool)> = generator.oscillators[0]
+            .members
+            .iter()
+            .map(|&(index, _, inverted)| (index, inverted))
+            .collect();
+        assert_eq!(members, [(1, true), (0, false)]);
+    }
+}
@@ -0,0 +1,71 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use std::sync::{Arc, RwLock};
+
+use crate::fabric::Fabric;
+
+// The physics thread publishes copies, analysis threads hold on to whichever copy they last took.
+#[derive(Clone, Default)]
+pub struct FabricReader {
+    latest: Arc<RwLock<Option<Arc<Fabric>>>>,
+}
+
+impl FabricReader {
+    pub fn new() -> FabricReader {
+        FabricReader::default()
+    }
+
+    pub fn publish(&self, fabric: &Fabric) {
+        let snapshot = Arc::new(fabric.clone());
+        *self.latest.write().unwrap() = Some(snapshot);
+    }
+
+    pub fn snapshot(&self) -> Option<Arc<Fabric>> {
+        self.latest.read().unwrap().clone()
+    }
+
+    pub fn age(&self) -> Option<u32> {
+        self.latest
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|fabric| fabric.age)
+    }
+}
+
+// Anything in the fabric that can't cross threads should fail here rather than at a caller.
+const _: fn() = || {
+    fn assert<T: Send + Sync>() {}
+    assert::<FabricReader>();
+};
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::world::World;
+
+    use super::*;
+
+    #[test]
+    fn readers_keep_the_copy_they_took() {
+        let reader = FabricReader::new();
+        assert!(reader.snapshot().is_none());
+        let mut fabric = Fabric::new(0);
+        fabric.create_joint(0.0, 1.0, 0.0).unwrap();
+        reader.publish(&fabric);
+        let taken = reader.snapshot().unwrap();
+        fabric.iterate_ticks(&World::new(), 10);
+        reader.publish(&fabric);
+        assert_eq!(taken.age, 0);
+        assert_eq!(reader.age(), Some(10));
+        let elsewhere = reader.clone();
+        let age = thread::spawn(move || elsewhere.snapshot().map(|fabric| fabric.age))
+            .join()
+            .unwrap();
+        assert_eq!(age, Some(10));
+    }
+}
@@ -0,0 +1,70 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+// The scalar the physics runs on, single precision as the browser has always had it, or double
+// behind the f64 feature for long runs. What goes out to JavaScript and into snapshots stays f32.
+
+#[cfg(all(feature = "f64", feature = "simd"))]
+compile_error!("the simd lanes are f32, so simd and f64 don't go together");
+
+#[cfg(not(feature = "f64"))]
+pub type Real = f32;
+
+#[cfg(feature = "f64")]
+pub type Real = f64;
+
+#[cfg(not(feature = "f64"))]
+pub use std::f32::consts;
+
+#[cfg(feature = "f64")]
+pub use std::f64::consts;
+
+// A no-op unless the physics is in f64.
+#[allow(clippy::unnecessary_cast)]
+pub fn single(value: Real) -> f32 {
+    value as f32
+}
+
+#[cfg(not(feature = "f64"))]
+pub fn inv_sqrt(value: Real) -> Real {
+    fast_inv_sqrt::InvSqrt32::inv_sqrt32(value)
+}
+
+#[cfg(feature = "f64")]
+pub fn inv_sqrt(value: Real) -> Real {
+    fast_inv_sqrt::InvSqrt64::inv_sqrt64(value)
+}
+
+// A typed array from JavaScript, widened to the physics.
+pub fn reals(values: &[f32]) -> Vec<Real> {
+    values.iter().map(|&value| value as Real).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widens_from_and_narrows_to_the_buffers_without_loss_for_singles() {
+        let values = [0.1_f32, -2.5, 1e-7, 3e7];
+        let widened = reals(&values);
+        assert_eq!(
+            widened.iter().copied().map(single).collect::<Vec<f32>>(),
+            values
+        );
+        assert_eq!(
+            std::mem::size_of::<Real>(),
+            if cfg!(feature = "f64") { 8 } else { 4 }
+        );
+    }
+
+    #[test]
+    fn inv_sqrt_is_close_enough_for_lengths() {
+        for value in [0.01, 1.0, 4.0, 1e4] {
+            let exact = 1.0 / (value as Real).sqrt();
+            assert!((inv_sqrt(value) - exact).abs() / exact < 0.01, "{}", value);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use nalgebra::*;
+
+use crate::interval::Interval;
+use crate::joint::Joint;
+use crate::real::Real;
+
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Region {
+    Tag(u16),
+    // intervals whose midpoint lies inside
+    Aabb {
+        min: Point3<Real>,
+        max: Point3<Real>,
+    },
+}
+
+impl Region {
+    pub fn contains(&self, interval: &Interval, joints: &[Joint]) -> bool {
+        match *self {
+            Region::Tag(tag) => interval.tag == tag,
+            Region::Aabb { min, max } => {
+                let alpha = &joints[interval.alpha_index].location.coords;
+                let omega = &joints[interval.omega_index].location.coords;
+                let midpoint = (alpha + omega) / 2.0;
+                (0..3).all(|axis| midpoint[axis] >= min[axis] && midpoint[axis] <= max[axis])
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegionStrain {
+    pub count: usize,
+    pub mean: Real,
+    // largest magnitude, with its sign
+    pub max: Real,
+}
+
+// Accumulated while the intervals are being stepped anyway, so asking costs nothing extra.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegionWatch {
+    pub(crate) region: Region,
+    pub(crate) latest: RegionStrain,
+    sum: Real,
+    count: usize,
+    max: Real,
+}
+
+impl RegionWatch {
+    pub fn new(region: Region) -> RegionWatch {
+        RegionWatch {
+            region,
+            latest: RegionStrain::default(),
+            sum: 0.0,
+            count: 0,
+            max: 0.0,
+        }
+    }
+
+    pub fn contains(&self, interval: &Interval, joints: &[Joint]) -> bool {
+        self.region.contains(interval, joints)
+    }
+
+    pub fn accumulate(&mut self, strain: Real) {
+        self.sum += strain;
+        self.count += 1;
+        if strain.abs() > self.max.abs() {
+            self.max = strain;
+        }
+    }
+
+    pub fn finish(&mut self) {
+        self.latest = RegionStrain {
+            count: self.count,
+            mean: if self.count == 0 {
+                0.0
+            } else {
+                self.sum / self.count as Real
+            },
+            max: self.max,
+        };
+        self.sum = 0.0;
+        self.count = 0;
+        self.max = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::IntervalRole;
+    use crate::error::Error;
+    use crate::fabric::Fabric;
+    use crate::world::World;
+
+    use super::*;
+
+    #[test]
+    fn keeps_the_signed_max_and_starts_over_every_tick() {
+        let mut watch = RegionWatch::new(Region::Tag(0));
+        for strain in [0.01, -0.03, 0.02] {
+            watch.accumulate(strain);
+        }
+        watch.finish();
+        assert_eq!(watch.latest.count, 3);
+        assert_eq!(watch.latest.max, -0.03);
+        assert!(watch.latest.mean.abs() < 1e-6);
+        watch.finish();
+        assert_eq!(watch.latest, RegionStrain::default());
+    }
+
+    #[test]
+    fn watches_by_tag_or_by_the_midpoint_in_a_box() {
+        let mut fabric = Fabric::new(0);
+        for x in 0..3 {
+            fabric.create_joint(x as Real, 1.0, 0.0).unwrap();
+        }
+        fabric
+            .add_interval_safe(0, 1, IntervalRole::Pull, 1.0, 0.9, 0.0)
+            .unwrap();
+        fabric
+            .add_interval_safe(1, 2, IntervalRole::Pull, 1.0, 0.8, 0.0)
+            .unwrap();
+        fabric.set_interval_tag(1, 5).unwrap();
+        let tagged = fabric.watch_region_tag(5);
+        let boxed = fabric.watch_region_box(-1.0, 0.0, -1.0, 1.0, 2.0, 1.0);
+        fabric.iterate_ticks(&World::new(), 1);
+        let strains = [fabric.intervals[0].strain, fabric.intervals[1].strain];
+        assert_eq!(fabric.region_strain(tagged).unwrap().count, 1);
+        assert_eq!(fabric.get_region_max_strain(tagged).unwrap(), strains[1]);
+        assert_eq!(fabric.get_region_mean_strain(boxed).unwrap(), strains[0]);
+        assert!(matches!(
+            fabric.region_strain(2),
+            Err(Error::RegionOutOfRange { index: 2, count: 2 })
+        ));
+    }
+}
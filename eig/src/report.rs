@@ -0,0 +1,506 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+// Plain-text tables for spreadsheets, a header row and then one row per element, and a summary of a
+// whole run as JSON or a standalone HTML page.
+
+use std::fmt::Write;
+
+use nalgebra::*;
+
+use crate::constants::IntervalRole;
+use crate::event::{ContactEvent, FabricEvent};
+use crate::fabric::Fabric;
+use crate::real::Real;
+use crate::world::World;
+
+const INTERVAL_COLUMNS: [&str; 9] = [
+    "interval",
+    "role",
+    "alpha",
+    "omega",
+    "rest_length",
+    "real_length",
+    "strain",
+    "stiffness",
+    "force",
+];
+
+// Comma for CSV, tab for TSV.
+pub fn intervals(fabric: &Fabric, world: &World, separator: char) -> String {
+    let world = world.in_stage(fabric.stage);
+    let separator = separator.to_string();
+    let mut report = INTERVAL_COLUMNS.join(&separator);
+    report.push('\n');
+    for (index, interval) in fabric.intervals.iter().enumerate() {
+        let fields = [
+            index.to_string(),
+            role_name(interval.role()).to_string(),
+            interval.alpha_index.to_string(),
+            interval.omega_index.to_string(),
+            interval.length_1.to_string(),
+            interval
+                .calculate_current_length(&fabric.joints)
+                .to_string(),
+            interval.strain.to_string(),
+            interval.stiffness.to_string(),
+            interval.axial_force(&world, fabric.stage).to_string(),
+        ];
+        let _ = writeln!(report, "{}", fields.join(&separator));
+    }
+    report
+}
+
+// What a run went through, fed after every iterate: the energies at that age, the extremes each
+// interval's strain reached, and the events drained from the fabric.
+#[derive(Clone, Debug, Default)]
+pub struct RunLog {
+    pub energy: Vec<EnergySample>,
+    pub envelopes: Vec<(Real, Real)>,
+    pub events: Vec<FabricEvent>,
+}
+
+// Potential and strain energy as in FabricStats, so potential means nothing but its changes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EnergySample {
+    pub age: u32,
+    pub kinetic: Real,
+    pub potential: Real,
+    pub strain: Real,
+    pub actuation: Real,
+}
+
+impl RunLog {
+    // The envelopes start over when intervals come or go, since their indices no longer line up.
+    pub fn record(&mut self, fabric: &Fabric, world: &World) {
+        let stats = fabric.stats(world);
+        self.energy.push(EnergySample {
+            age: fabric.age,
+            kinetic: stats.kinetic,
+            potential: stats.potential,
+            strain: stats.strain,
+            actuation: fabric.actuation_energy,
+        });
+        if self.envelopes.len() != fabric.intervals.len() {
+            self.envelopes = vec![(Real::INFINITY, Real::NEG_INFINITY); fabric.intervals.len()];
+        }
+        for (envelope, interval) in self.envelopes.iter_mut().zip(fabric.intervals.iter()) {
+            envelope.0 = envelope.0.min(interval.strain);
+            envelope.1 = envelope.1.max(interval.strain);
+        }
+    }
+
+    pub fn events(&mut self, events: impl IntoIterator<Item = FabricEvent>) {
+        self.events.extend(events);
+    }
+}
+
+// Count and total rest length of one role, what has to be bought.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Material {
+    pub role: IntervalRole,
+    pub count: usize,
+    pub total_length: Real,
+}
+
+#[derive(Clone, Debug)]
+pub struct Summary {
+    pub age: u32,
+    pub joints: usize,
+    pub intervals: usize,
+    pub faces: usize,
+    pub min: Point3<Real>,
+    pub max: Point3<Real>,
+    pub height: Real,
+    pub radius: Real,
+    pub envelopes: Vec<(Real, Real)>,
+    pub materials: Vec<Material>,
+    pub energy: Vec<EnergySample>,
+    pub events: Vec<FabricEvent>,
+}
+
+// The state the run ended in, together with what the log saw along the way.
+pub fn generate(fabric: &Fabric, log: &RunLog) -> Summary {
+    let (min, max) = match fabric.joints.first() {
+        None => (Point3::origin(), Point3::origin()),
+        Some(first) => fabric
+            .joints
+            .iter()
+            .fold((first.location, first.location), |(min, max), joint| {
+                (min.inf(&joint.location), max.sup(&joint.location))
+            }),
+    };
+    let center = Point3::from((min.coords + max.coords) / 2.0);
+    let radius = fabric
+        .joints
+        .iter()
+        .map(|joint| {
+            Vector2::new(joint.location.x - center.x, joint.location.z - center.z).magnitude()
+        })
+        .fold(0.0, Real::max);
+    let envelopes = if log.envelopes.len() == fabric.intervals.len() {
+        log.envelopes.clone()
+    } else {
+        fabric
+            .intervals
+            .iter()
+            .map(|interval| (interval.strain, interval.strain))
+            .collect()
+    };
+    let materials = [IntervalRole::Push, IntervalRole::Pull]
+        .iter()
+        .map(|&role| {
+            let lengths = fabric
+                .intervals
+                .iter()
+                .filter(|interval| interval.role() == role)
+                .map(|interval| interval.length_1);
+            let (count, total_length) = lengths.fold((0, 0.0), |(count, total), length| {
+                (count + 1, total + length)
+            });
+            Material {
+                role,
+                count,
+                total_length,
+            }
+        })
+        .collect();
+    Summary {
+        age: fabric.age,
+        joints: fabric.joints.len(),
+        intervals: fabric.intervals.len(),
+        faces: fabric.faces.len(),
+        min,
+        max,
+        height: max.y - min.y,
+        radius,
+        envelopes,
+        materials,
+        energy: log.energy.clone(),
+        events: log.events.clone(),
+    }
+}
+
+impl Summary {
+    pub fn to_json(&self) -> String {
+        let point = |p: &Point3<Real>| format!("[{},{},{}]", number(p.x), number(p.y), number(p.z));
+        let envelopes: Vec<String> = self
+            .envelopes
+            .iter()
+            .map(|&(low, high)| format!("[{},{}]", number(low), number(high)))
+            .collect();
+        let materials: Vec<String> = self
+            .materials
+            .iter()
+            .map(|material| {
+                format!(
+                    r#"{{"role":"{}","count":{},"total_length":{}}}"#,
+                    role_name(material.role),
+                    material.count,
+                    number(material.total_length)
+                )
+            })
+            .collect();
+        let energy: Vec<String> = self
+            .energy
+            .iter()
+            .map(|sample| {
+                format!(
+                    r#"{{"age":{},"kinetic":{},"potential":{},"strain":{},"actuation":{}}}"#,
+                    sample.age,
+                    number(sample.kinetic),
+                    number(sample.potential),
+                    number(sample.strain),
+                    number(sample.actuation)
+                )
+            })
+            .collect();
+        let events: Vec<String> = self.events.iter().map(event_json).collect();
+        format!(
+            concat!(
+                r#"{{"age":{},"joints":{},"intervals":{},"faces":{},"#,
+                r#""bounds":{{"min":{},"max":{}}},"height":{},"radius":{},"#,
+                r#""strain_envelopes":[{}],"materials":[{}],"energy":[{}],"events":[{}]}}"#,
+            ),
+            self.age,
+            self.joints,
+            self.intervals,
+            self.faces,
+            point(&self.min),
+            point(&self.max),
+            number(self.height),
+            number(self.radius),
+            envelopes.join(","),
+            materials.join(","),
+            energy.join(","),
+            events.join(","),
+        )
+    }
+
+    // One page with nothing to fetch: the tables, and the energy history drawn inline.
+    pub fn to_html(&self) -> String {
+        let mut html = String::from(concat!(
+            "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>Run report</title>",
+            "<style>body{font-family:sans-serif}table{border-collapse:collapse}",
+            "td,th{border:1px solid #ccc;padding:2px 8px;text-align:right}</style></head><body>\n",
+        ));
+        let _ = writeln!(html, "<h1>Run report, age {}</h1>", self.age);
+        let _ = writeln!(html, "<h2>Geometry</h2><table>");
+        let rows = [
+            ("joints", self.joints.to_string()),
+            ("intervals", self.intervals.to_string()),
+            ("faces", self.faces.to_string()),
+            ("height", self.height.to_string()),
+            ("radius", self.radius.to_string()),
+            (
+                "min",
+                format!("{}, {}, {}", self.min.x, self.min.y, self.min.z),
+            ),
+            (
+                "max",
+                format!("{}, {}, {}", self.max.x, self.max.y, self.max.z),
+            ),
+        ];
+        for (name, value) in rows.iter() {
+            let _ = writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", name, value);
+        }
+        let _ = writeln!(html, "</table>\n<h2>Materials</h2><table><tr><th>role</th><th>count</th><th>total length</th></tr>");
+        for material in self.materials.iter() {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                role_name(material.role),
+                material.count,
+                material.total_length
+            );
+        }
+        let _ = writeln!(html, "</table>\n<h2>Energy</h2>");
+        html.push_str(&energy_chart(&self.energy));
+        let _ = writeln!(
+            html,
+            "<h2>Strain envelopes</h2><table><tr><th>interval</th><th>min</th><th>max</th></tr>"
+        );
+        for (index, (low, high)) in self.envelopes.iter().enumerate() {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                index, low, high
+            );
+        }
+        let _ = writeln!(
+            html,
+            "</table>\n<h2>Events</h2><table><tr><th>event</th></tr>"
+        );
+        for event in self.events.iter() {
+            let _ = writeln!(
+                html,
+                "<tr><td style=\"text-align:left\">{}</td></tr>",
+                event_json(event)
+            );
+        }
+        html.push_str("</table>\n</body></html>\n");
+        html
+    }
+}
+
+// Kinetic, potential and strain energy as polylines over age, all on one scale from the lowest to the
+// highest. Ages needn't rise, a log can go on across a loaded snapshot.
+fn energy_chart(energy: &[EnergySample]) -> String {
+    const WIDTH: Real = 600.0;
+    const HEIGHT: Real = 200.0;
+    // name, color and what it draws
+    type Line = (&'static str, &'static str, fn(&EnergySample) -> Real);
+    let curves: [Line; 3] = [
+        ("kinetic", "#c33", |sample| sample.kinetic),
+        ("potential", "#36c", |sample| sample.potential),
+        ("strain", "#393", |sample| sample.strain),
+    ];
+    let (Some(youngest), Some(oldest)) = (
+        energy.iter().map(|sample| sample.age).min(),
+        energy.iter().map(|sample| sample.age).max(),
+    ) else {
+        return String::new();
+    };
+    let span = (oldest - youngest).max(1) as Real;
+    let values = || {
+        energy
+            .iter()
+            .flat_map(|sample| curves.iter().map(move |(_, _, value)| value(sample)))
+            .filter(|value| value.is_finite())
+    };
+    let low = values().fold(0.0, Real::min);
+    let range = (values().fold(0.0, Real::max) - low).max(Real::EPSILON);
+    let mut svg = format!(
+        "<p>energy from {} to {}</p><svg width=\"{WIDTH}\" height=\"{HEIGHT}\">",
+        low,
+        low + range
+    );
+    for (name, color, value) in curves.iter() {
+        let points: Vec<String> = energy
+            .iter()
+            .filter(|sample| value(sample).is_finite())
+            .map(|sample| {
+                let x = (sample.age - youngest) as Real / span * WIDTH;
+                let y = HEIGHT - (value(sample) - low) / range * HEIGHT;
+                format!("{:.1},{:.1}", x, y)
+            })
+            .collect();
+        let _ = write!(
+            svg,
+            "<polyline fill=\"none\" stroke=\"{}\" points=\"{}\"><title>{}</title></polyline>",
+            color,
+            points.join(" "),
+            name
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn event_json(event: &FabricEvent) -> String {
+    match *event {
+        FabricEvent::Contact(ContactEvent {
+            joint,
+            impulse,
+            tick,
+            ..
+        }) => {
+            format!(
+                r#"{{"kind":"contact","tick":{},"joint":{},"impulse":{}}}"#,
+                tick,
+                joint,
+                number(impulse)
+            )
+        }
+        FabricEvent::FabricAsleep { tick } => format!(r#"{{"kind":"asleep","tick":{}}}"#, tick),
+        FabricEvent::IntervalRemoved { index, moved_from } => format!(
+            r#"{{"kind":"interval_removed","index":{},"moved_from":{}}}"#,
+            index,
+            moved_from.map_or("null".to_string(), |from| from.to_string())
+        ),
+        FabricEvent::JointRemoved { index } => {
+            format!(r#"{{"kind":"joint_removed","index":{}}}"#, index)
+        }
+        FabricEvent::AnchorReleased {
+            joint,
+            reaction,
+            tick,
+        } => format!(
+            r#"{{"kind":"anchor_released","tick":{},"joint":{},"reaction":{}}}"#,
+            tick,
+            joint,
+            number(reaction.magnitude())
+        ),
+        FabricEvent::StrainAlarm {
+            interval,
+            level,
+            strain,
+            tick,
+        } => format!(
+            r#"{{"kind":"strain_alarm","tick":{},"interval":{},"level":"{:?}","strain":{}}}"#,
+            tick,
+            interval,
+            level,
+            number(strain)
+        ),
+        FabricEvent::IntervalBroken {
+            interval,
+            strain,
+            tick,
+        } => format!(
+            r#"{{"kind":"interval_broken","tick":{},"interval":{},"strain":{}}}"#,
+            tick,
+            interval,
+            number(strain)
+        ),
+        FabricEvent::GrowthStopped { face, tick } => format!(
+            r#"{{"kind":"growth_stopped","tick":{},"face":[{},{},{}]}}"#,
+            tick, face[0], face[1], face[2]
+        ),
+    }
+}
+
+fn role_name(role: IntervalRole) -> &'static str {
+    match role {
+        IntervalRole::Push => "push",
+        IntervalRole::Pull => "pull",
+    }
+}
+
+// JSON has no infinities or NaN.
+fn number(value: Real) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(age: u32, kinetic: Real) -> EnergySample {
+        EnergySample {
+            age,
+            kinetic,
+            potential: -kinetic,
+            strain: kinetic / 2.0,
+            actuation: 0.0,
+        }
+    }
+
+    #[test]
+    fn charts_ages_that_go_back() {
+        let chart = energy_chart(&[sample(10, 1.0), sample(3, 2.0), sample(7, Real::NAN)]);
+        assert_eq!(chart.matches("<polyline").count(), 3);
+        assert!(chart.contains("energy from -2 to 2"));
+    }
+
+    #[test]
+    fn records_potential_and_strain_energy() {
+        let mut world = World::new();
+        world.gravity = 0.0;
+        let mut fabric = Fabric::new(0);
+        fabric.create_joint(0.0, 1.0, 0.0).unwrap();
+        fabric.create_joint(2.0, 1.0, 0.0).unwrap();
+        fabric
+            .add_interval_safe(0, 1, IntervalRole::Pull, 1.0, 1.0, 0.0)
+            .unwrap();
+        fabric.iterate_ticks(&world, 1);
+        let mut log = RunLog::default();
+        log.record(&fabric, &world);
+        let stats = fabric.stats(&world);
+        assert_eq!(
+            (log.energy[0].potential, log.energy[0].strain),
+            (stats.potential, stats.strain)
+        );
+        assert!(log.energy[0].strain > 0.0);
+        assert!(generate(&fabric, &log)
+            .to_json()
+            .contains(r#""potential":"#));
+    }
+
+    #[test]
+    fn tabulates_a_header_and_a_row_per_interval() {
+        let mut fabric = Fabric::new(0);
+        for x in [0.0, 2.0, 3.0] {
+            fabric.create_joint(x, 1.0, 0.0).unwrap();
+        }
+        fabric
+            .add_interval_safe(0, 1, IntervalRole::Push, 1.0, 1.0, 0.0)
+            .unwrap();
+        fabric
+            .add_interval_safe(1, 2, IntervalRole::Pull, 1.0, 1.0, 0.0)
+            .unwrap();
+        let tsv = intervals(&fabric, &World::new(), '\t');
+        let rows: Vec<Vec<&str>> = tsv.lines().map(|line| line.split('\t').collect()).collect();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0], INTERVAL_COLUMNS);
+        assert_eq!(rows[1][..5], ["0", "push", "0", "1", "2"]);
+        assert_eq!(rows[2][1], "pull");
+        assert!(rows.iter().all(|row| row.len() == INTERVAL_COLUMNS.len()));
+    }
+}
@@ -0,0 +1,84 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use wasm_bindgen::prelude::*;
+
+use crate::constants::*;
+use crate::real::Real;
+use crate::world::World;
+
+pub const PUSH_LINEAR_DENSITY: Real = 1.0;
+pub const PULL_LINEAR_DENSITY: Real = 0.05;
+
+// What an interval of a role becomes in a stage: its rest length is scaled, its own stiffness and
+// damping multiplied and its mass per unit length taken from here.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RoleDefaults {
+    pub rest_length_scale: Real,
+    pub stiffness: Real,
+    pub damping: Real,
+    pub linear_density: Real,
+}
+
+impl RoleDefaults {
+    pub fn of(world: &World, role: IntervalRole, stage: Stage) -> RoleDefaults {
+        let push = role == IntervalRole::Push;
+        let pretenst_factor = match stage {
+            Stage::Slack => 0.0,
+            Stage::Growing | Stage::Shaping => world.shaping_pretenst_factor,
+            Stage::Pretensing | Stage::Pretenst => world.pretenst_factor,
+        };
+        let stiffness_factor = match stage {
+            Stage::Slack => 0.0,
+            Stage::Growing | Stage::Shaping => world.shaping_stiffness_factor,
+            Stage::Pretensing | Stage::Pretenst => world.stiffness_factor,
+        };
+        // nothing resists in the slack stage
+        let damped = if stage == Stage::Slack { 0.0 } else { 1.0 };
+        if push {
+            RoleDefaults {
+                rest_length_scale: 1.0 + pretenst_factor,
+                stiffness: world.push_over_pull * stiffness_factor,
+                damping: world.push_damping * damped,
+                linear_density: PUSH_LINEAR_DENSITY,
+            }
+        } else {
+            RoleDefaults {
+                rest_length_scale: 1.0,
+                stiffness: stiffness_factor,
+                damping: world.pull_damping * damped,
+                linear_density: PULL_LINEAR_DENSITY,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_pushes_are_lengthened_and_nothing_resists_when_slack() {
+        let mut world = World::new();
+        world.pretenst_factor = 0.1;
+        world.push_over_pull = 4.0;
+        world.stiffness_factor = 2.0;
+        let push = RoleDefaults::of(&world, IntervalRole::Push, Stage::Pretenst);
+        let pull = RoleDefaults::of(&world, IntervalRole::Pull, Stage::Pretenst);
+        assert!((push.rest_length_scale - 1.1).abs() < 1e-6);
+        assert_eq!(pull.rest_length_scale, 1.0);
+        assert_eq!((push.stiffness, pull.stiffness), (8.0, 2.0));
+        assert_eq!(
+            (push.linear_density, pull.linear_density),
+            (PUSH_LINEAR_DENSITY, PULL_LINEAR_DENSITY)
+        );
+        let slack = RoleDefaults::of(&world, IntervalRole::Push, Stage::Slack);
+        assert_eq!(
+            (slack.rest_length_scale, slack.stiffness, slack.damping),
+            (1.0, 0.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,145 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use crate::constants::*;
+use crate::error::Error;
+use crate::fabric::Fabric;
+use crate::joint::Friction;
+use crate::real::Real;
+use crate::world::World;
+
+// What to perturb per episode. Features are drawn as values, stiffness and mass as factors on what
+// each interval already has, so randomize a fresh copy every episode rather than the same fabric again.
+#[derive(Clone, Debug, Default)]
+pub struct Ranges {
+    pub features: Vec<(WorldFeature, Real, Real)>,
+    // one ground friction fraction for all joints, the same along and across
+    pub friction: Option<(Real, Real)>,
+    pub stiffness: Option<(Real, Real)>,
+    pub mass: Option<(Real, Real)>,
+}
+
+// The same seed and ranges always give the same scenario.
+pub fn randomize(
+    seed: u64,
+    ranges: &Ranges,
+    fabric: &mut Fabric,
+    world: &mut World,
+) -> Result<(), Error> {
+    for &(_, min, max) in ranges.features.iter() {
+        check_range(min, max, Real::NEG_INFINITY)?;
+    }
+    for &(min, max) in [ranges.friction, ranges.stiffness, ranges.mass]
+        .iter()
+        .flatten()
+    {
+        check_range(min, max, 0.0)?;
+    }
+    if let Some((_, max)) = ranges.friction {
+        if max > 1.0 {
+            return Err(Error::IllegalFriction {
+                along: max,
+                across: max,
+            });
+        }
+    }
+    let mut state = seed | 1;
+    let mut draw = |(min, max): (Real, Real)| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        min + (max - min) * (state >> 40) as Real / (1_u64 << 24) as Real
+    };
+    for &(feature, min, max) in ranges.features.iter() {
+        world.set_float_value(feature, draw((min, max)));
+    }
+    if let Some(range) = ranges.friction {
+        let fraction = draw(range);
+        for joint in fabric.joints.iter_mut() {
+            joint.friction = Some(Friction::new(0.0, fraction, fraction));
+        }
+    }
+    for interval in fabric.intervals.iter_mut() {
+        if let Some(range) = ranges.stiffness {
+            interval.stiffness *= draw(range);
+        }
+        if let Some(range) = ranges.mass {
+            interval.linear_density *= draw(range);
+        }
+    }
+    fabric.wake();
+    Ok(())
+}
+
+fn check_range(min: Real, max: Real, floor: Real) -> Result<(), Error> {
+    if !(min.is_finite() && max.is_finite() && min >= floor && min <= max) {
+        return Err(Error::IllegalRange { min, max });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixture::chain;
+
+    use super::*;
+
+    fn scenario(seed: u64, ranges: &Ranges) -> (Fabric, World) {
+        let mut fabric = chain(2, 1.0);
+        let mut world = World::new();
+        randomize(seed, ranges, &mut fabric, &mut world).unwrap();
+        (fabric, world)
+    }
+
+    fn ranges() -> Ranges {
+        Ranges {
+            features: vec![(WorldFeature::Gravity, 1e-6, 2e-6)],
+            friction: Some((0.2, 0.4)),
+            stiffness: Some((0.5, 1.5)),
+            mass: None,
+        }
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_scenario_within_the_ranges() {
+        let draws = |seed| {
+            let (fabric, world) = scenario(seed, &ranges());
+            let friction = fabric.joints[0].friction.unwrap();
+            (
+                world.get_float_value(WorldFeature::Gravity),
+                friction.along,
+                fabric.intervals[0].stiffness,
+            )
+        };
+        let (gravity, friction, stiffness) = draws(7);
+        assert_eq!(draws(7), (gravity, friction, stiffness));
+        assert_ne!(draws(8), (gravity, friction, stiffness));
+        assert!((1e-6..2e-6).contains(&gravity), "{}", gravity);
+        assert!((0.2..0.4).contains(&friction), "{}", friction);
+        assert!((0.5..1.5).contains(&stiffness), "{}", stiffness);
+    }
+
+    #[test]
+    fn rejects_reversed_ranges_and_friction_past_one() {
+        let mut fabric = Fabric::new(0);
+        let mut world = World::new();
+        let reversed = Ranges {
+            stiffness: Some((2.0, 1.0)),
+            ..Ranges::default()
+        };
+        assert!(matches!(
+            randomize(1, &reversed, &mut fabric, &mut world),
+            Err(Error::IllegalRange { .. })
+        ));
+        let slippery = Ranges {
+            friction: Some((0.5, 1.5)),
+            ..Ranges::default()
+        };
+        assert!(matches!(
+            randomize(1, &slippery, &mut fabric, &mut world),
+            Err(Error::IllegalFriction { .. })
+        ));
+    }
+}
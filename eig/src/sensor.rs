@@ -0,0 +1,86 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use nalgebra::*;
+
+use crate::interval::Interval;
+use crate::joint::Joint;
+use crate::real::Real;
+
+// A point part way along an interval, where a gauge or target would sit on the real member.
+// Moved every tick, remembering where it was placed so deflection needs no bookkeeping by the host.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sensor {
+    pub(crate) interval_index: usize,
+    // zero at alpha, one at omega
+    pub(crate) position: Real,
+    pub(crate) location: Point3<Real>,
+    pub(crate) reference: Point3<Real>,
+}
+
+impl Sensor {
+    pub fn new(
+        interval_index: usize,
+        position: Real,
+        interval: &Interval,
+        joints: &[Joint],
+    ) -> Sensor {
+        let location = Sensor::locate(position, interval, joints);
+        Sensor {
+            interval_index,
+            position,
+            location,
+            reference: location,
+        }
+    }
+
+    pub fn update(&mut self, interval: &Interval, joints: &[Joint]) {
+        self.location = Sensor::locate(self.position, interval, joints);
+    }
+
+    pub fn deflection(&self) -> Vector3<Real> {
+        self.location - self.reference
+    }
+
+    fn locate(position: Real, interval: &Interval, joints: &[Joint]) -> Point3<Real> {
+        let alpha = &joints[interval.alpha_index].location;
+        let omega = &joints[interval.omega_index].location;
+        alpha + (omega - alpha) * position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::fixture::chain;
+    use crate::world::World;
+
+    use super::*;
+
+    #[test]
+    fn sensors_ride_their_interval_and_follow_it_when_another_is_removed() {
+        let mut fabric = chain(3, 1.0);
+        assert!(matches!(
+            fabric.add_sensor(1, 1.5),
+            Err(Error::IllegalPosition { .. })
+        ));
+        let sensor = fabric.add_sensor(1, 0.25).unwrap();
+        fabric.iterate_ticks(&World::new(), 100);
+        let expected = fabric.joints[1].location
+            + (fabric.joints[2].location - fabric.joints[1].location) * 0.25;
+        assert!((fabric.sensors[sensor].location - expected).magnitude() < 1e-6);
+        let deflection = fabric.get_sensor_deflection(sensor).unwrap();
+        assert!((deflection - (expected - Point3::new(1.25, 1.0, 0.0)).magnitude()).abs() < 1e-6);
+        fabric.remove_interval(0).unwrap();
+        assert_eq!(fabric.sensors[sensor].interval_index, 0);
+        fabric.remove_interval(0).unwrap();
+        assert!(fabric.sensors.is_empty());
+        assert!(matches!(
+            fabric.get_sensor_deflection(0),
+            Err(Error::SensorOutOfRange { index: 0, count: 0 })
+        ));
+    }
+}
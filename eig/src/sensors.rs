@@ -0,0 +1,192 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use std::collections::VecDeque;
+
+use nalgebra::*;
+
+use crate::fabric::Fabric;
+use crate::interval::Interval;
+use crate::joint::Joint;
+use crate::real::Real;
+
+// Read out at the end of every tick, as gauges and accelerometers on the built structure would be:
+// a strain gauge on every interval keeping its last readings, and on every joint the velocity and
+// the acceleration as the change in velocity over the tick, gravity and contact included.
+#[derive(Clone, Debug, Default)]
+pub struct Readings {
+    pub(crate) history: usize,
+    // per interval, oldest first
+    pub(crate) strains: Vec<VecDeque<Real>>,
+    pub(crate) velocities: Vec<Vector3<Real>>,
+    pub(crate) accelerations: Vec<Vector3<Real>>,
+}
+
+impl Readings {
+    // Starting from the velocities as they are, so the first reading isn't everything since rest.
+    pub fn new(history: usize, joints: &[Joint]) -> Readings {
+        let velocities = joints.iter().map(|joint| joint.velocity).collect();
+        let accelerations = vec![zero(); joints.len()];
+        Readings {
+            history,
+            velocities,
+            accelerations,
+            ..Readings::default()
+        }
+    }
+
+    // New intervals and joints start with nothing read, a new joint as if it had been still.
+    pub fn record(&mut self, intervals: &[Interval], joints: &[Joint], time_step: Real) {
+        self.strains.resize_with(intervals.len(), VecDeque::new);
+        for (strains, interval) in self.strains.iter_mut().zip(intervals) {
+            if strains.len() == self.history {
+                strains.pop_front();
+            }
+            strains.push_back(interval.strain);
+        }
+        self.velocities.resize(joints.len(), zero());
+        self.accelerations.resize(joints.len(), zero());
+        for (index, joint) in joints.iter().enumerate() {
+            self.accelerations[index] = (joint.velocity - self.velocities[index]) / time_step;
+            self.velocities[index] = joint.velocity;
+        }
+    }
+
+    pub fn interval_removed(&mut self, index: usize) {
+        if index < self.strains.len() {
+            self.strains.swap_remove(index);
+        }
+    }
+
+    pub fn joint_removed(&mut self, index: usize) {
+        if index < self.velocities.len() {
+            self.velocities.remove(index);
+            self.accelerations.remove(index);
+        }
+    }
+}
+
+impl Fabric {
+    pub fn sensor_view(&self) -> FabricSensors<'_> {
+        FabricSensors::new(self)
+    }
+}
+
+// What a controller may look at, and nothing it could not measure on the real thing. Everything is
+// as of the end of the last tick, and reads zero for anything not yet read.
+pub struct FabricSensors<'a> {
+    fabric: &'a Fabric,
+    readings: Option<&'a Readings>,
+}
+
+impl<'a> FabricSensors<'a> {
+    pub fn new(fabric: &'a Fabric) -> FabricSensors<'a> {
+        FabricSensors {
+            fabric,
+            readings: fabric.readings.as_ref(),
+        }
+    }
+
+    pub fn age(&self) -> u32 {
+        self.fabric.age
+    }
+
+    pub fn interval_count(&self) -> usize {
+        self.fabric.intervals.len()
+    }
+
+    pub fn joint_count(&self) -> usize {
+        self.fabric.joints.len()
+    }
+
+    pub fn strain(&self, interval: usize) -> Option<Real> {
+        self.fabric
+            .intervals
+            .get(interval)
+            .map(|interval| interval.strain)
+    }
+
+    // Oldest first, empty unless readings are being kept.
+    pub fn strain_history(&self, interval: usize) -> impl Iterator<Item = Real> + 'a {
+        self.readings
+            .and_then(|readings| readings.strains.get(interval))
+            .into_iter()
+            .flat_map(|strains| strains.iter().copied())
+    }
+
+    pub fn location(&self, joint: usize) -> Option<Point3<Real>> {
+        self.fabric.joints.get(joint).map(|joint| joint.location)
+    }
+
+    pub fn velocity(&self, joint: usize) -> Option<Vector3<Real>> {
+        self.fabric.joints.get(joint).map(|joint| joint.velocity)
+    }
+
+    pub fn acceleration(&self, joint: usize) -> Option<Vector3<Real>> {
+        if joint >= self.joint_count() {
+            return None;
+        }
+        let reading = self
+            .readings
+            .and_then(|readings| readings.accelerations.get(joint));
+        Some(reading.copied().unwrap_or_else(zero))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::fixture::chain;
+    use crate::world::World;
+
+    use super::*;
+
+    fn drifting() -> Fabric {
+        let mut fabric = chain(2, 1.0);
+        for joint in fabric.joints.iter_mut() {
+            joint.velocity = Vector3::new(0.01, 0.0, 0.0);
+        }
+        fabric
+    }
+
+    #[test]
+    fn first_reading_is_against_the_velocity_when_readings_started() {
+        let mut fabric = drifting();
+        fabric.set_reading_history(4);
+        fabric.iterate_ticks(&World::new(), 1);
+        let acceleration = fabric.sensor_view().acceleration(0).unwrap();
+        assert!(
+            acceleration.magnitude() < 0.001,
+            "{}",
+            acceleration.magnitude()
+        );
+    }
+
+    #[test]
+    fn keeps_only_the_history_asked_for() {
+        let mut fabric = drifting();
+        fabric.set_reading_history(3);
+        fabric.iterate_ticks(&World::new(), 5);
+        assert_eq!(fabric.sensor_view().strain_history(0).count(), 3);
+        let mut strains = [0.0; 8];
+        assert_eq!(fabric.copy_strain_history_to(0, &mut strains).unwrap(), 3);
+    }
+
+    #[test]
+    fn accelerations_need_a_buffer_of_the_right_length() {
+        let fabric = drifting();
+        let mut short = [0.0; 4];
+        assert!(matches!(
+            fabric.copy_joint_accelerations_to(&mut short),
+            Err(Error::WrongLength {
+                expected: 6,
+                actual: 4
+            })
+        ));
+        let mut exact = [1.0; 6];
+        fabric.copy_joint_accelerations_to(&mut exact).unwrap();
+        assert_eq!(exact, [0.0; 6]);
+    }
+}
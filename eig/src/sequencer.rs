@@ -0,0 +1,261 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use crate::error::Error;
+use crate::interval::Interval;
+use crate::real::Real;
+
+// What a cue acts on. A face is held by its joints, since face indices shift as faces go, and stands
+// for the intervals along its edges.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Target {
+    Interval(usize),
+    Face([usize; 3]),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Cue {
+    // out to the delta times the rest length over the attack and back over the decay
+    Twitch {
+        target: Target,
+        delta: Real,
+        attack: Real,
+        decay: Real,
+    },
+    // to the delta times the rest length over the countdown, for good
+    Length {
+        target: Target,
+        delta: Real,
+        countdown: Real,
+    },
+}
+
+impl Cue {
+    pub fn check(&self) -> Result<(), Error> {
+        let (delta, countdowns) = match *self {
+            Cue::Twitch {
+                delta,
+                attack,
+                decay,
+                ..
+            } => (delta, [attack, decay]),
+            Cue::Length {
+                delta, countdown, ..
+            } => (delta, [countdown, countdown]),
+        };
+        if !(delta.is_finite() && delta > 0.0) {
+            return Err(Error::IllegalLength { length: delta });
+        }
+        if let Some(&countdown) = countdowns
+            .iter()
+            .find(|countdown| !(countdown.is_finite() && **countdown > 0.0))
+        {
+            return Err(Error::IllegalCountdown { countdown });
+        }
+        Ok(())
+    }
+
+    fn target(&self) -> &Target {
+        match self {
+            Cue::Twitch { target, .. } | Cue::Length { target, .. } => target,
+        }
+    }
+
+    fn target_mut(&mut self) -> &mut Target {
+        match self {
+            Cue::Twitch { target, .. } | Cue::Length { target, .. } => target,
+        }
+    }
+}
+
+// A choreography played back tick by tick, counted from when it was loaded or last rewound. Cues due
+// on the same tick go in the order they were added, so playback is the same every time.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sequencer {
+    pub(crate) cues: Vec<(u32, Cue)>,
+    pub(crate) next: usize,
+    pub(crate) elapsed: u32,
+    // replay from the start once the last cue has gone, this many ticks after the first
+    pub(crate) period: Option<u32>,
+}
+
+impl Sequencer {
+    pub fn add(&mut self, tick: u32, cue: Cue) {
+        let position = self
+            .cues
+            .partition_point(|&(scheduled, _)| scheduled <= tick);
+        self.cues.insert(position, (tick, cue));
+        if position < self.next {
+            self.next += 1;
+        }
+    }
+
+    pub fn rewind(&mut self) {
+        self.next = 0;
+        self.elapsed = 0;
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.cues.len() - self.next
+    }
+
+    // Cues still to come, now or on the next time around.
+    pub fn is_playing(&self) -> bool {
+        self.remaining() > 0 || self.period.is_some() && !self.cues.is_empty()
+    }
+
+    pub fn advance(&mut self, intervals: &mut [Interval]) {
+        if let Some(period) = self.period {
+            if self.elapsed >= period {
+                self.rewind();
+            }
+        }
+        while let Some(&(tick, cue)) = self.cues.get(self.next) {
+            if tick > self.elapsed {
+                break;
+            }
+            self.next += 1;
+            for index in targeted(cue.target(), intervals) {
+                let interval = &mut intervals[index];
+                match cue {
+                    Cue::Twitch {
+                        delta,
+                        attack,
+                        decay,
+                        ..
+                    } => interval.actuate_twitch(attack, decay, delta),
+                    Cue::Length {
+                        delta, countdown, ..
+                    } => {
+                        let rest_length = interval.length_1 * delta;
+                        interval.actuate(rest_length, countdown)
+                    }
+                }
+            }
+        }
+        self.elapsed += 1;
+    }
+
+    pub fn interval_removed(&mut self, index: usize, last: usize) {
+        self.retain(|target| *target != Target::Interval(index));
+        for (_, cue) in self.cues.iter_mut() {
+            if let Target::Interval(interval) = cue.target_mut() {
+                if *interval == last {
+                    *interval = index;
+                }
+            }
+        }
+    }
+
+    pub fn joint_removed(&mut self, index: usize) {
+        self.retain(|target| !matches!(target, Target::Face(joints) if joints.contains(&index)));
+        for (_, cue) in self.cues.iter_mut() {
+            if let Target::Face(joints) = cue.target_mut() {
+                for joint in joints.iter_mut().filter(|joint| **joint > index) {
+                    *joint -= 1;
+                }
+            }
+        }
+    }
+
+    fn retain(&mut self, keep: impl Fn(&Target) -> bool) {
+        let played = self.cues[..self.next]
+            .iter()
+            .filter(|(_, cue)| keep(cue.target()))
+            .count();
+        self.cues.retain(|(_, cue)| keep(cue.target()));
+        self.next = played;
+    }
+}
+
+fn targeted(target: &Target, intervals: &[Interval]) -> Vec<usize> {
+    match *target {
+        Target::Interval(index) if index < intervals.len() => vec![index],
+        Target::Interval(_) => Vec::new(),
+        Target::Face(joints) => (0..intervals.len())
+            .filter(|&index| {
+                (0..3).any(|side| intervals[index].connects(joints[side], joints[(side + 1) % 3]))
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error;
+    use crate::fixture::chain;
+    use crate::world::World;
+
+    #[test]
+    fn plays_cues_on_their_ticks_in_order() {
+        let mut fabric = chain(2, 1.0);
+        #[rustfmt::skip]
+        let rows = [
+            5.0, 0.0, 0.0, 2.0, 10.0, 0.0,
+            2.0, 0.0, 0.0, 0.5, 10.0, 0.0,
+        ];
+        assert_eq!(fabric.load_sequence(&rows).unwrap(), 2);
+        let world = World::new();
+        fabric.iterate_ticks(&world, 3);
+        assert_eq!(fabric.get_sequence_remaining(), 1);
+        assert_eq!(fabric.intervals[0].length_1, 0.5);
+        fabric.iterate_ticks(&world, 3);
+        assert_eq!(fabric.get_sequence_remaining(), 0);
+        assert_eq!(fabric.intervals[0].length_1, 1.0);
+        fabric.rewind_sequence();
+        assert_eq!(fabric.get_sequence_remaining(), 2);
+    }
+
+    #[test]
+    fn refuses_rows_that_are_not_whole_numbers_and_keeps_what_was_loaded() {
+        let mut fabric = chain(2, 1.0);
+        fabric
+            .load_sequence(&[1.0, 0.0, 0.0, 2.0, 10.0, 0.0])
+            .unwrap();
+        for row in [
+            [1.5, 0.0, 0.0, 2.0, 10.0, 0.0],
+            [-1.0, 0.0, 0.0, 2.0, 10.0, 0.0],
+            [1.0, 2.0, 0.0, 2.0, 10.0, 0.0],
+            [1.0, 0.5, 0.0, 2.0, 10.0, 0.0],
+            [1.0, 0.0, f32::NAN, 2.0, 10.0, 0.0],
+        ] {
+            assert!(
+                matches!(
+                    fabric.load_sequence(&row),
+                    Err(Error::MalformedCommand { offset: 0 })
+                ),
+                "{:?}",
+                row
+            );
+        }
+        assert!(fabric
+            .load_sequence(&[1.0, 0.0, 7.0, 2.0, 10.0, 0.0])
+            .is_err());
+        assert_eq!(fabric.get_sequence_remaining(), 1);
+    }
+
+    #[test]
+    fn stays_awake_until_the_last_cue() {
+        let mut fabric = chain(2, 1.0);
+        let mut world = World::new();
+        world.sleep_ticks = 2;
+        world.gravity = 0.0;
+        world.sleep_velocity = 0.001;
+        world.sleep_force = 0.001;
+        fabric.iterate_ticks(&world, 10);
+        assert!(fabric.asleep);
+        fabric
+            .load_sequence(&[40.0, 0.0, 0.0, 1.1, 10.0, 0.0])
+            .unwrap();
+        assert!(!fabric.asleep);
+        fabric.iterate_ticks(&world, 20);
+        assert!(!fabric.asleep);
+        fabric.iterate_ticks(&world, 30);
+        assert_eq!(fabric.get_sequence_remaining(), 0);
+    }
+}
@@ -0,0 +1,131 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use nalgebra::*;
+
+use crate::interval::Interval;
+use crate::joint::Joint;
+use crate::real::Real;
+
+// The joints of the pulls carrying the tag, in order around the loop, or None if they don't form one.
+pub fn cable_loop(intervals: &[Interval], tag: u16) -> Option<Vec<usize>> {
+    let mut edges: Vec<(usize, usize)> = intervals
+        .iter()
+        .filter(|interval| !interval.push && interval.tag == tag)
+        .map(|interval| (interval.alpha_index, interval.omega_index))
+        .collect();
+    if edges.len() < 3 {
+        return None;
+    }
+    let (first, mut next) = edges.swap_remove(0);
+    let mut joints = vec![first];
+    while next != first {
+        let position = edges
+            .iter()
+            .position(|&(alpha, omega)| alpha == next || omega == next)?;
+        let (alpha, omega) = edges.swap_remove(position);
+        joints.push(next);
+        next = if alpha == next { omega } else { alpha };
+    }
+    if edges.is_empty() {
+        Some(joints)
+    } else {
+        None
+    }
+}
+
+pub fn centroid(joints: &[Joint], ring: &[usize]) -> Point3<Real> {
+    let sum = ring.iter().fold(Vector3::zeros(), |sum, &index| {
+        sum + joints[index].location.coords
+    });
+    Point3::from(sum / ring.len() as Real)
+}
+
+// Line the second ring up with the first: same direction, starting at the joint nearest the first's start.
+pub fn align(joints: &[Joint], ring: &[usize], other: &[usize]) -> Vec<usize> {
+    let distance = |a: usize, b: usize| (joints[a].location - joints[b].location).magnitude();
+    let start = (0..other.len())
+        .min_by(|&a, &b| distance(ring[0], other[a]).total_cmp(&distance(ring[0], other[b])))
+        .unwrap_or(0);
+    let mut aligned: Vec<usize> = other[start..]
+        .iter()
+        .chain(&other[..start])
+        .copied()
+        .collect();
+    let forward = distance(ring[1], aligned[1]);
+    let backward = distance(ring[1], aligned[aligned.len() - 1]);
+    if backward < forward {
+        aligned[1..].reverse();
+    }
+    aligned
+}
+
+// Triangles between two aligned rings, always advancing along the shorter diagonal.
+pub fn loft(joints: &[Joint], ring: &[usize], other: &[usize]) -> Vec<[usize; 3]> {
+    let distance = |a: usize, b: usize| (joints[a].location - joints[b].location).magnitude();
+    let (n, m) = (ring.len(), other.len());
+    let mut triangles = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        let a = ring[i % n];
+        let b = other[j % m];
+        let next_a = ring[(i + 1) % n];
+        let next_b = other[(j + 1) % m];
+        if j == m || (i < n && distance(next_a, b) < distance(a, next_b)) {
+            triangles.push([a, next_a, b]);
+            i += 1;
+        } else {
+            triangles.push([a, next_b, b]);
+            j += 1;
+        }
+    }
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::IntervalRole;
+    use crate::error::Error;
+    use crate::fabric::Fabric;
+
+    use super::*;
+
+    #[test]
+    fn skins_one_loop_with_a_cap_and_two_with_a_band() {
+        let mut fabric = Fabric::new(0);
+        for y in [1.0, 2.0] {
+            for (x, z) in [(1.0, 0.0), (0.0, 1.0), (-1.0, 0.0), (0.0, -1.0)] {
+                fabric.create_joint(x, y, z).unwrap();
+            }
+        }
+        for (ring, tag) in [(0, 1), (4, 2)] {
+            for corner in 0..4 {
+                let index = fabric
+                    .add_interval_safe(
+                        ring + corner,
+                        ring + (corner + 1) % 4,
+                        IntervalRole::Pull,
+                        1.0,
+                        1.0,
+                        0.0,
+                    )
+                    .unwrap();
+                fabric.set_interval_tag(index, tag).unwrap();
+            }
+        }
+        assert!(matches!(
+            fabric.skin(&[3], 0.0),
+            Err(Error::NotALoop { tag: 3 })
+        ));
+        assert_eq!(fabric.skin(&[1, 2], 0.0).unwrap(), 8);
+        assert_eq!(fabric.intervals.len(), 8);
+        assert_eq!(fabric.skin(&[1], 0.5).unwrap(), 4);
+        assert_eq!(fabric.joints.len(), 9);
+        assert_eq!(fabric.faces.len(), 12);
+        assert_eq!(fabric.intervals.len(), 12);
+        let hub = fabric.joints[8].location;
+        assert!((hub - Point3::new(0.0, 1.0, 0.0)).magnitude() < 1e-6);
+    }
+}
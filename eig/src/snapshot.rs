@@ -0,0 +1,1033 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+// Snapshot layout: MAGIC, VERSION, then self-delimiting chunks of
+//   tag [u8; 4], codec u8, raw length u32, stored length u32, checksum u32, stored bytes
+// so a reader can skip sections it doesn't want and a transfer can resume after the last whole chunk.
+// Older versions still decode, with defaults for whatever they did not store yet.
+
+use nalgebra::*;
+
+use crate::anchor::Anchor;
+use crate::command::WORLD_FEATURES;
+use crate::constants::*;
+use crate::constraint::Constraint;
+use crate::error::Error;
+use crate::fabric::Fabric;
+use crate::face::Face;
+use crate::interval::{Actuator, Attachment, Curve, Interval, MAX_CURVE_POINTS};
+use crate::joint::{Friction, Joint};
+use crate::medium::Medium;
+use crate::obstacle::Obstacle;
+use crate::real::{single, Real};
+use crate::surface::Heightmap;
+use crate::wind::Wind;
+use crate::world::World;
+
+const MAGIC: &[u8; 4] = b"EIGS";
+const VERSION: u8 = 4;
+const OLDEST_VERSION: u8 = 1;
+const HEADER_LENGTH: usize = 5;
+const CHUNK_HEADER_LENGTH: usize = 17;
+const CODEC_RAW: u8 = 0;
+const CODEC_LZ4: u8 = 1;
+// An lz4 block can't expand further than this.
+#[cfg(feature = "lz4")]
+const LZ4_MAX_RATIO: usize = 255;
+// Location and velocity.
+const JOINT_STATE_LENGTH: usize = 24;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Section {
+    Topology,
+    State,
+    Environment,
+    // since version 4, whatever the state leaves out
+    Extras,
+}
+
+impl Section {
+    fn tag(self) -> &'static [u8; 4] {
+        match self {
+            Section::Topology => b"TOPO",
+            Section::State => b"STAT",
+            Section::Environment => b"ENVR",
+            Section::Extras => b"EXTR",
+        }
+    }
+
+    fn from_tag(tag: &[u8]) -> Option<Section> {
+        [
+            Section::Topology,
+            Section::State,
+            Section::Environment,
+            Section::Extras,
+        ]
+        .iter()
+        .copied()
+        .find(|section| section.tag() == tag)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Chunk<'a> {
+    pub section: Option<Section>,
+    pub offset: usize,
+    pub version: u8,
+    codec: u8,
+    raw_length: usize,
+    stored: &'a [u8],
+}
+
+impl<'a> Chunk<'a> {
+    pub fn end(&self) -> usize {
+        self.offset + CHUNK_HEADER_LENGTH + self.stored.len()
+    }
+
+    fn payload(&self) -> Result<Vec<u8>, Error> {
+        let payload = match self.codec {
+            CODEC_RAW => self.stored.to_vec(),
+            #[cfg(feature = "lz4")]
+            CODEC_LZ4 if self.raw_length > self.stored.len().saturating_mul(LZ4_MAX_RATIO) => {
+                return Err(Error::CorruptSnapshot {
+                    offset: self.offset,
+                });
+            }
+            #[cfg(feature = "lz4")]
+            CODEC_LZ4 => lz4_flex::decompress(self.stored, self.raw_length).map_err(|_| {
+                Error::CorruptSnapshot {
+                    offset: self.offset,
+                }
+            })?,
+            #[cfg(not(feature = "lz4"))]
+            CODEC_LZ4 => return Err(Error::UnsupportedCodec { codec: CODEC_LZ4 }),
+            codec => return Err(Error::UnsupportedCodec { codec }),
+        };
+        if payload.len() != self.raw_length {
+            return Err(Error::CorruptSnapshot {
+                offset: self.offset,
+            });
+        }
+        Ok(payload)
+    }
+}
+
+pub fn encode(fabric: &Fabric, world: &World) -> Vec<u8> {
+    let mut bytes = encode_fabric(fabric);
+    push_chunk(&mut bytes, Section::Environment, &encode_environment(world));
+    bytes
+}
+
+// Without the environment, for saving many fabrics against one world.
+pub fn encode_fabric(fabric: &Fabric) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(VERSION);
+    push_chunk(&mut bytes, Section::Topology, &encode_topology(fabric));
+    push_chunk(&mut bytes, Section::State, &encode_state(fabric));
+    push_chunk(&mut bytes, Section::Extras, &encode_extras(fabric));
+    bytes
+}
+
+// Every whole, intact chunk, stopping quietly at a truncated tail so partial transfers can be resumed
+// from the end of the last one.
+pub fn chunks(bytes: &[u8]) -> Result<Vec<Chunk<'_>>, Error> {
+    if bytes.len() < HEADER_LENGTH || &bytes[..4] != MAGIC {
+        return Err(Error::CorruptSnapshot { offset: 0 });
+    }
+    let version = bytes[4];
+    if !(OLDEST_VERSION..=VERSION).contains(&version) {
+        return Err(Error::UnsupportedVersion { version });
+    }
+    let mut chunks = Vec::new();
+    let mut offset = HEADER_LENGTH;
+    while offset + CHUNK_HEADER_LENGTH <= bytes.len() {
+        let header = &bytes[offset..offset + CHUNK_HEADER_LENGTH];
+        let raw_length = u32_at(header, 5) as usize;
+        let stored_length = u32_at(header, 9) as usize;
+        let start = offset + CHUNK_HEADER_LENGTH;
+        if start + stored_length > bytes.len() {
+            break;
+        }
+        let stored = &bytes[start..start + stored_length];
+        if checksum(stored) != u32_at(header, 13) {
+            return Err(Error::CorruptSnapshot { offset });
+        }
+        chunks.push(Chunk {
+            section: Section::from_tag(&header[..4]),
+            offset,
+            version,
+            codec: header[4],
+            raw_length,
+            stored,
+        });
+        offset = start + stored_length;
+    }
+    Ok(chunks)
+}
+
+// Connectivity only: joints sit at the origin and intervals have no length until the state is applied.
+pub fn decode_topology(bytes: &[u8]) -> Result<Fabric, Error> {
+    let chunks = chunks(bytes)?;
+    let topology = find(&chunks, Section::Topology)?;
+    let mut reader = Reader::new(topology.payload()?, topology.offset);
+    let joint_count = reader.u32()? as usize;
+    // no more joints than the state has room for, or without one than there are bytes, so a corrupt
+    // count fails here instead of allocating
+    let room = find(&chunks, Section::State)
+        .map_or(bytes.len(), |state| state.raw_length / JOINT_STATE_LENGTH);
+    if joint_count > room {
+        return Err(reader.corrupt());
+    }
+    let mut fabric = Fabric::new(joint_count);
+    fabric.joints.resize(joint_count, Joint::new(0.0, 0.0, 0.0));
+    let joint = |reader: &mut Reader| -> Result<usize, Error> {
+        let index = reader.u32()? as usize;
+        if index >= joint_count {
+            return Err(reader.corrupt());
+        }
+        Ok(index)
+    };
+    for _interval in 0..reader.u32()? {
+        let alpha_index = joint(&mut reader)?;
+        let omega_index = joint(&mut reader)?;
+        let push = reader.u8()? != 0;
+        let mut interval = Interval::new(alpha_index, omega_index, push, 0.0, 0.0, 0.0, 0.0);
+        interval.tag = reader.u16()?;
+        fabric.intervals.push(interval);
+    }
+    for _face in 0..reader.u32()? {
+        let joints = [
+            joint(&mut reader)?,
+            joint(&mut reader)?,
+            joint(&mut reader)?,
+        ];
+        fabric
+            .faces
+            .push(Face::new(joints[0], joints[1], joints[2]));
+    }
+    for _anchor in 0..reader.u32()? {
+        let joint_index = joint(&mut reader)?;
+        let location = Point3::from(reader.vector()?);
+        let max_vertical = reader.f32()?;
+        let max_lateral = reader.f32()?;
+        fabric.anchors.push(Anchor::new(
+            joint_index,
+            location,
+            max_vertical,
+            max_lateral,
+        ));
+    }
+    Ok(fabric)
+}
+
+pub fn decode_fabric(bytes: &[u8]) -> Result<Fabric, Error> {
+    let mut fabric = decode_topology(bytes)?;
+    let chunks = chunks(bytes)?;
+    let state = find(&chunks, Section::State)?;
+    let mut reader = Reader::new(state.payload()?, state.offset);
+    fabric.age = reader.u32()?;
+    fabric.stage = match reader.u8()? {
+        0 => Stage::Growing,
+        1 => Stage::Shaping,
+        2 => Stage::Slack,
+        3 => Stage::Pretensing,
+        4 => Stage::Pretenst,
+        _ => return Err(reader.corrupt()),
+    };
+    fabric.pretensing_countdown = reader.f32()?;
+    fabric.floating = reader.u8()? != 0;
+    for joint in fabric.joints.iter_mut() {
+        joint.location = Point3::from(reader.vector()?);
+        joint.velocity = reader.vector()?;
+    }
+    for interval in fabric.intervals.iter_mut() {
+        interval.length_0 = reader.f32()?;
+        interval.length_1 = reader.f32()?;
+        interval.length_nuance = reader.f32()?;
+        interval.attack = reader.f32()?;
+        interval.decay = reader.f32()?;
+        interval.stiffness = reader.f32()?;
+        interval.linear_density = reader.f32()?;
+        interval.strain = reader.f32()?;
+    }
+    if state.version >= 2 {
+        fabric.frozen = reader.u8()? != 0;
+        for joint in fabric.joints.iter_mut() {
+            joint.payload_mass = reader.f32()?;
+        }
+    }
+    if state.version >= 3 {
+        for joint in fabric.joints.iter_mut() {
+            joint.fixed = reader.u8()? != 0;
+        }
+    }
+    if state.version >= 4 {
+        let extras = find(&chunks, Section::Extras)?;
+        decode_extras(
+            &mut fabric,
+            &mut Reader::new(extras.payload()?, extras.offset),
+        )?;
+    }
+    Ok(fabric)
+}
+
+fn decode_extras(fabric: &mut Fabric, reader: &mut Reader) -> Result<(), Error> {
+    let joint_count = fabric.joints.len();
+    let interval_count = fabric.intervals.len();
+    let index = |reader: &mut Reader, count: usize| -> Result<usize, Error> {
+        let index = reader.u32()? as usize;
+        if index >= count {
+            return Err(reader.corrupt());
+        }
+        Ok(index)
+    };
+    for joint in fabric.joints.iter_mut() {
+        if reader.flag()? {
+            let axis = reader.vector()?;
+            joint.friction = Some(Friction {
+                axis,
+                along: reader.f32()?,
+                across: reader.f32()?,
+            });
+        }
+    }
+    for interval in fabric.intervals.iter_mut() {
+        interval.disabled = reader.flag()?;
+        interval.delay = reader.u32()?;
+        interval.damping = reader.f32()?;
+        interval.plastic_strain = reader.f32()?;
+        interval.work = reader.f32()?;
+        if reader.flag()? {
+            interval.gap = Some(reader.f32()?);
+        }
+        if reader.flag()? {
+            let mut actuator = Actuator::new(reader.f32()?, reader.f32()?);
+            actuator.play = reader.f32()?;
+            interval.actuator = Some(actuator);
+        }
+        interval.curve = match reader.u8()? {
+            0 => None,
+            1 => {
+                let count = reader.u8()? as usize;
+                if !(2..=MAX_CURVE_POINTS).contains(&count) {
+                    return Err(reader.corrupt());
+                }
+                let mut points = [(0.0, 0.0); MAX_CURVE_POINTS];
+                for point in points.iter_mut().take(count) {
+                    *point = (reader.f32()?, reader.f32()?);
+                }
+                Some(Curve::Piecewise { points, count })
+            }
+            2 => Some(Curve::Polynomial([
+                reader.f32()?,
+                reader.f32()?,
+                reader.f32()?,
+                reader.f32()?,
+            ])),
+            _ => return Err(reader.corrupt()),
+        };
+        for attachment in interval.attachments.iter_mut() {
+            *attachment = if reader.flag()? {
+                let hub_index = index(reader, joint_count)?;
+                Some(Attachment {
+                    hub_index,
+                    axial: reader.f32()?,
+                    radial: reader.f32()?,
+                })
+            } else {
+                None
+            };
+        }
+    }
+    for _constraint in 0..reader.u32()? {
+        let alpha_index = index(reader, joint_count)?;
+        let omega_index = index(reader, joint_count)?;
+        fabric
+            .constraints
+            .push(Constraint::new(alpha_index, omega_index, reader.f32()?));
+    }
+    for _label in 0..reader.u32()? {
+        let interval = index(reader, interval_count)?;
+        let label = reader.string()?;
+        fabric.labels.insert(interval, label);
+    }
+    Ok(())
+}
+
+pub fn decode_world(bytes: &[u8]) -> Result<World, Error> {
+    let chunks = chunks(bytes)?;
+    let environment = find(&chunks, Section::Environment)?;
+    let mut reader = Reader::new(environment.payload()?, environment.offset);
+    let mut world = World::new();
+    world.surface_character = match reader.u8()? {
+        0 => SurfaceCharacter::Frozen,
+        1 => SurfaceCharacter::Sticky,
+        2 => SurfaceCharacter::Bouncy,
+        _ => return Err(reader.corrupt()),
+    };
+    world.push_and_pull = reader.u8()? != 0;
+    for value in world_values(&mut world) {
+        *value = reader.f32()?;
+    }
+    world.sleep_ticks = reader.u32()?;
+    if environment.version >= 4 {
+        decode_world_extras(&mut world, &mut reader)?;
+    }
+    Ok(world)
+}
+
+fn decode_world_extras(world: &mut World, reader: &mut Reader) -> Result<(), Error> {
+    world.kinetic_damping = reader.flag()?;
+    for remove_broken in world.remove_broken.iter_mut() {
+        *remove_broken = reader.flag()?;
+    }
+    world.integration = match reader.u8()? {
+        0 => Integration::SemiImplicitEuler,
+        1 => Integration::VelocityVerlet,
+        2 => Integration::RungeKutta4,
+        _ => return Err(reader.corrupt()),
+    };
+    world.substeps = reader.u32()?;
+    for value in later_world_values(world) {
+        *value = reader.f32()?;
+    }
+    // everything per unit of time divides by it
+    if !(world.time_step.is_finite() && world.time_step > 0.0) {
+        return Err(reader.corrupt());
+    }
+    for _sample in 0..reader.u32()? {
+        world.ground_motion.push(reader.f32()?);
+    }
+    for _medium in 0..reader.u32()? {
+        world.media.push(Medium {
+            top: reader.f32()?,
+            drag: reader.f32()?,
+            density: reader.f32()?,
+        });
+    }
+    if reader.flag()? {
+        let direction = reader.vector()?;
+        let mut wind = Wind::new(
+            0.0,
+            reader.f32()?,
+            reader.f32()?,
+            reader.f32()?,
+            reader.f32()?,
+            reader.f32()?,
+            reader.u32()?,
+        );
+        wind.direction = direction;
+        world.wind = Some(wind);
+    }
+    if reader.flag()? {
+        world.point_gravity = Some((Point3::from(reader.vector()?), reader.f32()?));
+    }
+    for _obstacle in 0..reader.u32()? {
+        let obstacle = match reader.u8()? {
+            0 => Obstacle::Sphere {
+                center: Point3::from(reader.vector()?),
+                radius: reader.f32()?,
+            },
+            1 => Obstacle::Box {
+                min: Point3::from(reader.vector()?),
+                max: Point3::from(reader.vector()?),
+            },
+            2 => Obstacle::Plane {
+                point: Point3::from(reader.vector()?),
+                normal: reader.vector()?,
+            },
+            _ => return Err(reader.corrupt()),
+        };
+        world.obstacles.push(obstacle);
+    }
+    if reader.flag()? {
+        let (origin_x, origin_z, spacing) = (reader.f32()?, reader.f32()?, reader.f32()?);
+        let columns = reader.u32()? as usize;
+        let mut heights = Vec::new();
+        for _height in 0..reader.u32()? {
+            heights.push(reader.f32()?);
+        }
+        let terrain = Heightmap::new(origin_x, origin_z, spacing, columns, &heights)
+            .map_err(|_| reader.corrupt())?;
+        world.terrain = Some(terrain);
+    }
+    for features in world.stage_features.iter_mut() {
+        for _feature in 0..reader.u32()? {
+            let feature = *WORLD_FEATURES
+                .get(reader.u8()? as usize)
+                .ok_or_else(|| reader.corrupt())?;
+            features.push((feature, reader.f32()?));
+        }
+    }
+    Ok(())
+}
+
+fn find<'a>(chunks: &[Chunk<'a>], section: Section) -> Result<Chunk<'a>, Error> {
+    chunks
+        .iter()
+        .find(|chunk| chunk.section == Some(section))
+        .copied()
+        .ok_or(Error::MissingSnapshotSection { section })
+}
+
+fn encode_topology(fabric: &Fabric) -> Vec<u8> {
+    let mut writer = Writer::default();
+    writer.u32(fabric.joints.len());
+    writer.u32(fabric.intervals.len());
+    for interval in fabric.intervals.iter() {
+        writer.u32(interval.alpha_index);
+        writer.u32(interval.omega_index);
+        writer.u8(interval.push as u8);
+        writer.u16(interval.tag);
+    }
+    writer.u32(fabric.faces.len());
+    for face in fabric.faces.iter() {
+        for joint_index in face.joints() {
+            writer.u32(joint_index);
+        }
+    }
+    writer.u32(fabric.anchors.len());
+    for anchor in fabric.anchors.iter() {
+        writer.u32(anchor.joint_index);
+        writer.vector(&anchor.location.coords);
+        writer.f32(anchor.max_vertical);
+        writer.f32(anchor.max_lateral);
+    }
+    writer.0
+}
+
+fn encode_state(fabric: &Fabric) -> Vec<u8> {
+    let mut writer = Writer::default();
+    writer.u32(fabric.age as usize);
+    writer.u8(fabric.stage as u8);
+    writer.f32(fabric.pretensing_countdown);
+    writer.u8(fabric.floating as u8);
+    for joint in fabric.joints.iter() {
+        writer.vector(&joint.location.coords);
+        writer.vector(&joint.velocity);
+    }
+    for interval in fabric.intervals.iter() {
+        for value in [
+            interval.length_0,
+            interval.length_1,
+            interval.length_nuance,
+            interval.attack,
+            interval.decay,
+            interval.stiffness,
+            interval.linear_density,
+            interval.strain,
+        ] {
+            writer.f32(value);
+        }
+    }
+    writer.u8(fabric.frozen as u8);
+    for joint in fabric.joints.iter() {
+        writer.f32(joint.payload_mass);
+    }
+    for joint in fabric.joints.iter() {
+        writer.u8(joint.fixed as u8);
+    }
+    writer.0
+}
+
+fn encode_environment(world: &World) -> Vec<u8> {
+    let mut writer = Writer::default();
+    let mut world = world.clone();
+    writer.u8(world.surface_character as u8);
+    writer.u8(world.push_and_pull as u8);
+    for value in world_values(&mut world) {
+        writer.f32(*value);
+    }
+    writer.u32(world.sleep_ticks as usize);
+    writer.u8(world.kinetic_damping as u8);
+    for &remove_broken in world.remove_broken.iter() {
+        writer.u8(remove_broken as u8);
+    }
+    writer.u8(world.integration as u8);
+    writer.u32(world.substeps as usize);
+    for value in later_world_values(&mut world) {
+        writer.f32(*value);
+    }
+    writer.u32(world.ground_motion.len());
+    for &sample in world.ground_motion.iter() {
+        writer.f32(sample);
+    }
+    writer.u32(world.media.len());
+    for medium in world.media.iter() {
+        for value in [medium.top, medium.drag, medium.density] {
+            writer.f32(value);
+        }
+    }
+    writer.u8(world.wind.is_some() as u8);
+    if let Some(wind) = &world.wind {
+        writer.vector(&wind.direction);
+        for value in [
+            wind.speed,
+            wind.gustiness,
+            wind.gust_ticks,
+            wind.turbulence,
+            wind.drag,
+        ] {
+            writer.f32(value);
+        }
+        writer.u32(wind.seed as usize);
+    }
+    writer.u8(world.point_gravity.is_some() as u8);
+    if let Some((center, radius)) = world.point_gravity {
+        writer.vector(&center.coords);
+        writer.f32(radius);
+    }
+    writer.u32(world.obstacles.len());
+    for obstacle in world.obstacles.iter() {
+        match *obstacle {
+            Obstacle::Sphere { center, radius } => {
+                writer.u8(0);
+                writer.vector(&center.coords);
+                writer.f32(radius);
+            }
+            Obstacle::Box { min, max } => {
+                writer.u8(1);
+                writer.vector(&min.coords);
+                writer.vector(&max.coords);
+            }
+            Obstacle::Plane { point, normal } => {
+                writer.u8(2);
+                writer.vector(&point.coords);
+                writer.vector(&normal);
+            }
+        }
+    }
+    writer.u8(world.terrain.is_some() as u8);
+    if let Some(terrain) = &world.terrain {
+        for value in [terrain.origin_x, terrain.origin_z, terrain.spacing] {
+            writer.f32(value);
+        }
+        writer.u32(terrain.columns);
+        writer.u32(terrain.heights.len());
+        for &height in terrain.heights.iter() {
+            writer.f32(height);
+        }
+    }
+    for features in world.stage_features.iter() {
+        writer.u32(features.len());
+        for &(feature, value) in features.iter() {
+            writer.u8(feature as u8);
+            writer.f32(value);
+        }
+    }
+    writer.0
+}
+
+fn encode_extras(fabric: &Fabric) -> Vec<u8> {
+    let mut writer = Writer::default();
+    for joint in fabric.joints.iter() {
+        writer.u8(joint.friction.is_some() as u8);
+        if let Some(friction) = &joint.friction {
+            writer.vector(&friction.axis);
+            writer.f32(friction.along);
+            writer.f32(friction.across);
+        }
+    }
+    for interval in fabric.intervals.iter() {
+        writer.u8(interval.disabled as u8);
+        writer.u32(interval.delay as usize);
+        for value in [interval.damping, interval.plastic_strain, interval.work] {
+            writer.f32(value);
+        }
+        writer.u8(interval.gap.is_some() as u8);
+        if let Some(gap) = interval.gap {
+            writer.f32(gap);
+        }
+        writer.u8(interval.actuator.is_some() as u8);
+        if let Some(actuator) = &interval.actuator {
+            for value in [actuator.dead_band, actuator.backlash, actuator.play] {
+                writer.f32(value);
+            }
+        }
+        match &interval.curve {
+            None => writer.u8(0),
+            Some(Curve::Piecewise { points, count }) => {
+                writer.u8(1);
+                writer.u8(*count as u8);
+                for &(strain, stress) in points.iter().take(*count) {
+                    writer.f32(strain);
+                    writer.f32(stress);
+                }
+            }
+            Some(Curve::Polynomial(coefficients)) => {
+                writer.u8(2);
+                for &coefficient in coefficients.iter() {
+                    writer.f32(coefficient);
+                }
+            }
+        }
+        for attachment in interval.attachments.iter() {
+            writer.u8(attachment.is_some() as u8);
+            if let Some(attachment) = attachment {
+                writer.u32(attachment.hub_index);
+                writer.f32(attachment.axial);
+                writer.f32(attachment.radial);
+            }
+        }
+    }
+    writer.u32(fabric.constraints.len());
+    for constraint in fabric.constraints.iter() {
+        writer.u32(constraint.alpha_index);
+        writer.u32(constraint.omega_index);
+        writer.f32(constraint.distance);
+    }
+    let mut labels: Vec<_> = fabric.labels.iter().collect();
+    labels.sort();
+    writer.u32(labels.len());
+    for (&interval, label) in labels {
+        writer.u32(interval);
+        writer.string(label);
+    }
+    writer.0
+}
+
+// Order matters: this is the layout of the environment section.
+fn world_values(world: &mut World) -> Vec<&mut Real> {
+    let World {
+        gravity,
+        drag,
+        pretenst_factor,
+        stiffness_factor,
+        iterations_per_frame,
+        interval_countdown,
+        pretensing_countdown,
+        shaping_pretenst_factor,
+        shaping_drag,
+        shaping_stiffness_factor,
+        visual_strain,
+        push_over_pull,
+        antigravity,
+        active_damping,
+        sleep_velocity,
+        sleep_force,
+        speed_limits,
+        ..
+    } = world;
+    let mut values = vec![
+        gravity,
+        drag,
+        pretenst_factor,
+        stiffness_factor,
+        iterations_per_frame,
+        interval_countdown,
+        pretensing_countdown,
+        shaping_pretenst_factor,
+        shaping_drag,
+        shaping_stiffness_factor,
+        visual_strain,
+        push_over_pull,
+        antigravity,
+        active_damping,
+        sleep_velocity,
+        sleep_force,
+    ];
+    values.extend(speed_limits.iter_mut());
+    values
+}
+
+// Added in version 4, after the sleep ticks and the switches.
+fn later_world_values(world: &mut World) -> Vec<&mut Real> {
+    let World {
+        alarm_hysteresis,
+        strain_alarms,
+        ultimate_strains,
+        plastic_yield,
+        medium_transition,
+        obstacle_stiffness,
+        obstacle_friction,
+        strut_collision,
+        time_step,
+        acceleration_limit,
+        gravity_direction,
+        ground_motion_ticks,
+        shaping_slew_rate,
+        push_damping,
+        pull_damping,
+        ..
+    } = world;
+    let mut values = vec![
+        alarm_hysteresis,
+        medium_transition,
+        obstacle_stiffness,
+        obstacle_friction,
+        &mut strut_collision.0,
+        &mut strut_collision.1,
+        time_step,
+        acceleration_limit,
+        ground_motion_ticks,
+        shaping_slew_rate,
+        push_damping,
+        pull_damping,
+    ];
+    values.extend(strain_alarms.iter_mut().flatten());
+    values.extend(ultimate_strains.iter_mut());
+    values.extend(plastic_yield.iter_mut().flatten());
+    values.extend(gravity_direction.iter_mut());
+    values
+}
+
+fn push_chunk(bytes: &mut Vec<u8>, section: Section, payload: &[u8]) {
+    #[cfg(feature = "lz4")]
+    let (codec, stored) = (CODEC_LZ4, lz4_flex::compress(payload));
+    #[cfg(not(feature = "lz4"))]
+    let (codec, stored) = (CODEC_RAW, payload.to_vec());
+    bytes.extend_from_slice(section.tag());
+    bytes.push(codec);
+    bytes.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&checksum(&stored).to_le_bytes());
+    bytes.extend_from_slice(&stored);
+}
+
+fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5_u32, |hash, &byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+#[derive(Default)]
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: usize) {
+        self.0.extend_from_slice(&(value as u32).to_le_bytes());
+    }
+
+    fn f32(&mut self, value: Real) {
+        self.0.extend_from_slice(&single(value).to_le_bytes());
+    }
+
+    fn vector(&mut self, vector: &Vector3<Real>) {
+        for value in vector.iter() {
+            self.f32(*value);
+        }
+    }
+
+    fn string(&mut self, value: &str) {
+        self.u32(value.len());
+        self.0.extend_from_slice(value.as_bytes());
+    }
+}
+
+struct Reader {
+    bytes: Vec<u8>,
+    position: usize,
+    chunk_offset: usize,
+}
+
+impl Reader {
+    fn new(bytes: Vec<u8>, chunk_offset: usize) -> Reader {
+        Reader {
+            bytes,
+            position: 0,
+            chunk_offset,
+        }
+    }
+
+    fn corrupt(&self) -> Error {
+        Error::CorruptSnapshot {
+            offset: self.chunk_offset,
+        }
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let bytes =
+            self.bytes
+                .get(self.position..self.position + N)
+                .ok_or(Error::CorruptSnapshot {
+                    offset: self.chunk_offset,
+                })?;
+        self.position += N;
+        let mut taken = [0_u8; N];
+        taken.copy_from_slice(bytes);
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    fn f32(&mut self) -> Result<Real, Error> {
+        Ok(f32::from_le_bytes(self.take()?) as Real)
+    }
+
+    fn flag(&mut self) -> Result<bool, Error> {
+        Ok(self.u8()? != 0)
+    }
+
+    fn vector(&mut self) -> Result<Vector3<Real>, Error> {
+        Ok(Vector3::new(self.f32()?, self.f32()?, self.f32()?))
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        let length = self.u32()? as usize;
+        let bytes = self
+            .bytes
+            .get(self.position..self.position.saturating_add(length))
+            .ok_or(Error::CorruptSnapshot {
+                offset: self.chunk_offset,
+            })?;
+        self.position += length;
+        String::from_utf8(bytes.to_vec()).map_err(|_| self.corrupt())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(sections: &[(Section, Vec<u8>)]) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        for (section, payload) in sections {
+            push_chunk(&mut bytes, *section, payload);
+        }
+        bytes
+    }
+
+    fn topology(joint_count: u32) -> Vec<u8> {
+        let mut payload = joint_count.to_le_bytes().to_vec();
+        payload.extend_from_slice(&[0_u8; 12]);
+        payload
+    }
+
+    #[test]
+    fn rejects_more_joints_than_the_state_can_hold() {
+        let bytes = snapshot(&[
+            (Section::Topology, topology(u32::MAX)),
+            (Section::State, vec![0_u8; 64]),
+        ]);
+        assert!(matches!(
+            decode_topology(&bytes),
+            Err(Error::CorruptSnapshot { .. })
+        ));
+        let bytes = snapshot(&[(Section::Topology, topology(u32::MAX))]);
+        assert!(matches!(
+            decode_topology(&bytes),
+            Err(Error::CorruptSnapshot { .. })
+        ));
+    }
+
+    fn dressed() -> (Fabric, World) {
+        let mut fabric = Fabric::new(0);
+        for x in 0..4 {
+            fabric.create_joint(x as Real, 1.0, 0.0).unwrap();
+        }
+        fabric
+            .create_interval(0, 1, true, 1.0, 1.0, 1.0, 0.0)
+            .unwrap();
+        fabric
+            .create_interval(1, 2, false, 1.0, 1.0, 1.0, 0.0)
+            .unwrap();
+        fabric
+            .create_interval(2, 3, false, 1.0, 1.0, 1.0, 0.0)
+            .unwrap();
+        fabric.set_joint_friction(3, 0.5, 0.1, 0.9).unwrap();
+        fabric.set_interval_gap(1, 0.8).unwrap();
+        fabric.set_interval_actuator(2, 0.01, 0.02).unwrap();
+        fabric.set_interval_damping(0, 2.0).unwrap();
+        fabric
+            .set_interval_curve(1, &[0.0, 0.1, 0.2], &[0.0, 1.0, 1.5])
+            .unwrap();
+        fabric.set_interval_label(2, "tendon").unwrap();
+        fabric.intervals[0].attachments[0] = Some(Attachment {
+            hub_index: 3,
+            axial: 0.5,
+            radial: 0.1,
+        });
+        fabric.intervals[1].delay = 7;
+        fabric.intervals[2].disabled = true;
+        fabric.intervals[2].plastic_strain = 0.05;
+        fabric.constraints.push(Constraint::new(0, 3, 3.0));
+        let mut world = World::new();
+        world.add_medium(0.5, 0.1, 1.0).unwrap();
+        world.set_point_gravity(0.0, -10.0, 0.0, 5.0).unwrap();
+        world
+            .set_terrain(-1.0, -1.0, 1.0, 2, &[0.0, 0.1, 0.2, 0.3])
+            .unwrap();
+        world.set_stage_feature(Stage::Pretenst, WorldFeature::Drag, 0.5);
+        world.obstacles.push(Obstacle::Sphere {
+            center: Point3::new(1.0, 2.0, 3.0),
+            radius: 0.5,
+        });
+        world.ground_motion = vec![0.0, 0.1, -0.1];
+        world.integration = Integration::VelocityVerlet;
+        world.remove_broken = [false, true];
+        world.time_step = 0.5;
+        world.shaping_slew_rate = 0.01;
+        world.pull_damping = 0.2;
+        (fabric, world)
+    }
+
+    #[test]
+    fn round_trips_everything_it_stores() {
+        let (fabric, world) = dressed();
+        let bytes = encode(&fabric, &world);
+        let decoded = decode_fabric(&bytes).unwrap();
+        let decoded_world = decode_world(&bytes).unwrap();
+        assert_eq!(encode(&decoded, &decoded_world), bytes);
+        assert_eq!(decoded.labels.get(&2).map(String::as_str), Some("tendon"));
+        assert_eq!(decoded.constraints.len(), 1);
+        assert_eq!(
+            decoded.intervals[0].attachments[0].map(|attachment| attachment.hub_index),
+            Some(3)
+        );
+        // stored as f32, whatever the physics runs in
+        assert_eq!(decoded.intervals[1].gap.map(single), Some(0.8));
+        assert!(decoded.intervals[1].curve.is_some());
+        assert!(decoded.intervals[2].disabled);
+        assert!(decoded.joints[3].friction.is_some());
+        assert_eq!(decoded_world.integration, Integration::VelocityVerlet);
+        assert_eq!(decoded_world.remove_broken, [false, true]);
+        assert!(decoded_world.terrain.is_some());
+        assert_eq!(decoded_world.media.len(), 1);
+        assert_eq!(single(decoded_world.shaping_slew_rate), 0.01);
+        assert_eq!(single(decoded_world.pull_damping), 0.2);
+    }
+
+    #[test]
+    fn still_decodes_version_3() {
+        let (fabric, _) = dressed();
+        let mut bytes = encode_fabric(&fabric);
+        bytes[4] = 3;
+        let decoded = decode_fabric(&bytes).unwrap();
+        assert_eq!(decoded.intervals.len(), 3);
+        assert!(decoded.labels.is_empty());
+    }
+
+    #[test]
+    fn decodes_a_topology_the_state_has_room_for() {
+        let bytes = snapshot(&[
+            (Section::Topology, topology(2)),
+            (Section::State, vec![0_u8; 2 * JOINT_STATE_LENGTH]),
+        ]);
+        assert_eq!(decode_topology(&bytes).unwrap().joints.len(), 2);
+    }
+}
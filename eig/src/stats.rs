@@ -0,0 +1,138 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use nalgebra::*;
+
+use crate::constants::Stage;
+use crate::fabric::Fabric;
+use crate::real::Real;
+use crate::world::World;
+
+// Where the energy of the fabric is after an iterate. Potential energy is measured from the origin,
+// so only its changes mean anything, and it is zero whenever gravity is off.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FabricStats {
+    pub kinetic: Real,
+    pub potential: Real,
+    pub strain: Real,
+    // elastic energy stored in each interval
+    pub interval_strain: Vec<Real>,
+}
+
+impl FabricStats {
+    pub fn total(&self) -> Real {
+        self.kinetic + self.potential + self.strain
+    }
+}
+
+impl Fabric {
+    pub fn stats(&self, world: &World) -> FabricStats {
+        let world = world.in_stage(self.stage);
+        let pretensing_nuance = world.pretensing_nuance(self);
+        let interval_strain: Vec<Real> = self
+            .intervals
+            .iter()
+            .map(|interval| {
+                let ideal_length = interval.ideal_length_now(&world, self.stage, pretensing_nuance);
+                interval.strain_energy(&world, self.stage, ideal_length)
+            })
+            .collect();
+        // gravity only pulls once pretenst, and never on a floating fabric
+        let gravity = if self.stage == Stage::Pretenst && !self.floating {
+            world.gravity
+        } else {
+            0.0
+        };
+        let potential = if gravity == 0.0 {
+            0.0
+        } else {
+            self.joints
+                .iter()
+                .map(|joint| joint.interval_mass * potential(&world, &joint.location, gravity))
+                .sum()
+        };
+        FabricStats {
+            kinetic: self.kinetic_energy(),
+            potential,
+            strain: interval_strain.iter().sum(),
+            interval_strain,
+        }
+    }
+}
+
+// Per unit of mass, matching World::gravity_at: uniform along the direction, or inverse square outside
+// the radius of a point and linear inside it, continuous at the radius.
+fn potential(world: &World, location: &Point3<Real>, gravity: Real) -> Real {
+    let Some((center, radius)) = world.point_gravity else {
+        return -gravity * world.gravity_direction.dot(&location.coords);
+    };
+    let distance = (location - center).magnitude();
+    if distance < radius {
+        gravity * (distance * distance / radius - 3.0 * radius) / 2.0
+    } else {
+        -gravity * radius * radius / distance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::IntervalRole;
+    use crate::fixture::joints;
+
+    use super::*;
+
+    // The pull of gravity is the potential falling away, everywhere.
+    #[test]
+    fn the_potential_falls_the_way_gravity_pulls() {
+        let mut world = World::new();
+        let check = |world: &World, location: Point3<Real>| {
+            let step = 1e-2;
+            for axis in 0..3 {
+                let mut ahead = location;
+                ahead[axis] += step;
+                let mut behind = location;
+                behind[axis] -= step;
+                let slope =
+                    (potential(world, &ahead, 1.0) - potential(world, &behind, 1.0)) / (2.0 * step);
+                let pull = world.gravity_at(&location, 1.0)[axis];
+                assert!(
+                    (slope + pull).abs() < 1e-2,
+                    "{} {} {}",
+                    location,
+                    slope,
+                    pull
+                );
+            }
+        };
+        check(&world, Point3::new(1.0, 2.0, 3.0));
+        world.set_point_gravity(0.0, 0.0, 0.0, 2.0).unwrap();
+        check(&world, Point3::new(0.5, 1.0, 0.0));
+        check(&world, Point3::new(3.0, 0.0, 4.0));
+        let inside = potential(&world, &Point3::new(0.0, 2.0 - 1e-4, 0.0), 1.0);
+        let outside = potential(&world, &Point3::new(0.0, 2.0 + 1e-4, 0.0), 1.0);
+        assert!((inside - outside).abs() < 1e-3, "{} {}", inside, outside);
+    }
+
+    #[test]
+    fn adds_up_strain_per_interval_and_leaves_potential_out_until_pretenst() {
+        let world = World::new();
+        let mut fabric = joints(3);
+        fabric
+            .add_interval_safe(0, 1, IntervalRole::Pull, 1.0, 0.5, 0.0)
+            .unwrap();
+        fabric
+            .add_interval_safe(1, 2, IntervalRole::Pull, 1.0, 1.0, 0.0)
+            .unwrap();
+        fabric.iterate_ticks(&world, 1);
+        let stats = fabric.stats(&world);
+        assert_eq!(stats.interval_strain.len(), 2);
+        assert!(stats.interval_strain[0] > stats.interval_strain[1]);
+        assert_eq!(stats.strain, stats.interval_strain.iter().sum::<Real>());
+        assert_eq!(stats.potential, 0.0);
+        assert_eq!(stats.total(), stats.kinetic + stats.strain);
+        fabric.stage = Stage::Pretenst;
+        assert!(fabric.stats(&world).potential > 0.0);
+    }
+}
@@ -0,0 +1,131 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use nalgebra::*;
+
+use crate::error::Error;
+use crate::real::Real;
+
+// The ground joints rest on: its height and upward normal under any horizontal position.
+pub trait Surface {
+    fn height(&self, x: Real, z: Real) -> Real;
+
+    fn normal(&self, x: Real, z: Real) -> Vector3<Real>;
+}
+
+pub struct Flat;
+
+impl Surface for Flat {
+    fn height(&self, _x: Real, _z: Real) -> Real {
+        0.0
+    }
+
+    fn normal(&self, _x: Real, _z: Real) -> Vector3<Real> {
+        Vector3::y()
+    }
+}
+
+// Heights sampled on a square grid, row by row along z with columns along x, starting at the origin.
+// Beyond the edges the border samples carry on.
+#[derive(Clone, Debug)]
+pub struct Heightmap {
+    pub(crate) origin_x: Real,
+    pub(crate) origin_z: Real,
+    pub(crate) spacing: Real,
+    pub(crate) columns: usize,
+    pub(crate) heights: Vec<Real>,
+    normals: Vec<Vector3<Real>>,
+}
+
+impl Heightmap {
+    pub fn new(
+        origin_x: Real,
+        origin_z: Real,
+        spacing: Real,
+        columns: usize,
+        heights: &[Real],
+    ) -> Result<Heightmap, Error> {
+        if !(spacing.is_finite() && spacing > 0.0) {
+            return Err(Error::IllegalLength { length: spacing });
+        }
+        let rows = if columns < 2 {
+            0
+        } else {
+            heights.len() / columns
+        };
+        if rows < 2 || heights.len() != rows * columns {
+            return Err(Error::WrongLength {
+                expected: rows.max(2) * columns.max(2),
+                actual: heights.len(),
+            });
+        }
+        if let Some(&position) = heights.iter().find(|height| !height.is_finite()) {
+            return Err(Error::IllegalPosition { position });
+        }
+        let sample = |column: usize, row: usize| {
+            heights[row.min(rows - 1) * columns + column.min(columns - 1)]
+        };
+        // central differences inside, one-sided along the border
+        let normals = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column, row)))
+            .map(|(column, row)| {
+                let (left, right) = (column.saturating_sub(1), column + 1);
+                let (back, front) = (row.saturating_sub(1), row + 1);
+                let run_x = (right.min(columns - 1) - left) as Real * spacing;
+                let run_z = (front.min(rows - 1) - back) as Real * spacing;
+                let slope_x = (sample(right, row) - sample(left, row)) / run_x;
+                let slope_z = (sample(column, front) - sample(column, back)) / run_z;
+                Vector3::new(-slope_x, 1.0, -slope_z).normalize()
+            })
+            .collect();
+        Ok(Heightmap {
+            origin_x,
+            origin_z,
+            spacing,
+            columns,
+            heights: heights.to_vec(),
+            normals,
+        })
+    }
+
+    // The four samples around the position with their bilinear weights.
+    fn cell(&self, x: Real, z: Real) -> [(usize, Real); 4] {
+        let rows = self.heights.len() / self.columns;
+        let locate = |position: Real, count: usize| {
+            let position = (position / self.spacing).clamp(0.0, (count - 1) as Real);
+            let index = (position as usize).min(count - 2);
+            (index, position - index as Real)
+        };
+        let (column, along_x) = locate(x - self.origin_x, self.columns);
+        let (row, along_z) = locate(z - self.origin_z, rows);
+        let at = |column: usize, row: usize| row * self.columns + column;
+        [
+            (at(column, row), (1.0 - along_x) * (1.0 - along_z)),
+            (at(column + 1, row), along_x * (1.0 - along_z)),
+            (at(column, row + 1), (1.0 - along_x) * along_z),
+            (at(column + 1, row + 1), along_x * along_z),
+        ]
+    }
+}
+
+impl Surface for Heightmap {
+    fn height(&self, x: Real, z: Real) -> Real {
+        self.cell(x, z)
+            .iter()
+            .map(|&(index, weight)| self.heights[index] * weight)
+            .sum()
+    }
+
+    fn normal(&self, x: Real, z: Real) -> Vector3<Real> {
+        let normal: Vector3<Real> = self
+            .cell(x, z)
+            .iter()
+            .map(|&(index, weight)| self.normals[index] * weight)
+            .sum();
+        normal
+            .try_normalize(Real::EPSILON)
+            .unwrap_or_else(Vector3::y)
+    }
+}
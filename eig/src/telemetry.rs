@@ -0,0 +1,81 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+// Native only: publishes through the `metrics` facade, so any exporter (Prometheus etc.) installed
+// by the host program picks these up.
+
+use std::time::Instant;
+
+use metrics::{counter, gauge};
+
+use crate::fabric::Fabric;
+use crate::real::Real;
+use crate::world::World;
+
+#[derive(Default)]
+pub struct MetricsRecorder {
+    last: Option<(Instant, u32)>,
+}
+
+impl MetricsRecorder {
+    pub fn new() -> MetricsRecorder {
+        MetricsRecorder::default()
+    }
+
+    // Call once per iterate or so; rates are measured between calls.
+    pub fn record(&mut self, fabric: &Fabric, world: &World) {
+        let now = Instant::now();
+        if let Some((then, age)) = self.last {
+            let ticks = fabric.age.saturating_sub(age);
+            counter!("eig_ticks_total").increment(ticks as u64);
+            let seconds = now.duration_since(then).as_secs_f64();
+            if seconds > 0_f64 && world.iterations_per_frame > 0.0 {
+                let iterations = ticks as f64 / world.iterations_per_frame as f64;
+                gauge!("eig_iterations_per_second").set(iterations / seconds);
+            }
+        }
+        self.last = Some((now, fabric.age));
+        let max_strain = fabric
+            .intervals
+            .iter()
+            .map(|interval| interval.strain.abs())
+            .fold(0.0, Real::max);
+        gauge!("eig_max_strain").set(max_strain as f64);
+        gauge!("eig_stage").set(fabric.stage as u8 as f64);
+        gauge!("eig_sleeping_joints").set(fabric.get_sleeping_joint_count(world) as f64);
+        gauge!("eig_joints").set(fabric.joints.len() as f64);
+        gauge!("eig_intervals").set(fabric.intervals.len() as f64);
+        gauge!("eig_actuation_energy").set(fabric.get_actuation_energy() as f64);
+        gauge!("eig_actuation_work").set(fabric.get_actuation_work() as f64);
+        let stats = fabric.stats(world);
+        gauge!("eig_kinetic_energy").set(stats.kinetic as f64);
+        gauge!("eig_potential_energy").set(stats.potential as f64);
+        gauge!("eig_strain_energy").set(stats.strain as f64);
+        if let Some((_, _, momentum)) = fabric.momentum_drift() {
+            gauge!("eig_linear_momentum").set(momentum.linear.magnitude() as f64);
+            gauge!("eig_angular_momentum").set(momentum.angular.magnitude() as f64);
+            gauge!("eig_linear_momentum_drift").set(fabric.get_linear_momentum_drift() as f64);
+            gauge!("eig_angular_momentum_drift").set(fabric.get_angular_momentum_drift() as f64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_ticks_from_the_age_at_the_last_record() {
+        let mut fabric = Fabric::new(0);
+        fabric.create_joint(0.0, 1.0, 0.0).unwrap();
+        let world = World::new();
+        let mut recorder = MetricsRecorder::new();
+        recorder.record(&fabric, &world);
+        assert_eq!(recorder.last.map(|(_, age)| age), Some(0));
+        fabric.iterate_ticks(&world, 10);
+        recorder.record(&fabric, &world);
+        assert_eq!(recorder.last.map(|(_, age)| age), Some(10));
+    }
+}
@@ -0,0 +1,245 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use crate::error::Error;
+use crate::real::Real;
+use crate::tenscript::{FaceName, TenscriptNode};
+
+// Twists in a whole growth, counting the first.
+const MAX_TWISTS: usize = 1000;
+
+// The joints of a twist: the base it stands on and the top, corner by corner, the pushes leaning
+// from each base corner to the next top corner.
+#[derive(Clone, Copy, Debug)]
+pub struct Twist {
+    pub(crate) base: [usize; 3],
+    pub(crate) top: [usize; 3],
+}
+
+impl Twist {
+    // Each side is a quad split into two triangles of pulls along the diagonal without a push, the
+    // upper one for A+, B+ or C+ and the lower for A-, B- or C-. A twist has no D faces.
+    pub fn face(&self, name: FaceName) -> Option<[usize; 3]> {
+        let (side, upper) = match name {
+            FaceName::Aplus => (0, true),
+            FaceName::Bplus => (1, true),
+            FaceName::Cplus => (2, true),
+            FaceName::Aminus => (0, false),
+            FaceName::Bminus => (1, false),
+            FaceName::Cminus => (2, false),
+            _ => return None,
+        };
+        let next = (side + 1) % 3;
+        Some(if upper {
+            [self.base[next], self.top[next], self.top[side]]
+        } else {
+            [self.base[side], self.base[next], self.top[side]]
+        })
+    }
+}
+
+// A face still to be grown from, with the twists left to go, each scaled against the one before,
+// and what grows off the sides of the last.
+#[derive(Clone, Debug)]
+pub struct Bud {
+    pub(crate) face: [usize; 3],
+    pub(crate) remaining: usize,
+    pub(crate) scale: Real,
+    pub(crate) size: Real,
+    pub(crate) then: Option<TenscriptNode>,
+}
+
+impl Bud {
+    // What a node grows off the sides of a twist.
+    pub fn sprout(node: &TenscriptNode, twist: &Twist, size: Real) -> Vec<Bud> {
+        match node {
+            TenscriptNode::Grow {
+                face,
+                forward,
+                scale,
+                branch,
+                ..
+            } => twist
+                .face(*face)
+                .map(|face| Bud {
+                    face,
+                    remaining: forward.len(),
+                    scale: scale.unwrap_or(1.0) as Real,
+                    size,
+                    then: branch.as_deref().cloned(),
+                })
+                .into_iter()
+                .collect(),
+            TenscriptNode::Branch { subtrees } => subtrees
+                .iter()
+                .flat_map(|subtree| Bud::sprout(subtree, twist, size))
+                .collect(),
+        }
+    }
+}
+
+// Every bud grows one twist at a time, the spacing in ticks apart, so each has a while to settle
+// before the next goes on top of it. The first twist stands on the face growth starts from, and the
+// code grows off its sides.
+#[derive(Clone, Debug)]
+pub struct Growth {
+    pub(crate) buds: Vec<Bud>,
+    pub(crate) spacing: u32,
+    pub(crate) countdown: u32,
+}
+
+impl Growth {
+    pub fn new(face: [usize; 3], node: TenscriptNode, spacing: u32) -> Result<Growth, Error> {
+        check(&node)?;
+        let twists = 1 + count_twists(&node);
+        if twists > MAX_TWISTS {
+            return Err(Error::IllegalGrowth {
+                reason: format!("{twists} twists, more than {MAX_TWISTS}"),
+            });
+        }
+        let seed = Bud {
+            face,
+            remaining: 1,
+            scale: 1.0,
+            size: 1.0,
+            then: Some(node),
+        };
+        Ok(Growth {
+            buds: vec![seed],
+            spacing,
+            countdown: 0,
+        })
+    }
+
+    pub fn joint_removed(&mut self, index: usize) {
+        self.buds.retain(|bud| !bud.face.contains(&index));
+        for bud in self.buds.iter_mut() {
+            for joint in bud.face.iter_mut().filter(|joint| **joint > index) {
+                *joint -= 1;
+            }
+        }
+    }
+}
+
+fn check(node: &TenscriptNode) -> Result<(), Error> {
+    match node {
+        TenscriptNode::Grow {
+            face,
+            scale,
+            branch,
+            ..
+        } => {
+            let probe = Twist {
+                base: [0, 1, 2],
+                top: [3, 4, 5],
+            };
+            if probe.face(*face).is_none() {
+                return Err(Error::IllegalGrowth {
+                    reason: format!("a twist has no face {face}"),
+                });
+            }
+            if let Some(scale) = scale {
+                if !(scale.is_finite() && *scale > 0.0) {
+                    return Err(Error::IllegalGrowth {
+                        reason: format!("scale {scale}"),
+                    });
+                }
+            }
+            branch.as_deref().map_or(Ok(()), check)
+        }
+        TenscriptNode::Branch { subtrees } => subtrees.iter().try_for_each(check),
+    }
+}
+
+fn count_twists(node: &TenscriptNode) -> usize {
+    match node {
+        TenscriptNode::Grow {
+            forward, branch, ..
+        } => forward.len() + branch.as_deref().map_or(0, count_twists),
+        TenscriptNode::Branch { subtrees } => subtrees.iter().map(count_twists).sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fabric::Fabric;
+    use crate::tenscript::parse_growth;
+    use crate::world::World;
+
+    use super::*;
+
+    fn triangle() -> Fabric {
+        let mut fabric = Fabric::new(0);
+        for (x, z) in [(1.0, 0.0), (-0.5, 0.866), (-0.5, -0.866)] {
+            fabric.create_joint(x, 1.0, z).unwrap();
+        }
+        fabric.create_face(0, 2, 1).unwrap();
+        fabric
+    }
+
+    fn grow(fabric: &mut Fabric) {
+        let world = World::new();
+        while fabric.is_growing() {
+            fabric.iterate(&world);
+        }
+    }
+
+    #[test]
+    fn parses_forward_counts_branches_and_scale() {
+        let node = parse_growth("(branch (grow A+ 3) (grow B+ 5 (scale 90%)))").unwrap();
+        let TenscriptNode::Branch { subtrees } = &node else {
+            panic!("expected a branch, got {:?}", node);
+        };
+        assert_eq!(subtrees.len(), 2);
+        let TenscriptNode::Grow { forward, scale, .. } = &subtrees[1] else {
+            panic!("expected a grow, got {:?}", subtrees[1]);
+        };
+        assert_eq!(forward.len(), 5);
+        assert_eq!(*scale, Some(0.9));
+        assert_eq!(count_twists(&node), 8);
+    }
+
+    #[test]
+    fn rejects_runaway_forward_counts() {
+        assert!(parse_growth("(grow A+ 99999999)").is_err());
+        assert!(parse_growth("(grow A+ 0)").is_err());
+    }
+
+    #[test]
+    fn rejects_unterminated_code() {
+        assert!(parse_growth("(grow A+ 3").is_err());
+        assert!(parse_growth("(grow \"A+ 3)").is_err());
+    }
+
+    #[test]
+    fn grows_one_twist_per_count_and_caps_the_end() {
+        let mut fabric = triangle();
+        fabric.start_growth(0, "(grow A+ 2)", 0).unwrap();
+        grow(&mut fabric);
+        // the seed twist and two more, each adding three joints, three pushes and six pulls, plus the
+        // three base pulls of the seed and the one pull closing the side face the growth goes on from
+        assert_eq!(fabric.joints.len(), 12);
+        assert_eq!(fabric.intervals.len(), 3 + 3 * 9 + 1);
+        assert_eq!(
+            fabric
+                .intervals
+                .iter()
+                .filter(|interval| interval.push)
+                .count(),
+            9
+        );
+        assert_eq!(fabric.faces.len(), 1);
+    }
+
+    #[test]
+    fn leaves_the_fabric_alone_when_a_twist_would_exceed_the_degree() {
+        let mut fabric = triangle();
+        fabric.set_topology_rules(3, 0);
+        fabric.start_growth(0, "(grow A+)", 0).unwrap();
+        grow(&mut fabric);
+        assert_eq!(fabric.joints.len(), 3);
+        assert!(fabric.intervals.is_empty());
+    }
+}
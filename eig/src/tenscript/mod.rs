@@ -1,8 +1,9 @@
 mod error;
+pub mod growth;
+mod output;
 mod parser;
 mod scanner;
 mod sexp;
-mod output;
 
-pub use parser::parse;
-pub use output::*;
\ No newline at end of file
+pub use output::*;
+pub use parser::{parse, parse_growth};
@@ -35,7 +35,6 @@ pub enum VulcanizeType {
     Snelson,
 }
 
-
 #[derive(Debug, Clone, Copy)]
 pub enum SurfaceCharacter {
     Frozen,
@@ -62,6 +61,8 @@ pub enum TenscriptNode {
     Grow {
         face: FaceName,
         forward: String,
+        // each twist against the one before
+        scale: Option<f64>,
         branch: Option<Box<TenscriptNode>>,
         marks: Vec<Mark>,
     },
@@ -93,6 +94,7 @@ pub struct Features {
     pub antigravity: Option<f64>,
     pub interval_countdown: Option<f64>,
     pub pretensing_countdown: Option<f64>,
+    pub shaping_slew_rate: Option<f64>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -2,8 +2,8 @@ use std::fmt::{Debug, Display, Formatter};
 
 use crate::tenscript::error::Error;
 use crate::tenscript::scanner;
+use crate::tenscript::scanner::Token::{Atom, Float, Ident, Integer, Paren, Percent, EOF};
 use crate::tenscript::scanner::{ScannedToken, Token};
-use crate::tenscript::scanner::Token::{Atom, EOF, Float, Ident, Integer, Paren, Percent};
 use crate::tenscript::sexp::ErrorKind::{ConsumeFailed, MatchExhausted};
 
 #[derive(Clone)]
@@ -23,7 +23,6 @@ impl Debug for Sexp {
     }
 }
 
-
 impl Display for Sexp {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -74,29 +73,27 @@ struct Parser {
     index: usize,
 }
 
-
 impl Parser {
     pub fn new(tokens: Vec<ScannedToken>) -> Self {
-        Self {
-            tokens,
-            index: 0,
-        }
+        Self { tokens, index: 0 }
     }
 
     pub fn parse(mut self) -> Result<Sexp, ParseError> {
-        self.sexp()
-            .map_err(|kind| ParseError { kind, token: self.current_scanned().clone() })
+        self.sexp().map_err(|kind| ParseError {
+            kind,
+            token: self.current_scanned().clone(),
+        })
     }
 
+    // the EOF at the end once past it
     fn current_scanned(&self) -> &ScannedToken {
-        &self.tokens[self.index]
+        &self.tokens[self.index.min(self.tokens.len() - 1)]
     }
 
     fn current(&self) -> &Token {
         &self.current_scanned().tok
     }
 
-
     fn increment(&mut self) {
         self.index += 1;
     }
@@ -105,20 +102,13 @@ impl Parser {
         let token = self.current().clone();
         self.increment();
         match token {
-            Paren('(') =>
-                self.list(),
-            Ident(name) =>
-                Ok(Sexp::Ident(name)),
-            Float(value) =>
-                Ok(Sexp::Float(value)),
-            Integer(value) =>
-                Ok(Sexp::Integer(value)),
-            Percent(value) =>
-                Ok(Sexp::Percent(value)),
-            Atom(value) =>
-                Ok(Sexp::Atom(value)),
-            Token::String(value) =>
-                Ok(Sexp::String(value)),
+            Paren('(') => self.list(),
+            Ident(name) => Ok(Sexp::Ident(name)),
+            Float(value) => Ok(Sexp::Float(value)),
+            Integer(value) => Ok(Sexp::Integer(value)),
+            Percent(value) => Ok(Sexp::Percent(value)),
+            Atom(value) => Ok(Sexp::Atom(value)),
+            Token::String(value) => Ok(Sexp::String(value)),
             _ => Err(MatchExhausted),
         }
     }
@@ -130,9 +120,11 @@ impl Parser {
             terms.push(term);
         }
         let Paren(')') = self.current() else {
-            return Err(ConsumeFailed { expected: "right paren" });
+            return Err(ConsumeFailed {
+                expected: "right paren",
+            });
         };
         self.increment();
         Ok(Sexp::List(terms))
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,71 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+// These compile away unless the `tracing` feature is on.
+
+macro_rules! trace_info {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        {
+            tracing::info!($($arg)*);
+        }
+    };
+}
+
+macro_rules! trace_debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        {
+            tracing::debug!($($arg)*);
+        }
+    };
+}
+
+macro_rules! trace_trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "tracing")]
+        {
+            tracing::trace!($($arg)*);
+        }
+    };
+}
+
+#[cfg(all(feature = "tracing", target_arch = "wasm32"))]
+#[wasm_bindgen::prelude::wasm_bindgen]
+pub fn init_console_tracing() {
+    tracing_wasm::set_as_global_default();
+}
+
+#[cfg(test)]
+mod tests {
+    // nothing in the fields is worked out unless something traces
+    #[test]
+    #[cfg(not(feature = "tracing"))]
+    fn fields_cost_nothing_without_the_feature() {
+        let evaluated = std::cell::Cell::new(false);
+        trace_info!(
+            value = {
+                evaluated.set(true);
+                1
+            },
+            "message"
+        );
+        trace_debug!(
+            value = {
+                evaluated.set(true);
+                2
+            },
+            "message"
+        );
+        trace_trace!(
+            value = {
+                evaluated.set(true);
+                3
+            },
+            "message"
+        );
+        assert!(!evaluated.get());
+    }
+}
@@ -3,18 +3,102 @@
  * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
  */
 
+use crate::constants::*;
 use crate::fabric::{Fabric, DEFAULT_STRAIN_LIMITS};
+use crate::joint::Joint;
+use crate::real::{single, Real};
 use crate::world::World;
 use nalgebra::*;
 use wasm_bindgen::prelude::*;
 
+// Channel layouts only change with VIEW_VERSION; a frontend asks for the channels it knows instead
+// of assuming which buffers come filled.
+pub const VIEW_VERSION: u32 = 1;
+
+const VIEW_CHANNELS: [ViewChannel; 22] = [
+    ViewChannel::JointLocations,
+    ViewChannel::JointVelocities,
+    ViewChannel::JointColors,
+    ViewChannel::JointSizes,
+    ViewChannel::LineLocations,
+    ViewChannel::LineColors,
+    ViewChannel::ShadowLocations,
+    ViewChannel::FaceMidpoints,
+    ViewChannel::FaceNormals,
+    ViewChannel::FaceVertexLocations,
+    ViewChannel::UnitVectors,
+    ViewChannel::IdealLengths,
+    ViewChannel::Strains,
+    ViewChannel::StrainLimits,
+    ViewChannel::StrainNuances,
+    ViewChannel::Stiffnesses,
+    ViewChannel::LinearDensities,
+    ViewChannel::Alarms,
+    ViewChannel::AxialForces,
+    ViewChannel::ForceVectors,
+    ViewChannel::CalloutLocations,
+    ViewChannel::CalloutIntervals,
+];
+
+// A channel holds stride scalars per element, and is only filled by render when enabled.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChannelLayout {
+    pub channel: ViewChannel,
+    pub enabled: bool,
+    pub element: ViewElement,
+    pub scalar: ViewScalar,
+    pub stride: u8,
+}
+
+// Stage feedback every frontend should show the same way.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewStyle {
+    pub background_red: Real,
+    pub background_green: Real,
+    pub background_blue: Real,
+    pub strain_colors: bool,
+    pub pretenst_progress: Real,
+}
+
+impl ViewStyle {
+    fn of(fabric: &Fabric, world: &World) -> ViewStyle {
+        let pretenst_progress = match fabric.stage {
+            Stage::Growing | Stage::Shaping | Stage::Slack => 0.0,
+            Stage::Pretensing => world.pretensing_nuance(fabric).clamp(0.0, 1.0),
+            Stage::Pretenst => 1.0,
+        };
+        let (red, green, blue) = match fabric.stage {
+            Stage::Growing => (0.0, 0.0, 0.0),
+            Stage::Shaping => (0.0, 0.02, 0.06),
+            Stage::Slack => (0.04, 0.04, 0.04),
+            Stage::Pretensing | Stage::Pretenst => {
+                (0.06 * pretenst_progress, 0.02 * pretenst_progress, 0.0)
+            }
+        };
+        let floating = fabric.floating || world.gravity == 0.0;
+        ViewStyle {
+            background_red: red,
+            background_green: green,
+            background_blue: if floating { blue + 0.04 } else { blue },
+            strain_colors: fabric.stage >= Stage::Pretensing && world.push_and_pull,
+            pretenst_progress,
+        }
+    }
+}
+
 #[wasm_bindgen]
 pub struct View {
-    pub(crate) midpoint: Point3<f32>,
-    pub(crate) mass: f32,
-    pub(crate) radius: f32,
+    pub(crate) style: ViewStyle,
+    pub(crate) midpoint: Point3<Real>,
+    pub(crate) mass: Real,
+    pub(crate) radius: Real,
     pub(crate) joint_locations: Vec<f32>,
     pub(crate) joint_velocities: Vec<f32>,
+    pub(crate) joint_coloring: JointColoring,
+    pub(crate) joint_colors: Vec<f32>,
+    pub(crate) joint_sizes: Vec<f32>,
     pub(crate) line_locations: Vec<f32>,
     pub(crate) line_colors: Vec<f32>,
     pub(crate) face_midpoints: Vec<f32>,
@@ -25,8 +109,20 @@ pub struct View {
     pub(crate) strains: Vec<f32>,
     pub(crate) strain_limits: Vec<f32>,
     pub(crate) strain_nuances: Vec<f32>,
+    pub(crate) strain_scale: StrainScale,
+    pub(crate) strain_percentile: Real,
     pub(crate) stiffnesses: Vec<f32>,
     pub(crate) linear_densities: Vec<f32>,
+    pub(crate) alarms: Vec<u8>,
+    pub(crate) shadows: bool,
+    pub(crate) shadow_locations: Vec<f32>,
+    pub(crate) explode: Real,
+    pub(crate) forces: bool,
+    pub(crate) axial_forces: Vec<f32>,
+    pub(crate) force_vectors: Vec<f32>,
+    pub(crate) callouts: Option<(Vector3<Real>, Real)>,
+    pub(crate) callout_locations: Vec<f32>,
+    pub(crate) callout_intervals: Vec<u32>,
 }
 
 #[wasm_bindgen]
@@ -36,11 +132,21 @@ impl View {
         let interval_count = fabric.get_interval_count() as usize;
         let face_count = fabric.get_face_count() as usize;
         View {
+            style: ViewStyle {
+                background_red: 0.0,
+                background_green: 0.0,
+                background_blue: 0.0,
+                strain_colors: false,
+                pretenst_progress: 0.0,
+            },
             midpoint: Point3::origin(),
-            mass: 0_f32,
-            radius: 2_f32,
+            mass: 0.0,
+            radius: 2.0,
             joint_locations: Vec::with_capacity(joint_count * 3),
             joint_velocities: Vec::with_capacity(joint_count * 3),
+            joint_coloring: JointColoring::Off,
+            joint_colors: Vec::with_capacity(joint_count * 3),
+            joint_sizes: Vec::with_capacity(joint_count),
             line_locations: Vec::with_capacity(interval_count * 2 * 3),
             line_colors: Vec::with_capacity(interval_count * 2 * 3),
             face_midpoints: Vec::with_capacity(face_count * 3),
@@ -49,21 +155,42 @@ impl View {
             unit_vectors: Vec::with_capacity(interval_count * 3),
             ideal_lengths: Vec::with_capacity(interval_count),
             strains: Vec::with_capacity(interval_count),
-            strain_limits: DEFAULT_STRAIN_LIMITS.to_vec(),
+            strain_limits: DEFAULT_STRAIN_LIMITS.map(single).to_vec(),
             strain_nuances: Vec::with_capacity(interval_count),
+            strain_scale: StrainScale::PerRole,
+            strain_percentile: 1.0,
             stiffnesses: Vec::with_capacity(interval_count),
             linear_densities: Vec::with_capacity(interval_count),
+            alarms: Vec::with_capacity(interval_count),
+            shadows: false,
+            shadow_locations: Vec::new(),
+            explode: 0.0,
+            forces: false,
+            axial_forces: Vec::new(),
+            force_vectors: Vec::new(),
+            callouts: None,
+            callout_locations: Vec::new(),
+            callout_intervals: Vec::new(),
         }
     }
 
     pub fn render(&mut self, fabric: &Fabric, world: &World) {
+        let world = &*world.in_stage(fabric.stage);
         self.clear();
-        for joint in fabric.joints.iter() {
+        self.style = ViewStyle::of(fabric, world);
+        let exploded;
+        let joints = if self.explode > 0.0 {
+            exploded = exploded_joints(fabric, self.explode);
+            &exploded
+        } else {
+            &fabric.joints
+        };
+        for joint in joints.iter() {
             joint.project(self);
         }
         self.midpoint /= self.mass;
-        let mut radius_squared = 0_f32;
-        for joint in fabric.joints.iter() {
+        let mut radius_squared = 0.0;
+        for joint in joints.iter() {
             let from_midpoint = &joint.location - &self.midpoint;
             let squared = from_midpoint.magnitude_squared();
             if radius_squared < squared {
@@ -72,12 +199,18 @@ impl View {
         }
         self.radius = radius_squared.sqrt();
         let pretensing_nuance = world.pretensing_nuance(fabric);
-        for interval in fabric.intervals.iter() {
-            let current_length = interval.calculate_current_length(&fabric.joints) + 0.01_f32;
+        let strain_limits = strain_limits(fabric, self.strain_scale, self.strain_percentile);
+        let strain_nuances: Vec<Real> = fabric
+            .intervals
+            .iter()
+            .map(|interval| interval.calculate_strain_nuance(&strain_limits))
+            .collect();
+        for (interval, &strain_nuance) in fabric.intervals.iter().zip(strain_nuances.iter()) {
+            let current_length = interval.calculate_current_length(&fabric.joints) + 0.01;
             let ideal_length = interval.ideal_length_now(world, fabric.stage, pretensing_nuance);
             let slack_pull = !interval.push && ideal_length > current_length;
             let extend = if slack_pull {
-                0_f32
+                0.0
             } else {
                 interval.strain * ideal_length * world.visual_strain
             };
@@ -86,38 +219,160 @@ impl View {
             } else {
                 extend
             };
-            interval.project_line_locations(self, &fabric.joints, bounded_extend / -2_f32);
-            interval.project_line_features(self, ideal_length)
+            interval.project_line_locations(self, joints, bounded_extend / -2.0);
+            interval.project_line_features(self, ideal_length, strain_nuance);
+            if self.forces {
+                interval.project_force(self, world, fabric.stage);
+            }
         }
-        self.strain_limits = fabric.strain_limits.to_vec();
-        for interval in fabric.intervals.iter() {
-            interval.project_line_color_nuance(self)
+        self.strain_limits = strain_limits.map(single).to_vec();
+        for (interval, &strain_nuance) in fabric.intervals.iter().zip(strain_nuances.iter()) {
+            interval.project_line_color_nuance(self, strain_nuance)
         }
         for face in fabric.faces.iter() {
-            face.project_features(&fabric.joints, self)
+            face.project_features(joints, self)
+        }
+        if self.joint_coloring != JointColoring::Off {
+            self.render_joint_colors(fabric, world);
+        }
+        if let Some((camera, offset)) = self.callouts {
+            self.render_callouts(fabric, joints, camera, offset);
+        }
+    }
+
+    pub fn set_joint_coloring(&mut self, joint_coloring: JointColoring) {
+        self.joint_coloring = joint_coloring;
+    }
+
+    // Line endpoints dropped straight down onto the ground, filled in by render when enabled.
+    pub fn set_shadows(&mut self, shadows: bool) {
+        self.shadows = shadows;
+    }
+
+    // Assembly diagrams: every group of struts sharing a tag is drawn pushed away from the middle by the
+    // factor times its own distance from it. Zero draws the fabric as it is.
+    pub fn set_explode(&mut self, factor: Real) {
+        self.explode = factor.max(0.0);
+    }
+
+    // The strain limits buffer holds the resulting ranges for a legend. The percentile, from a half to
+    // one, only matters for the percentile scale.
+    pub fn set_strain_scale(&mut self, strain_scale: StrainScale, percentile: Real) {
+        self.strain_scale = strain_scale;
+        self.strain_percentile = percentile.clamp(0.5, 1.0);
+    }
+
+    // Signed axial force per interval and the force along the interval's unit vector, for load paths.
+    pub fn set_forces(&mut self, forces: bool) {
+        self.forces = forces;
+    }
+
+    // Anchor points for labelled intervals: the midpoint pushed sideways, across both the interval and
+    // the direction the camera looks in, so the label clears the line on screen.
+    pub fn set_callouts(&mut self, camera_x: Real, camera_y: Real, camera_z: Real, offset: Real) {
+        let camera = Vector3::new(camera_x, camera_y, camera_z);
+        self.callouts = camera
+            .try_normalize(Real::EPSILON)
+            .map(|camera| (camera, offset));
+    }
+
+    pub fn clear_callouts(&mut self) {
+        self.callouts = None;
+    }
+
+    pub fn get_callout_count(&self) -> u16 {
+        self.callout_intervals.len() as u16
+    }
+
+    pub fn get_style(&self) -> ViewStyle {
+        self.style
+    }
+
+    pub fn view_version() -> u32 {
+        VIEW_VERSION
+    }
+
+    pub fn get_channel_count() -> u8 {
+        VIEW_CHANNELS.len() as u8
+    }
+
+    pub fn get_capability(&self, channel: ViewChannel) -> ChannelLayout {
+        let (element, scalar, stride) = match channel {
+            ViewChannel::JointLocations => (ViewElement::Joint, ViewScalar::F32, 3),
+            ViewChannel::JointVelocities => (ViewElement::Joint, ViewScalar::F32, 3),
+            ViewChannel::JointColors => (ViewElement::Joint, ViewScalar::F32, 3),
+            ViewChannel::JointSizes => (ViewElement::Joint, ViewScalar::F32, 1),
+            ViewChannel::LineLocations => (ViewElement::Interval, ViewScalar::F32, 6),
+            ViewChannel::LineColors => (ViewElement::Interval, ViewScalar::F32, 6),
+            ViewChannel::ShadowLocations => (ViewElement::Interval, ViewScalar::F32, 6),
+            ViewChannel::FaceMidpoints => (ViewElement::Face, ViewScalar::F32, 3),
+            ViewChannel::FaceNormals => (ViewElement::Face, ViewScalar::F32, 9),
+            ViewChannel::FaceVertexLocations => (ViewElement::Face, ViewScalar::F32, 9),
+            ViewChannel::UnitVectors => (ViewElement::Interval, ViewScalar::F32, 3),
+            ViewChannel::IdealLengths => (ViewElement::Interval, ViewScalar::F32, 1),
+            ViewChannel::Strains => (ViewElement::Interval, ViewScalar::F32, 1),
+            ViewChannel::StrainLimits => (ViewElement::Fabric, ViewScalar::F32, 4),
+            ViewChannel::StrainNuances => (ViewElement::Interval, ViewScalar::F32, 1),
+            ViewChannel::Stiffnesses => (ViewElement::Interval, ViewScalar::F32, 1),
+            ViewChannel::LinearDensities => (ViewElement::Interval, ViewScalar::F32, 1),
+            ViewChannel::Alarms => (ViewElement::Interval, ViewScalar::U8, 1),
+            ViewChannel::AxialForces => (ViewElement::Interval, ViewScalar::F32, 1),
+            ViewChannel::ForceVectors => (ViewElement::Interval, ViewScalar::F32, 3),
+            ViewChannel::CalloutLocations => (ViewElement::Callout, ViewScalar::F32, 3),
+            ViewChannel::CalloutIntervals => (ViewElement::Callout, ViewScalar::U32, 1),
+        };
+        let enabled = match channel {
+            ViewChannel::JointColors | ViewChannel::JointSizes => {
+                self.joint_coloring != JointColoring::Off
+            }
+            ViewChannel::ShadowLocations => self.shadows,
+            ViewChannel::AxialForces | ViewChannel::ForceVectors => self.forces,
+            ViewChannel::CalloutLocations | ViewChannel::CalloutIntervals => {
+                self.callouts.is_some()
+            }
+            _ => true,
+        };
+        ChannelLayout {
+            channel,
+            enabled,
+            element,
+            scalar,
+            stride,
         }
     }
 
-    pub fn midpoint_x(&self) -> f32 {
+    pub fn midpoint_x(&self) -> Real {
         self.midpoint.x
     }
 
-    pub fn midpoint_y(&self) -> f32 {
+    pub fn midpoint_y(&self) -> Real {
         self.midpoint.y
     }
 
-    pub fn midpoint_z(&self) -> f32 {
+    pub fn midpoint_z(&self) -> Real {
         self.midpoint.z
     }
 
-    pub fn radius(&self) -> f32 {
-        if self.radius < 2_f32 {
-            2_f32
+    pub fn radius(&self) -> Real {
+        if self.radius < 2.0 {
+            2.0
         } else {
             self.radius
         }
     }
 
+    pub fn get_joint_count(&self) -> u16 {
+        (self.joint_locations.len() / 3) as u16
+    }
+
+    pub fn get_interval_count(&self) -> u16 {
+        self.ideal_lengths.len() as u16
+    }
+
+    pub fn get_face_count(&self) -> u16 {
+        (self.face_midpoints.len() / 3) as u16
+    }
+
     pub fn copy_joint_locations_to(&self, joint_locations: &mut [f32]) {
         joint_locations.copy_from_slice(&self.joint_locations);
     }
@@ -126,10 +381,30 @@ impl View {
         joint_velocities.copy_from_slice(&self.joint_velocities);
     }
 
+    pub fn copy_joint_colors_to(&self, joint_colors: &mut [f32]) {
+        joint_colors.copy_from_slice(&self.joint_colors);
+    }
+
+    pub fn copy_joint_sizes_to(&self, joint_sizes: &mut [f32]) {
+        joint_sizes.copy_from_slice(&self.joint_sizes);
+    }
+
     pub fn copy_line_locations_to(&self, line_locations: &mut [f32]) {
         line_locations.copy_from_slice(&self.line_locations);
     }
 
+    pub fn copy_shadow_locations_to(&self, shadow_locations: &mut [f32]) {
+        shadow_locations.copy_from_slice(&self.shadow_locations);
+    }
+
+    pub fn copy_callout_locations_to(&self, callout_locations: &mut [f32]) {
+        callout_locations.copy_from_slice(&self.callout_locations);
+    }
+
+    pub fn copy_callout_intervals_to(&self, callout_intervals: &mut [u32]) {
+        callout_intervals.copy_from_slice(&self.callout_intervals);
+    }
+
     pub fn copy_line_colors_to(&self, line_colors: &mut [f32]) {
         line_colors.copy_from_slice(&self.line_colors);
     }
@@ -174,12 +449,80 @@ impl View {
         linear_densities.copy_from_slice(&self.linear_densities);
     }
 
+    pub fn copy_axial_forces_to(&self, axial_forces: &mut [f32]) {
+        axial_forces.copy_from_slice(&self.axial_forces);
+    }
+
+    pub fn copy_force_vectors_to(&self, force_vectors: &mut [f32]) {
+        force_vectors.copy_from_slice(&self.force_vectors);
+    }
+
+    pub fn copy_alarms_to(&self, alarms: &mut [u8]) {
+        alarms.copy_from_slice(&self.alarms);
+    }
+
+    fn render_joint_colors(&mut self, fabric: &Fabric, world: &World) {
+        let mut degrees = vec![0_usize; fabric.joints.len()];
+        for interval in fabric.intervals.iter() {
+            degrees[interval.alpha_index] += 1;
+            degrees[interval.omega_index] += 1;
+        }
+        let max_degree = degrees.iter().copied().max().unwrap_or(0);
+        let mut anchored = vec![false; fabric.joints.len()];
+        for anchor in fabric.anchors.iter() {
+            anchored[anchor.joint_index] = true;
+        }
+        for ((joint, degree), anchored) in fabric.joints.iter().zip(degrees).zip(anchored) {
+            let in_contact = joint.is_in_contact(world.surface());
+            joint.project_color(
+                self,
+                degree,
+                max_degree,
+                anchored || joint.fixed,
+                in_contact,
+            );
+        }
+    }
+
+    fn render_callouts(
+        &mut self,
+        fabric: &Fabric,
+        joints: &[Joint],
+        camera: Vector3<Real>,
+        offset: Real,
+    ) {
+        let mut labelled: Vec<usize> = fabric.labels.keys().copied().collect();
+        labelled.sort_unstable();
+        for index in labelled {
+            let interval = &fabric.intervals[index];
+            let alpha = &joints[interval.alpha_index].location.coords;
+            let omega = &joints[interval.omega_index].location.coords;
+            let midpoint = (alpha + omega) / 2.0;
+            let sideways = interval
+                .unit
+                .cross(&camera)
+                .try_normalize(Real::EPSILON)
+                .unwrap_or_else(zero);
+            let anchor = midpoint + sideways * offset;
+            self.callout_locations
+                .extend([anchor.x, anchor.y, anchor.z].map(single));
+            self.callout_intervals.push(index as u32);
+        }
+    }
+
     fn clear(&mut self) {
         self.midpoint.coords.fill(0.0);
-        self.mass = 0_f32;
+        self.mass = 0.0;
         self.joint_locations.clear();
         self.joint_velocities.clear();
+        self.joint_colors.clear();
+        self.joint_sizes.clear();
         self.line_locations.clear();
+        self.shadow_locations.clear();
+        self.callout_locations.clear();
+        self.axial_forces.clear();
+        self.force_vectors.clear();
+        self.callout_intervals.clear();
         self.line_colors.clear();
         self.face_midpoints.clear();
         self.face_normals.clear();
@@ -190,5 +533,348 @@ impl View {
         self.strain_nuances.clear();
         self.stiffnesses.clear();
         self.linear_densities.clear();
+        self.alarms.clear();
+    }
+}
+
+impl View {
+    pub fn capabilities(&self) -> Vec<ChannelLayout> {
+        VIEW_CHANNELS
+            .iter()
+            .map(|&channel| self.get_capability(channel))
+            .collect()
+    }
+}
+
+// Ranges in the layout of the fabric's strain limits: push low and high, then pull low and high.
+// Like the fabric's, they always reach zero on the side of the role's own strain.
+fn strain_limits(fabric: &Fabric, strain_scale: StrainScale, percentile: Real) -> [Real; 4] {
+    let margin = 1e-3;
+    let range = |role: Option<bool>, default: [Real; 2]| {
+        let mut strains: Vec<Real> = fabric
+            .intervals
+            .iter()
+            .filter(|interval| role.is_none_or(|push| interval.push == push))
+            .map(|interval| interval.strain)
+            .collect();
+        if strains.is_empty() {
+            return default;
+        }
+        strains.sort_by(Real::total_cmp);
+        let last = strains.len() - 1;
+        let cut = match strain_scale {
+            StrainScale::Percentile => ((1.0 - percentile) * last as Real).round() as usize,
+            StrainScale::Shared | StrainScale::PerRole => 0,
+        };
+        [strains[cut] - margin, strains[last - cut] + margin]
+    };
+    let [push_low, push_high, pull_low, pull_high] = DEFAULT_STRAIN_LIMITS;
+    let [push_low, push_high, pull_low, pull_high] = match strain_scale {
+        StrainScale::Shared => {
+            let [low, high] = range(None, [push_low, pull_high]);
+            [low, high, low, high]
+        }
+        StrainScale::PerRole | StrainScale::Percentile => {
+            let [push_low, push_high] = range(Some(true), [push_low, push_high]);
+            let [pull_low, pull_high] = range(Some(false), [pull_low, pull_high]);
+            [push_low, push_high, pull_low, pull_high]
+        }
+    };
+    [push_low.min(0.0), push_high, pull_low, pull_high.max(0.0)]
+}
+
+// A copy of the joints moved apart, each with the tag group of its strut. Joints without a strut stay.
+fn exploded_joints(fabric: &Fabric, factor: Real) -> Vec<Joint> {
+    let mut joints = fabric.joints.clone();
+    if joints.is_empty() {
+        return joints;
+    }
+    let middle = joints
+        .iter()
+        .map(|joint| joint.location.coords)
+        .sum::<Vector3<Real>>()
+        / joints.len() as Real;
+    let mut groups: Vec<(u16, Vector3<Real>, usize)> = Vec::new();
+    let mut group_of: Vec<Option<usize>> = vec![None; joints.len()];
+    for push in fabric.intervals.iter().filter(|interval| interval.push) {
+        let group = match groups.iter().position(|&(tag, _, _)| tag == push.tag) {
+            Some(group) => group,
+            None => {
+                groups.push((push.tag, zero(), 0));
+                groups.len() - 1
+            }
+        };
+        for index in [push.alpha_index, push.omega_index] {
+            if group_of[index].is_none() {
+                group_of[index] = Some(group);
+                groups[group].1 += joints[index].location.coords;
+                groups[group].2 += 1;
+            }
+        }
+    }
+    for (joint, group) in joints.iter_mut().zip(group_of) {
+        if let Some(group) = group {
+            let (_, sum, count) = groups[group];
+            joint.location += (sum / count as Real - middle) * factor;
+        }
+    }
+    joints
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::constants::IntervalRole;
+    use crate::event::FabricEvent;
+    use crate::fixture::chain;
+
+    use super::*;
+
+    fn ticked() -> Fabric {
+        let mut fabric = chain(3, 1.0);
+        fabric.iterate_ticks(&World::new(), 1);
+        fabric
+    }
+
+    #[test]
+    fn joint_colors_stay_empty_unless_asked_for() {
+        let fabric = ticked();
+        let mut view = View::on_fabric(&fabric);
+        view.render(&fabric, &World::new());
+        assert!(view.joint_colors.is_empty());
+        assert!(view.joint_sizes.is_empty());
+    }
+
+    #[test]
+    fn colors_joints_by_their_degree() {
+        let fabric = ticked();
+        let mut view = View::on_fabric(&fabric);
+        view.set_joint_coloring(JointColoring::Degree);
+        view.render(&fabric, &World::new());
+        assert_eq!(view.joint_sizes, [1.0, 1.5, 1.0]);
+        assert_eq!(view.joint_colors[3..6], [1.0, 0.0, 0.0]);
+        view.render(&fabric, &World::new());
+        assert_eq!(view.joint_sizes.len(), 3);
+    }
+
+    #[test]
+    fn colors_anchored_joints_apart() {
+        let mut fabric = ticked();
+        fabric.create_anchor(2, 1.0, 1.0).unwrap();
+        let mut view = View::on_fabric(&fabric);
+        view.set_joint_coloring(JointColoring::Anchored);
+        view.render(&fabric, &World::new());
+        assert_eq!(view.joint_sizes, [1.0, 1.0, 1.5]);
+    }
+
+    #[test]
+    fn buffers_follow_a_removed_interval() {
+        let mut fabric = ticked();
+        fabric.create_joint(3.0, 1.0, 0.0).unwrap();
+        fabric
+            .add_interval_safe(2, 3, IntervalRole::Push, 1.0, 1.0, 0.0)
+            .unwrap();
+        fabric.set_interval_label(2, "last").unwrap();
+        fabric.drain_events();
+        fabric.remove_interval(0).unwrap();
+        assert!(matches!(
+            fabric.drain_events()[..],
+            [FabricEvent::IntervalRemoved {
+                index: 0,
+                moved_from: Some(2)
+            }]
+        ));
+        let mut view = View::on_fabric(&fabric);
+        view.set_callouts(0.0, 0.0, 1.0, 0.0);
+        view.render(&fabric, &World::new());
+        assert_eq!(view.strains.len(), 2);
+        assert_eq!(view.line_locations.len(), 2 * 6);
+        assert_eq!(view.callout_intervals, [0]);
+        assert!(
+            (view.callout_locations[0] - 2.5).abs() < 1e-3,
+            "{}",
+            view.callout_locations[0]
+        );
+    }
+
+    #[test]
+    fn style_follows_the_stage_and_the_world() {
+        let mut fabric = ticked();
+        let mut world = World::new();
+        let growing = ViewStyle::of(&fabric, &world);
+        assert_eq!(growing.pretenst_progress, 0.0);
+        assert!(!growing.strain_colors);
+        assert_eq!(growing.background_blue, 0.0);
+        fabric.stage = Stage::Pretenst;
+        world.push_and_pull = true;
+        world.gravity = 0.0;
+        let pretenst = ViewStyle::of(&fabric, &world);
+        assert_eq!(pretenst.pretenst_progress, 1.0);
+        assert!(pretenst.strain_colors);
+        assert!(pretenst.background_red > 0.0);
+        assert!(pretenst.background_blue > 0.0);
+    }
+
+    #[test]
+    fn shadows_are_the_lines_flattened_onto_the_ground() {
+        let fabric = ticked();
+        let mut view = View::on_fabric(&fabric);
+        view.render(&fabric, &World::new());
+        assert!(view.shadow_locations.is_empty());
+        view.set_shadows(true);
+        view.render(&fabric, &World::new());
+        assert_eq!(view.shadow_locations.len(), view.line_locations.len());
+        for (shadow, line) in view
+            .shadow_locations
+            .chunks(3)
+            .zip(view.line_locations.chunks(3))
+        {
+            assert_eq!(shadow, [line[0], 0.0, line[2]]);
+        }
+    }
+
+    #[test]
+    fn callouts_sit_beside_labelled_intervals_across_the_line_of_sight() {
+        let mut fabric = ticked();
+        fabric.set_interval_label(1, "b").unwrap();
+        fabric.set_interval_label(0, "a").unwrap();
+        let mut view = View::on_fabric(&fabric);
+        view.set_callouts(0.0, 0.0, 2.0, 0.5);
+        view.render(&fabric, &World::new());
+        assert_eq!(view.callout_intervals, [0, 1]);
+        let first = &view.callout_locations[..3];
+        assert!(
+            (first[0] - 0.5).abs() < 1e-3 && (first[1] - 0.5).abs() < 1e-3,
+            "{:?}",
+            first
+        );
+        fabric.set_interval_label(0, "").unwrap();
+        view.render(&fabric, &World::new());
+        assert_eq!(view.callout_intervals, [1]);
+        view.clear_callouts();
+        view.render(&fabric, &World::new());
+        assert!(view.callout_locations.is_empty());
+    }
+
+    #[test]
+    fn forces_are_signed_and_point_along_their_intervals() {
+        let mut fabric = ticked();
+        fabric.intervals[0].length_0 = 0.5;
+        fabric.iterate_ticks(&World::new(), 1);
+        let mut view = View::on_fabric(&fabric);
+        view.render(&fabric, &World::new());
+        assert!(view.axial_forces.is_empty());
+        view.set_forces(true);
+        view.render(&fabric, &World::new());
+        assert_eq!((view.axial_forces.len(), view.force_vectors.len()), (2, 6));
+        assert!(view.axial_forces[0] > 0.0);
+        let vector = &view.force_vectors[..3];
+        let magnitude = vector.iter().map(|value| value * value).sum::<f32>().sqrt();
+        assert!((magnitude - view.axial_forces[0]).abs() < 1e-6 * magnitude.max(1.0));
+        assert!(vector[0] > 0.0);
+    }
+
+    #[test]
+    fn explodes_strut_groups_away_from_the_middle_and_leaves_the_rest() {
+        let mut fabric = Fabric::new(0);
+        for x in 0..5 {
+            fabric.create_joint(x as Real, 1.0, 0.0).unwrap();
+        }
+        for (alpha, tag) in [(0, 1), (3, 2)] {
+            let index = fabric
+                .add_interval_safe(alpha, alpha + 1, IntervalRole::Push, 1.0, 1.0, 0.0)
+                .unwrap();
+            fabric.set_interval_tag(index, tag).unwrap();
+        }
+        let exploded: Vec<Real> = exploded_joints(&fabric, 1.0)
+            .iter()
+            .map(|joint| joint.location.x)
+            .collect();
+        assert_eq!(exploded, [-1.5, -0.5, 2.0, 4.5, 5.5]);
+        assert_eq!(fabric.joints[0].location.x, 0.0);
+    }
+
+    #[test]
+    fn capabilities_give_the_stride_render_fills_and_only_what_is_enabled() {
+        let fabric = ticked();
+        let mut view = View::on_fabric(&fabric);
+        let capabilities = view.capabilities();
+        assert_eq!(capabilities.len(), View::get_channel_count() as usize);
+        assert!(capabilities
+            .iter()
+            .enumerate()
+            .all(|(index, layout)| layout.channel as usize == index));
+        assert!(!view.get_capability(ViewChannel::ShadowLocations).enabled);
+        view.set_shadows(true);
+        view.render(&fabric, &World::new());
+        let intervals = fabric.intervals.len();
+        for (channel, filled) in [
+            (ViewChannel::LineLocations, view.line_locations.len()),
+            (ViewChannel::UnitVectors, view.unit_vectors.len()),
+            (ViewChannel::ShadowLocations, view.shadow_locations.len()),
+        ] {
+            let layout = view.get_capability(channel);
+            assert!(layout.enabled);
+            assert_eq!(layout.element, ViewElement::Interval);
+            assert_eq!(layout.stride as usize * intervals, filled, "{:?}", channel);
+        }
+        let limits = view.get_capability(ViewChannel::StrainLimits);
+        assert_eq!(
+            (limits.element, limits.stride as usize),
+            (ViewElement::Fabric, view.strain_limits.len())
+        );
+    }
+
+    #[test]
+    fn strain_scales_are_shared_per_role_or_trimmed_to_the_percentile() {
+        let mut fabric = Fabric::new(0);
+        for x in 0..8 {
+            fabric.create_joint(x as Real, 1.0, 0.0).unwrap();
+        }
+        let strains = [-0.02, -0.01, 0.01, 0.02, 0.03, 0.04, 0.5];
+        for (index, strain) in strains.iter().enumerate() {
+            let role = if *strain < 0.0 {
+                IntervalRole::Push
+            } else {
+                IntervalRole::Pull
+            };
+            fabric
+                .add_interval_safe(index, index + 1, role, 1.0, 1.0, 0.0)
+                .unwrap();
+        }
+        for (interval, strain) in fabric.intervals.iter_mut().zip(strains) {
+            interval.strain = strain;
+        }
+        let close = |actual: &[Real], expected: [Real; 4]| {
+            assert!(
+                actual.len() == 4
+                    && actual
+                        .iter()
+                        .zip(expected)
+                        .all(|(a, e)| (a - e).abs() < 1e-6),
+                "{:?}",
+                actual
+            );
+        };
+        close(
+            &strain_limits(&fabric, StrainScale::Shared, 1.0),
+            [-0.021, 0.501, -0.021, 0.501],
+        );
+        close(
+            &strain_limits(&fabric, StrainScale::PerRole, 1.0),
+            [-0.021, -0.009, 0.009, 0.501],
+        );
+        close(
+            &strain_limits(&fabric, StrainScale::Percentile, 0.75),
+            [-0.021, -0.009, 0.019, 0.041],
+        );
+        let mut view = View::on_fabric(&fabric);
+        view.set_strain_scale(StrainScale::Percentile, 0.75);
+        view.render(&fabric, &World::new());
+        let rendered: Vec<Real> = view
+            .strain_limits
+            .iter()
+            .map(|&limit| Real::from(limit))
+            .collect();
+        close(&rendered, [-0.021, -0.009, 0.019, 0.041]);
     }
 }
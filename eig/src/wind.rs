@@ -0,0 +1,123 @@
+/*
+ * Copyright (c) 2020. Beautiful Code BV, Rotterdam, Netherlands
+ * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
+ */
+
+use nalgebra::*;
+
+use crate::real::Real;
+
+// Horizontal wind swelling and easing sinusoidally by the gustiness, with seeded turbulence jittering
+// it every tick. Both are fractions of the speed.
+#[derive(Clone, Copy, Debug)]
+pub struct Wind {
+    pub(crate) direction: Vector3<Real>,
+    pub(crate) speed: Real,
+    pub(crate) gustiness: Real,
+    pub(crate) gust_ticks: Real,
+    pub(crate) turbulence: Real,
+    pub(crate) drag: Real,
+    pub(crate) seed: u32,
+}
+
+impl Wind {
+    // The direction is turned by the angle from x toward z.
+    pub fn new(
+        angle: Real,
+        speed: Real,
+        gustiness: Real,
+        gust_ticks: Real,
+        turbulence: Real,
+        drag: Real,
+        seed: u32,
+    ) -> Wind {
+        Wind {
+            direction: Vector3::new(angle.cos(), 0.0, angle.sin()),
+            speed,
+            gustiness,
+            gust_ticks,
+            turbulence,
+            drag,
+            seed,
+        }
+    }
+
+    pub fn velocity(&self, tick: u32) -> Vector3<Real> {
+        let phase = tick as Real / self.gust_ticks * crate::real::consts::TAU;
+        let steady = self.direction * self.speed * (1.0 + self.gustiness * phase.sin());
+        if self.turbulence == 0.0 {
+            return steady;
+        }
+        let mut state =
+            ((self.seed as u64) << 32 | tick as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+        let mut noise = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 40) as Real / (1_u64 << 23) as Real - 1.0
+        };
+        let jitter = Vector3::new(noise(), noise(), noise()) * self.speed * self.turbulence;
+        steady + jitter
+    }
+
+    // Drag on an interval grows with the square of the wind speed and with the length it shows the wind.
+    pub fn load(
+        &self,
+        velocity: &Vector3<Real>,
+        unit: &Vector3<Real>,
+        length: Real,
+    ) -> Vector3<Real> {
+        let speed = velocity.magnitude();
+        if speed == 0.0 {
+            return zero();
+        }
+        let projected_length = length * unit.cross(&(velocity / speed)).magnitude();
+        velocity * speed * self.drag * projected_length
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gusts_swing_the_speed_and_turbulence_is_seeded_and_bounded() {
+        let steady = Wind::new(0.0, 2.0, 0.5, 100.0, 0.0, 1.0, 0);
+        assert!((steady.velocity(0) - Vector3::new(2.0, 0.0, 0.0)).magnitude() < 1e-6);
+        assert!((steady.velocity(25).x - 3.0).abs() < 1e-5);
+        assert!((steady.velocity(75).x - 1.0).abs() < 1e-5);
+        let turbulent = Wind::new(0.0, 2.0, 0.0, 100.0, 0.1, 1.0, 9);
+        assert_eq!(turbulent.velocity(3), turbulent.velocity(3));
+        assert_ne!(turbulent.velocity(3), turbulent.velocity(4));
+        let jitter = turbulent.velocity(3) - steady.direction * 2.0;
+        assert!(
+            jitter.iter().all(|component| component.abs() <= 0.2),
+            "{}",
+            jitter
+        );
+    }
+
+    #[test]
+    fn loads_intervals_by_the_length_they_show_the_wind() {
+        let wind = Wind::new(0.0, 2.0, 0.0, 100.0, 0.0, 0.5, 0);
+        let velocity = wind.velocity(0);
+        let along = wind.load(&velocity, &Vector3::new(1.0, 0.0, 0.0), 3.0);
+        assert!(along.magnitude() < 1e-6, "{}", along);
+        let across = wind.load(&velocity, &Vector3::new(0.0, 1.0, 0.0), 3.0);
+        assert!(
+            (across - Vector3::new(6.0, 0.0, 0.0)).magnitude() < 1e-5,
+            "{}",
+            across
+        );
+        let slanted = wind.load(&velocity, &Vector3::new(1.0, 1.0, 0.0).normalize(), 3.0);
+        assert!(
+            (slanted.x - 6.0 / (2.0 as Real).sqrt()).abs() < 1e-5,
+            "{}",
+            slanted
+        );
+        assert_eq!(
+            wind.load(&Vector3::zeros(), &Vector3::new(0.0, 1.0, 0.0), 3.0),
+            Vector3::zeros()
+        );
+    }
+}
@@ -3,27 +3,84 @@
  * Licensed under GNU GENERAL PUBLIC LICENSE Version 3.
  */
 
+use std::borrow::Cow;
+
 use crate::constants::*;
+use crate::error::Error;
 use crate::fabric::Fabric;
+use crate::medium::{immersion, Medium};
+use crate::obstacle::Obstacle;
+use crate::real::{reals, Real};
+use crate::role::RoleDefaults;
+use crate::snapshot;
+use crate::surface::{Flat, Heightmap, Surface};
+use crate::wind::Wind;
+use nalgebra::*;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
+#[derive(Clone)]
 pub struct World {
     pub(crate) surface_character: SurfaceCharacter,
     pub(crate) push_and_pull: bool,
-    pub(crate) gravity: f32,
-    pub(crate) drag: f32,
-    pub(crate) pretenst_factor: f32,
-    pub(crate) stiffness_factor: f32,
-    pub(crate) iterations_per_frame: f32,
-    pub(crate) interval_countdown: f32,
-    pub(crate) pretensing_countdown: f32,
-    pub(crate) shaping_pretenst_factor: f32,
-    pub(crate) shaping_drag: f32,
-    pub(crate) shaping_stiffness_factor: f32,
-    pub(crate) visual_strain: f32,
-    pub(crate) push_over_pull: f32,
-    pub(crate) antigravity: f32,
+    pub(crate) gravity: Real,
+    pub(crate) drag: Real,
+    pub(crate) pretenst_factor: Real,
+    pub(crate) stiffness_factor: Real,
+    pub(crate) iterations_per_frame: Real,
+    pub(crate) interval_countdown: Real,
+    pub(crate) pretensing_countdown: Real,
+    pub(crate) shaping_pretenst_factor: Real,
+    pub(crate) shaping_drag: Real,
+    pub(crate) shaping_stiffness_factor: Real,
+    pub(crate) visual_strain: Real,
+    pub(crate) push_over_pull: Real,
+    pub(crate) antigravity: Real,
+    pub(crate) shaping_slew_rate: Real,
+    pub(crate) push_damping: Real,
+    pub(crate) pull_damping: Real,
+    pub(crate) active_damping: Real,
+    pub(crate) kinetic_damping: bool,
+    pub(crate) sleep_velocity: Real,
+    pub(crate) sleep_force: Real,
+    pub(crate) sleep_ticks: u32,
+    pub(crate) speed_limits: [Real; 5],
+    pub(crate) strain_alarms: [[Real; 2]; 2],
+    // per role, zero for unbreakable
+    pub(crate) ultimate_strains: [Real; 2],
+    // per role, whether broken intervals go rather than staying on disabled
+    pub(crate) remove_broken: [bool; 2],
+    // per role, the yield strain and the fraction of the excess taken up per tick, zero for elastic
+    pub(crate) plastic_yield: [[Real; 2]; 2],
+    pub(crate) alarm_hysteresis: Real,
+    pub(crate) ground_motion: Vec<Real>,
+    pub(crate) ground_motion_ticks: Real,
+    pub(crate) ground_motion_serial: u32,
+    pub(crate) media: Vec<Medium>,
+    pub(crate) medium_transition: Real,
+    pub(crate) stage_features: [Vec<(WorldFeature, Real)>; 5],
+    pub(crate) wind: Option<Wind>,
+    pub(crate) gravity_direction: Vector3<Real>,
+    // center and surface radius
+    pub(crate) point_gravity: Option<(Point3<Real>, Real)>,
+    pub(crate) terrain: Option<Heightmap>,
+    pub(crate) obstacles: Vec<Obstacle>,
+    pub(crate) obstacle_stiffness: Real,
+    pub(crate) obstacle_friction: Real,
+    // capsule radius and separation stiffness, no strut collision at zero radius
+    pub(crate) strut_collision: (Real, Real),
+    pub(crate) time_step: Real,
+    // ticks per iterate instead of iterations per frame, unless zero
+    pub(crate) substeps: u32,
+    // the most any joint may gain in speed over one time step, unless zero
+    pub(crate) acceleration_limit: Real,
+    pub(crate) integration: Integration,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        World::new()
+    }
 }
 
 #[wasm_bindgen]
@@ -45,6 +102,38 @@ impl World {
             visual_strain: default_world_feature(WorldFeature::VisualStrain),
             push_over_pull: default_world_feature(WorldFeature::PushOverPull),
             antigravity: default_world_feature(WorldFeature::Antigravity),
+            shaping_slew_rate: default_world_feature(WorldFeature::ShapingSlewRate),
+            push_damping: 0.0,
+            pull_damping: 0.0,
+            active_damping: 0.0,
+            kinetic_damping: false,
+            sleep_velocity: 0.0,
+            sleep_force: 0.0,
+            sleep_ticks: 0,
+            speed_limits: [0.0; 5],
+            strain_alarms: [[0.0; 2]; 2],
+            ultimate_strains: [0.0; 2],
+            remove_broken: [false; 2],
+            plastic_yield: [[0.0; 2]; 2],
+            alarm_hysteresis: 0.0,
+            ground_motion: Vec::new(),
+            ground_motion_ticks: 1.0,
+            ground_motion_serial: 0,
+            media: Vec::new(),
+            medium_transition: 0.0,
+            stage_features: Default::default(),
+            wind: None,
+            gravity_direction: -Vector3::y(),
+            point_gravity: None,
+            terrain: None,
+            obstacles: Vec::new(),
+            obstacle_stiffness: 0.1,
+            obstacle_friction: 0.1,
+            strut_collision: (0.0, 0.0),
+            time_step: 1.0,
+            substeps: 0,
+            acceleration_limit: 0.0,
+            integration: Integration::SemiImplicitEuler,
         }
     }
 
@@ -56,7 +145,434 @@ impl World {
         self.push_and_pull = push_and_pull;
     }
 
-    pub fn get_float_value(&self, feature: WorldFeature) -> f32 {
+    // From zero, off, to one for the most aggressive.
+    pub fn set_active_damping(&mut self, aggressiveness: Real) -> Result<(), Error> {
+        if !(0.0..=1.0).contains(&aggressiveness) {
+            return Err(Error::IllegalDamping {
+                damping: aggressiveness,
+            });
+        }
+        self.active_damping = aggressiveness;
+        Ok(())
+    }
+
+    // Dynamic relaxation: every joint stops dead whenever the kinetic energy has just peaked.
+    pub fn set_kinetic_damping(&mut self, kinetic_damping: bool) {
+        self.kinetic_damping = kinetic_damping;
+    }
+
+    // Joints slower and less loaded than these sleep, zero for never.
+    pub fn set_sleep(&mut self, velocity: Real, force: Real, ticks: u32) -> Result<(), Error> {
+        if !(velocity.is_finite() && velocity >= 0.0) {
+            return Err(Error::IllegalSpeed { speed: velocity });
+        }
+        if !(force.is_finite() && force >= 0.0) {
+            return Err(Error::IllegalForce { force });
+        }
+        self.sleep_velocity = velocity;
+        self.sleep_force = force;
+        self.sleep_ticks = ticks;
+        Ok(())
+    }
+
+    // Zero for no limit.
+    pub fn set_speed_limit(&mut self, stage: Stage, speed_limit: Real) -> Result<(), Error> {
+        if !(speed_limit.is_finite() && speed_limit >= 0.0) {
+            return Err(Error::IllegalSpeed { speed: speed_limit });
+        }
+        self.speed_limits[stage as usize] = speed_limit;
+        Ok(())
+    }
+
+    pub fn get_speed_limit(&self, stage: Stage) -> Real {
+        self.speed_limits[stage as usize]
+    }
+
+    // Strain magnitudes for warning and critical, either zero to switch that level off for the role.
+    pub fn set_strain_alarm(
+        &mut self,
+        role: IntervalRole,
+        warning: Real,
+        critical: Real,
+    ) -> Result<(), Error> {
+        let band = |strain: Real| strain.is_finite() && strain >= 0.0;
+        if !(band(warning) && band(critical)) || (critical > 0.0 && warning > critical) {
+            return Err(Error::IllegalRange {
+                min: warning,
+                max: critical,
+            });
+        }
+        self.strain_alarms[role as usize] = [warning, critical];
+        Ok(())
+    }
+
+    // Past this strain magnitude an interval of the role breaks, zero for never. Broken intervals of
+    // the role are disabled, or removed altogether.
+    pub fn set_ultimate_strain(
+        &mut self,
+        role: IntervalRole,
+        ultimate_strain: Real,
+        remove_broken: bool,
+    ) -> Result<(), Error> {
+        if !(ultimate_strain.is_finite() && ultimate_strain >= 0.0) {
+            return Err(Error::IllegalStrain {
+                strain: ultimate_strain,
+            });
+        }
+        self.ultimate_strains[role as usize] = ultimate_strain;
+        self.remove_broken[role as usize] = remove_broken;
+        Ok(())
+    }
+
+    // Beyond the yield strain magnitude an interval of the role gives way for good: every tick its rest
+    // length takes up the flow fraction of the excess strain. A flow of zero keeps the role elastic.
+    pub fn set_yield_strain(
+        &mut self,
+        role: IntervalRole,
+        yield_strain: Real,
+        flow: Real,
+    ) -> Result<(), Error> {
+        if !(yield_strain.is_finite() && yield_strain >= 0.0) {
+            return Err(Error::IllegalStrain {
+                strain: yield_strain,
+            });
+        }
+        if !(0.0..=1.0).contains(&flow) {
+            return Err(Error::IllegalFlow { flow });
+        }
+        self.plastic_yield[role as usize] = [yield_strain, flow];
+        Ok(())
+    }
+
+    // Viscous damping on the strain rate of every interval of the role, multiplied by each interval's
+    // own damping. Zero, the default, for none.
+    pub fn set_role_damping(&mut self, role: IntervalRole, damping: Real) -> Result<(), Error> {
+        if !(damping.is_finite() && damping >= 0.0) {
+            return Err(Error::IllegalDamping { damping });
+        }
+        match role {
+            IntervalRole::Push => self.push_damping = damping,
+            IntervalRole::Pull => self.pull_damping = damping,
+        }
+        Ok(())
+    }
+
+    // How far below a band the strain must fall before its alarm clears.
+    pub fn set_alarm_hysteresis(&mut self, hysteresis: Real) -> Result<(), Error> {
+        if !(hysteresis.is_finite() && hysteresis >= 0.0) {
+            return Err(Error::IllegalStrain { strain: hysteresis });
+        }
+        self.alarm_hysteresis = hysteresis;
+        Ok(())
+    }
+
+    // Ground acceleration as x,y,z triples in the units of gravity, one sample every dt of time, which
+    // is dt ticks at a time step of one.
+    // Playback starts over in every fabric when a new series is set.
+    pub fn set_ground_motion(&mut self, samples: &[f32], dt: Real) -> Result<(), Error> {
+        if !samples.len().is_multiple_of(3) {
+            return Err(Error::WrongLength {
+                expected: (samples.len() / 3 + 1) * 3,
+                actual: samples.len(),
+            });
+        }
+        if !(dt.is_finite() && dt > 0.0) {
+            return Err(Error::IllegalCountdown { countdown: dt });
+        }
+        self.ground_motion = reals(samples);
+        self.ground_motion_ticks = dt;
+        self.ground_motion_serial += 1;
+        Ok(())
+    }
+
+    // Media stack upwards from the ground, so each top must be above the last one.
+    pub fn add_medium(&mut self, top: Real, drag: Real, density: Real) -> Result<usize, Error> {
+        let floor = self.media.last().map(|medium| medium.top).unwrap_or(0.0);
+        let legal = top.is_finite()
+            && top > floor
+            && (0.0..1.0).contains(&drag)
+            && density.is_finite()
+            && density >= 0.0;
+        if !legal {
+            return Err(Error::IllegalMedium { top, drag, density });
+        }
+        self.media.push(Medium { top, drag, density });
+        Ok(self.media.len() - 1)
+    }
+
+    pub fn clear_media(&mut self) {
+        self.media.clear();
+    }
+
+    pub fn get_medium_count(&self) -> usize {
+        self.media.len()
+    }
+
+    // Thickness over which drag and buoyancy change when crossing between media.
+    pub fn set_medium_transition(&mut self, thickness: Real) {
+        self.medium_transition = thickness.max(0.0);
+    }
+
+    // Gravity keeps its strength from the feature but can pull any way, not just down.
+    pub fn set_gravity_direction(&mut self, x: Real, y: Real, z: Real) -> Result<(), Error> {
+        let direction = Vector3::new(x, y, z)
+            .try_normalize(Real::EPSILON)
+            .filter(|direction| direction.iter().all(|component| component.is_finite()))
+            .ok_or(Error::IllegalPosition {
+                position: x.hypot(y).hypot(z),
+            })?;
+        self.gravity_direction = direction;
+        Ok(())
+    }
+
+    // A planet instead of a direction: the gravity feature at the radius around the center, falling off
+    // with the square of the distance outside it and linearly to nothing inside.
+    pub fn set_point_gravity(
+        &mut self,
+        x: Real,
+        y: Real,
+        z: Real,
+        radius: Real,
+    ) -> Result<(), Error> {
+        for position in [x, y, z] {
+            if !position.is_finite() {
+                return Err(Error::IllegalPosition { position });
+            }
+        }
+        if !(radius.is_finite() && radius > 0.0) {
+            return Err(Error::IllegalLength { length: radius });
+        }
+        self.point_gravity = Some((Point3::new(x, y, z), radius));
+        Ok(())
+    }
+
+    pub fn clear_point_gravity(&mut self) {
+        self.point_gravity = None;
+    }
+
+    // Uneven ground instead of the plane at zero, see Heightmap for the layout of the heights.
+    pub fn set_terrain(
+        &mut self,
+        origin_x: Real,
+        origin_z: Real,
+        spacing: Real,
+        columns: usize,
+        heights: &[f32],
+    ) -> Result<(), Error> {
+        self.terrain = Some(Heightmap::new(
+            origin_x,
+            origin_z,
+            spacing,
+            columns,
+            &reals(heights),
+        )?);
+        Ok(())
+    }
+
+    pub fn clear_terrain(&mut self) {
+        self.terrain = None;
+    }
+
+    pub fn add_sphere_obstacle(
+        &mut self,
+        x: Real,
+        y: Real,
+        z: Real,
+        radius: Real,
+    ) -> Result<usize, Error> {
+        if !(radius.is_finite() && radius > 0.0) {
+            return Err(Error::IllegalLength { length: radius });
+        }
+        self.add_obstacle(Obstacle::Sphere {
+            center: Point3::new(x, y, z),
+            radius,
+        })
+    }
+
+    pub fn add_box_obstacle(
+        &mut self,
+        min_x: Real,
+        min_y: Real,
+        min_z: Real,
+        max_x: Real,
+        max_y: Real,
+        max_z: Real,
+    ) -> Result<usize, Error> {
+        for length in [max_x - min_x, max_y - min_y, max_z - min_z] {
+            if !(length.is_finite() && length > 0.0) {
+                return Err(Error::IllegalLength { length });
+            }
+        }
+        let (min, max) = (
+            Point3::new(min_x, min_y, min_z),
+            Point3::new(max_x, max_y, max_z),
+        );
+        self.add_obstacle(Obstacle::Box { min, max })
+    }
+
+    // Solid below the plane through the point, on the far side from the normal.
+    pub fn add_plane_obstacle(
+        &mut self,
+        x: Real,
+        y: Real,
+        z: Real,
+        normal_x: Real,
+        normal_y: Real,
+        normal_z: Real,
+    ) -> Result<usize, Error> {
+        let normal = Vector3::new(normal_x, normal_y, normal_z)
+            .try_normalize(Real::EPSILON)
+            .ok_or(Error::IllegalLength { length: 0.0 })?;
+        self.add_obstacle(Obstacle::Plane {
+            point: Point3::new(x, y, z),
+            normal,
+        })
+    }
+
+    pub fn clear_obstacles(&mut self) {
+        self.obstacles.clear();
+    }
+
+    pub fn get_obstacle_count(&self) -> usize {
+        self.obstacles.len()
+    }
+
+    // Penalty force per unit of depth, and the fraction of sliding velocity taken per tick in contact.
+    pub fn set_obstacle_contact(&mut self, stiffness: Real, friction: Real) -> Result<(), Error> {
+        if !(stiffness.is_finite() && stiffness >= 0.0) {
+            return Err(Error::IllegalStiffness { stiffness });
+        }
+        if !(0.0..=1.0).contains(&friction) {
+            return Err(Error::IllegalFriction {
+                along: friction,
+                across: friction,
+            });
+        }
+        self.obstacle_stiffness = stiffness;
+        self.obstacle_friction = friction;
+        Ok(())
+    }
+
+    // Struts become capsules of the radius that push each other apart when they overlap.
+    pub fn set_strut_collision(&mut self, radius: Real, stiffness: Real) -> Result<(), Error> {
+        if !(radius.is_finite() && radius >= 0.0) {
+            return Err(Error::IllegalLength { length: radius });
+        }
+        if !(stiffness.is_finite() && stiffness >= 0.0) {
+            return Err(Error::IllegalStiffness { stiffness });
+        }
+        self.strut_collision = (radius, stiffness);
+        Ok(())
+    }
+
+    // A tick integrates the joints over the time step, shortened whenever the fastest accelerating joint
+    // would gain more speed than the acceleration limit allows. Substeps, if any, replace iterations
+    // per frame.
+    pub fn set_time_step(
+        &mut self,
+        time_step: Real,
+        substeps: u32,
+        acceleration_limit: Real,
+    ) -> Result<(), Error> {
+        if !(time_step.is_finite()
+            && time_step > 0.0
+            && acceleration_limit.is_finite()
+            && acceleration_limit >= 0.0)
+        {
+            return Err(Error::IllegalTimeStep {
+                time_step,
+                acceleration_limit,
+            });
+        }
+        self.time_step = time_step;
+        self.substeps = substeps;
+        self.acceleration_limit = acceleration_limit;
+        Ok(())
+    }
+
+    pub fn set_integration(&mut self, integration: Integration) {
+        self.integration = integration;
+    }
+
+    // Blows on pretenst fabrics. The angle turns the direction from x toward z, gusts swing the speed by
+    // the gustiness over the gust period, turbulence adds seeded noise up to its fraction of the speed.
+    // Flat, for JavaScript.
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_wind(
+        &mut self,
+        angle: Real,
+        speed: Real,
+        gustiness: Real,
+        gust_ticks: Real,
+        turbulence: Real,
+        drag: Real,
+        seed: u32,
+    ) -> Result<(), Error> {
+        let fine = |value: Real| value.is_finite() && value >= 0.0;
+        if !(fine(speed) && fine(gustiness) && fine(turbulence) && fine(drag)) {
+            return Err(Error::IllegalWind {
+                speed,
+                gustiness,
+                turbulence,
+                drag,
+            });
+        }
+        if !(gust_ticks.is_finite() && gust_ticks > 0.0) {
+            return Err(Error::IllegalCountdown {
+                countdown: gust_ticks,
+            });
+        }
+        self.wind = Some(Wind::new(
+            angle, speed, gustiness, gust_ticks, turbulence, drag, seed,
+        ));
+        Ok(())
+    }
+
+    pub fn clear_wind(&mut self) {
+        self.wind = None;
+    }
+
+    // Replaces the feature value only while a fabric is in the stage, so leaving it restores the base value.
+    pub fn set_stage_feature(&mut self, stage: Stage, feature: WorldFeature, value: Real) {
+        let overrides = &mut self.stage_features[stage as usize];
+        match overrides
+            .iter_mut()
+            .find(|(overridden, _)| *overridden == feature)
+        {
+            Some(entry) => entry.1 = value,
+            None => overrides.push((feature, value)),
+        }
+    }
+
+    pub fn get_stage_feature(&self, stage: Stage, feature: WorldFeature) -> Option<Real> {
+        self.stage_features[stage as usize]
+            .iter()
+            .find(|(overridden, _)| *overridden == feature)
+            .map(|&(_, value)| value)
+    }
+
+    pub fn clear_stage_feature(&mut self, stage: Stage, feature: WorldFeature) {
+        self.stage_features[stage as usize].retain(|(overridden, _)| *overridden != feature);
+    }
+
+    pub fn clear_stage_features(&mut self, stage: Stage) {
+        self.stage_features[stage as usize].clear();
+    }
+
+    pub fn load_snapshot(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        *self = World {
+            ground_motion_serial: self.ground_motion_serial + 1,
+            ..snapshot::decode_world(bytes)?
+        };
+        Ok(())
+    }
+
+    pub fn clear_ground_motion(&mut self) {
+        self.ground_motion.clear();
+        self.ground_motion_serial += 1;
+    }
+
+    pub fn get_float_value(&self, feature: WorldFeature) -> Real {
         match feature {
             WorldFeature::Gravity => self.gravity,
             WorldFeature::Drag => self.drag,
@@ -71,11 +587,12 @@ impl World {
             WorldFeature::VisualStrain => self.visual_strain,
             WorldFeature::PushOverPull => self.push_over_pull,
             WorldFeature::Antigravity => self.antigravity,
+            WorldFeature::ShapingSlewRate => self.shaping_slew_rate,
         }
     }
 
-    pub fn set_float_value(&mut self, feature: WorldFeature, value: f32) -> f32 {
-        let value_pointer: &mut f32 = match feature {
+    pub fn set_float_value(&mut self, feature: WorldFeature, value: Real) -> Real {
+        let value_pointer: &mut Real = match feature {
             WorldFeature::Gravity => &mut self.gravity,
             WorldFeature::Drag => &mut self.drag,
             WorldFeature::PretenstFactor => &mut self.pretenst_factor,
@@ -89,21 +606,309 @@ impl World {
             WorldFeature::VisualStrain => &mut self.visual_strain,
             WorldFeature::PushOverPull => &mut self.push_over_pull,
             WorldFeature::Antigravity => &mut self.antigravity,
+            WorldFeature::ShapingSlewRate => &mut self.shaping_slew_rate,
         };
         *value_pointer = value;
         value
     }
 
-    pub fn set_float_percent(&mut self, feature: WorldFeature, percent: f32) -> f32 {
-        let value = percent * default_world_feature(feature) / 100_f32;
+    pub fn set_float_percent(&mut self, feature: WorldFeature, percent: Real) -> Real {
+        let value = percent * default_world_feature(feature) / 100.0;
         self.set_float_value(feature, value)
     }
 
-    pub fn pretensing_nuance(&self, fabric: &Fabric) -> f32 {
+    pub fn role_defaults(&self, role: IntervalRole, stage: Stage) -> RoleDefaults {
+        RoleDefaults::of(self, role, stage)
+    }
+
+    pub fn pretensing_nuance(&self, fabric: &Fabric) -> Real {
         if fabric.stage <= Stage::Slack {
-            0_f32
+            0.0
         } else {
             (self.pretensing_countdown - fabric.pretensing_countdown) / self.pretensing_countdown
         }
     }
 }
+
+impl World {
+    // This world as seen by a fabric in the stage, with its overrides applied.
+    pub(crate) fn in_stage(&self, stage: Stage) -> Cow<'_, World> {
+        let overrides = &self.stage_features[stage as usize];
+        if overrides.is_empty() {
+            return Cow::Borrowed(self);
+        }
+        let mut staged = self.clone();
+        for &(feature, value) in overrides {
+            staged.set_float_value(feature, value);
+        }
+        Cow::Owned(staged)
+    }
+
+    fn add_obstacle(&mut self, obstacle: Obstacle) -> Result<usize, Error> {
+        let finite = match obstacle {
+            Obstacle::Sphere { center, .. } => center.iter().all(|value| value.is_finite()),
+            Obstacle::Box { min, max } => {
+                min.iter().chain(max.iter()).all(|value| value.is_finite())
+            }
+            Obstacle::Plane { point, normal } => point
+                .iter()
+                .chain(normal.iter())
+                .all(|value| value.is_finite()),
+        };
+        if !finite {
+            return Err(Error::IllegalPosition {
+                position: Real::NAN,
+            });
+        }
+        self.obstacles.push(obstacle);
+        Ok(self.obstacles.len() - 1)
+    }
+
+    pub(crate) fn surface(&self) -> &dyn Surface {
+        match &self.terrain {
+            Some(heightmap) => heightmap,
+            None => &Flat,
+        }
+    }
+
+    // The acceleration at the location, for a gravity strength that may be zeroed for floating fabrics.
+    pub(crate) fn gravity_at(&self, location: &Point3<Real>, gravity: Real) -> Vector3<Real> {
+        let Some((center, radius)) = self.point_gravity else {
+            return self.gravity_direction * gravity;
+        };
+        let toward = center - location;
+        let distance = toward.magnitude();
+        if distance < Real::EPSILON {
+            return zero();
+        }
+        let strength = if distance < radius {
+            gravity * distance / radius
+        } else {
+            gravity * (radius / distance).powi(2)
+        };
+        toward * (strength / distance)
+    }
+
+    pub(crate) fn immersion(&self, altitude: Real) -> (Real, Real) {
+        if self.media.is_empty() {
+            return (0.0, 0.0);
+        }
+        immersion(&self.media, self.medium_transition, altitude)
+    }
+
+    // Straight from sample to sample, the last held for its dt like the others, then still.
+    pub(crate) fn ground_acceleration(&self, time: Real) -> Vector3<Real> {
+        let position = time / self.ground_motion_ticks;
+        let sample = position as usize;
+        let sample_count = self.ground_motion.len() / 3;
+        let at = |index: usize| {
+            Vector3::new(
+                self.ground_motion[index * 3],
+                self.ground_motion[index * 3 + 1],
+                self.ground_motion[index * 3 + 2],
+            )
+        };
+        if sample >= sample_count {
+            return zero();
+        }
+        if sample + 1 == sample_count {
+            return at(sample);
+        }
+        let nuance = position - sample as Real;
+        at(sample) * (1.0 - nuance) + at(sample + 1) * nuance
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ground_motion_plays_every_sample_then_stops() {
+        let mut world = World::new();
+        world
+            .set_ground_motion(&[0.0, 0.0, 0.0, 2.0, 0.0, 0.0], 2.0)
+            .unwrap();
+        assert_eq!(world.ground_acceleration(1.0).x, 1.0);
+        assert_eq!(world.ground_acceleration(2.0).x, 2.0);
+        assert_eq!(world.ground_acceleration(3.5).x, 2.0);
+        assert_eq!(world.ground_acceleration(4.0), Vector3::zeros());
+        assert!(world.set_ground_motion(&[0.0; 3], Real::INFINITY).is_err());
+        assert!(world.set_ground_motion(&[0.0; 4], 1.0).is_err());
+    }
+
+    #[test]
+    fn stage_overrides_apply_only_in_their_stage() {
+        let mut world = World::new();
+        let gravity = world.get_float_value(WorldFeature::Gravity);
+        world.set_stage_feature(Stage::Pretenst, WorldFeature::Gravity, 1.0);
+        world.set_stage_feature(Stage::Pretenst, WorldFeature::Gravity, 2.0);
+        assert_eq!(
+            world.get_stage_feature(Stage::Pretenst, WorldFeature::Gravity),
+            Some(2.0)
+        );
+        assert_eq!(world.in_stage(Stage::Pretenst).gravity, 2.0);
+        assert!(matches!(world.in_stage(Stage::Shaping), Cow::Borrowed(_)));
+        assert_eq!(world.get_float_value(WorldFeature::Gravity), gravity);
+        world.clear_stage_feature(Stage::Pretenst, WorldFeature::Gravity);
+        assert_eq!(world.in_stage(Stage::Pretenst).gravity, gravity);
+    }
+
+    #[test]
+    fn gravity_pulls_along_its_direction_or_toward_its_point() {
+        let mut world = World::new();
+        let somewhere = Point3::new(3.0, 2.0, 1.0);
+        assert_eq!(
+            world.gravity_at(&somewhere, 2.0),
+            Vector3::new(0.0, -2.0, 0.0)
+        );
+        assert!(world.set_gravity_direction(0.0, 0.0, 0.0).is_err());
+        world.set_gravity_direction(4.0, 0.0, 0.0).unwrap();
+        assert_eq!(
+            world.gravity_at(&somewhere, 2.0),
+            Vector3::new(2.0, 0.0, 0.0)
+        );
+        assert!(world.set_point_gravity(0.0, 0.0, 0.0, 0.0).is_err());
+        world.set_point_gravity(0.0, 0.0, 0.0, 2.0).unwrap();
+        let surface = world.gravity_at(&Point3::new(0.0, 2.0, 0.0), 1.0);
+        assert!(
+            (surface - Vector3::new(0.0, -1.0, 0.0)).magnitude() < 1e-6,
+            "{}",
+            surface
+        );
+        let outside = world.gravity_at(&Point3::new(4.0, 0.0, 0.0), 1.0);
+        assert!(
+            (outside - Vector3::new(-0.25, 0.0, 0.0)).magnitude() < 1e-6,
+            "{}",
+            outside
+        );
+        let inside = world.gravity_at(&Point3::new(0.0, 0.0, -1.0), 1.0);
+        assert!(
+            (inside - Vector3::new(0.0, 0.0, 0.5)).magnitude() < 1e-6,
+            "{}",
+            inside
+        );
+        assert_eq!(world.gravity_at(&Point3::origin(), 1.0), Vector3::zeros());
+        world.clear_point_gravity();
+        assert_eq!(
+            world.gravity_at(&somewhere, 2.0),
+            Vector3::new(2.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn role_damping_is_set_per_role_and_never_negative() {
+        let mut world = World::new();
+        assert!(matches!(
+            world.set_role_damping(IntervalRole::Pull, -0.1),
+            Err(Error::IllegalDamping { .. })
+        ));
+        assert!(world
+            .set_role_damping(IntervalRole::Pull, Real::NAN)
+            .is_err());
+        world.set_role_damping(IntervalRole::Pull, 0.2).unwrap();
+        assert_eq!((world.push_damping, world.pull_damping), (0.0, 0.2));
+        assert_eq!(
+            world
+                .role_defaults(IntervalRole::Pull, Stage::Pretenst)
+                .damping,
+            0.2
+        );
+        assert_eq!(
+            world
+                .role_defaults(IntervalRole::Pull, Stage::Slack)
+                .damping,
+            0.0
+        );
+    }
+
+    #[test]
+    fn yield_strains_and_flows_come_back_in_their_own_errors() {
+        let mut world = World::new();
+        assert!(
+            matches!(world.set_yield_strain(IntervalRole::Pull, -0.1, 0.5), Err(Error::IllegalStrain { strain }) if strain == -0.1)
+        );
+        assert!(
+            matches!(world.set_yield_strain(IntervalRole::Pull, 0.1, 1.5), Err(Error::IllegalFlow { flow }) if flow == 1.5)
+        );
+        assert!(
+            matches!(world.set_yield_strain(IntervalRole::Pull, 0.1, -0.5), Err(Error::IllegalFlow { flow }) if flow == -0.5)
+        );
+        world
+            .set_yield_strain(IntervalRole::Pull, 0.1, 0.5)
+            .unwrap();
+        assert_eq!(world.plastic_yield[IntervalRole::Pull as usize], [0.1, 0.5]);
+    }
+
+    #[test]
+    fn alarm_bands_must_be_in_order_and_never_negative() {
+        let mut world = World::new();
+        for (warning, critical) in [(-0.1, 0.3), (0.1, Real::NAN), (0.3, 0.1)] {
+            let result = world.set_strain_alarm(IntervalRole::Pull, warning, critical);
+            assert!(
+                matches!(result, Err(Error::IllegalRange { .. })),
+                "{} {}",
+                warning,
+                critical
+            );
+        }
+        world
+            .set_strain_alarm(IntervalRole::Pull, 0.3, 0.0)
+            .unwrap();
+        world
+            .set_strain_alarm(IntervalRole::Push, 0.1, 0.3)
+            .unwrap();
+        assert_eq!(world.strain_alarms, [[0.1, 0.3], [0.3, 0.0]]);
+        assert!(matches!(
+            world.set_alarm_hysteresis(-0.01),
+            Err(Error::IllegalStrain { .. })
+        ));
+        assert!(world.set_alarm_hysteresis(Real::INFINITY).is_err());
+        world.set_alarm_hysteresis(0.01).unwrap();
+        assert_eq!(world.alarm_hysteresis, 0.01);
+    }
+
+    #[test]
+    fn active_damping_runs_from_zero_to_one() {
+        let mut world = World::new();
+        for aggressiveness in [-0.1, 1.5, Real::NAN] {
+            assert!(
+                matches!(
+                    world.set_active_damping(aggressiveness),
+                    Err(Error::IllegalDamping { .. })
+                ),
+                "{}",
+                aggressiveness
+            );
+        }
+        assert_eq!(world.active_damping, 0.0);
+        world.set_active_damping(1.0).unwrap();
+        assert_eq!(world.active_damping, 1.0);
+    }
+
+    #[test]
+    fn sleep_thresholds_and_speed_limits_are_never_negative() {
+        let mut world = World::new();
+        assert!(matches!(
+            world.set_sleep(-1e-3, 1e-3, 10),
+            Err(Error::IllegalSpeed { .. })
+        ));
+        assert!(matches!(
+            world.set_sleep(1e-3, Real::NAN, 10),
+            Err(Error::IllegalForce { .. })
+        ));
+        assert_eq!((world.sleep_velocity, world.sleep_force), (0.0, 0.0));
+        world.set_sleep(1e-3, 2e-3, 10).unwrap();
+        assert_eq!(
+            (world.sleep_velocity, world.sleep_force, world.sleep_ticks),
+            (1e-3, 2e-3, 10)
+        );
+        assert!(matches!(
+            world.set_speed_limit(Stage::Pretenst, -0.01),
+            Err(Error::IllegalSpeed { .. })
+        ));
+        assert_eq!(world.get_speed_limit(Stage::Pretenst), 0.0);
+        world.set_speed_limit(Stage::Pretenst, 0.01).unwrap();
+        assert_eq!(world.get_speed_limit(Stage::Pretenst), 0.01);
+    }
+}